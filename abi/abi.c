@@ -0,0 +1,27 @@
+/*
+ * Reports the layout of the Zydis structs mirrored on the Rust side, so
+ * `zydis::verify_abi` can detect drift between both.
+ */
+
+#include <stddef.h>
+#include <Zydis/Decoder.h>
+#include <Zydis/Encoder.h>
+#include <Zydis/Formatter.h>
+
+#define ZYDIS_RS_LAYOUT(name, type)                                  \
+    typedef struct ZydisRsAlignOf##name##_                           \
+    {                                                                \
+        char c;                                                      \
+        type t;                                                      \
+    } ZydisRsAlignOf##name;                                          \
+                                                                     \
+    void ZydisRsLayoutOf##name(ZyanUSize* size, ZyanUSize* align)    \
+    {                                                                \
+        *size = sizeof(type);                                        \
+        *align = offsetof(ZydisRsAlignOf##name, t);                  \
+    }
+
+ZYDIS_RS_LAYOUT(DecodedInstruction, ZydisDecodedInstruction)
+ZYDIS_RS_LAYOUT(DecodedOperand, ZydisDecodedOperand)
+ZYDIS_RS_LAYOUT(EncoderRequest, ZydisEncoderRequest)
+ZYDIS_RS_LAYOUT(Formatter, ZydisFormatter)
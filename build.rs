@@ -1,4 +1,11 @@
-use std::env;
+// Build scripts report failures by panicking, so the `check_panics` CI job
+// only applies to the library.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
 fn bool2cmake(x: bool) -> &'static str {
     if x {
@@ -8,7 +15,35 @@ fn bool2cmake(x: bool) -> &'static str {
     }
 }
 
-fn build_library() {
+fn is_msvc() -> bool {
+    let target = env::var("TARGET").unwrap_or("(unknown)".to_string());
+    target.ends_with("windows-msvc")
+}
+
+/// Applies the `nolibc` and `no-stack-protector` features to a C build.
+///
+/// This only affects the C code. Functions the compiler may still emit calls
+/// to, e.g. `memcpy` and `memset`, must be provided by the environment.
+fn configure_runtime(config: &mut cmake::Config) {
+    let nolibc = env::var("CARGO_FEATURE_NOLIBC").is_ok();
+    config.define("ZYAN_NO_LIBC", bool2cmake(nolibc));
+
+    // Without libc, the compiler must not assume a hosted environment, e.g.
+    // by turning formatting code into `printf` style calls.
+    if nolibc && !is_msvc() {
+        config.cflag("-ffreestanding");
+    }
+
+    if env::var("CARGO_FEATURE_NO_STACK_PROTECTOR").is_ok() {
+        if is_msvc() {
+            config.cflag("/GS-");
+        } else {
+            config.cflag("-fno-stack-protector");
+        }
+    }
+}
+
+fn build_library() -> PathBuf {
     let mut config = cmake::Config::new("zydis-c");
 
     config
@@ -30,24 +65,10 @@ fn build_library() {
         "ZYDIS_FEATURE_ENCODER",
         bool2cmake(env::var("CARGO_FEATURE_ENCODER").is_ok()),
     );
-    config.define(
-        "ZYAN_NO_LIBC",
-        bool2cmake(env::var("CARGO_FEATURE_NOLIBC").is_ok()),
-    );
-
-    let target = env::var("TARGET").unwrap_or("(unknown)".to_string());
-    let is_msvc = target.ends_with("windows-msvc");
-
-    if env::var("CARGO_FEATURE_NO_STACK_PROTECTOR").is_ok() {
-        if is_msvc {
-            config.cflag("/GS-");
-        } else {
-            config.cflag("-fno-stack-protector");
-        }
-    }
+    configure_runtime(&mut config);
 
     let dst = config.build();
-    let relative_build_dir = if is_msvc { config.get_profile() } else { "" };
+    let relative_build_dir = if is_msvc() { config.get_profile() } else { "" };
 
     println!(
         "cargo:rustc-link-search=native={}/build/{}",
@@ -62,10 +83,38 @@ fn build_library() {
 
     println!("cargo:rustc-link-lib=static=Zydis");
     println!("cargo:rustc-link-lib=static=Zycore");
+
+    dst
+}
+
+/// Builds the helpers reporting the C struct layouts for `verify_abi`.
+fn build_abi_helpers(zydis_dst: &Path) {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap()).join("abi");
+    let mut config = cmake::Config::new("abi");
+
+    config
+        .out_dir(&out_dir)
+        .build_target("ZydisRsAbi")
+        .define("ZYDIS_SOURCE_DIR", manifest_dir.join("zydis-c"))
+        .define("ZYDIS_BINARY_DIR", zydis_dst.join("build"));
+    configure_runtime(&mut config);
+
+    let dst = config.build();
+    let relative_build_dir = if is_msvc() { config.get_profile() } else { "" };
+
+    println!(
+        "cargo:rustc-link-search=native={}/build/{}",
+        dst.display(),
+        relative_build_dir
+    );
+    println!("cargo:rustc-link-lib=static=ZydisRsAbi");
 }
 
 fn main() {
     println!("cargo:rerun-if-changed=zydis-c");
+    println!("cargo:rerun-if-changed=abi");
 
-    build_library();
+    let zydis_dst = build_library();
+    build_abi_helpers(&zydis_dst);
 }
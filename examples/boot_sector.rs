@@ -0,0 +1,31 @@
+use zydis::*;
+
+/// Start of a boot sector printing `A` via the BIOS and halting.
+#[rustfmt::skip]
+static CODE: &[u8] = &[
+    0xFA,             // cli
+    0x31, 0xC0,       // xor ax, ax
+    0x8E, 0xD8,       // mov ds, ax
+    0x8E, 0xD0,       // mov ss, ax
+    0xBC, 0x00, 0x7C, // mov sp, 0x7C00
+    0xFB,             // sti
+    0xB4, 0x0E,       // mov ah, 0x0E
+    0xB0, 0x41,       // mov al, 0x41
+    0xCD, 0x10,       // int 0x10
+    0xEB, 0xFE,       // jmp $
+];
+
+/// The BIOS loads the boot sector to this address.
+const LOAD_ADDRESS: u64 = 0x7C00;
+
+fn main() -> Result<()> {
+    let fmt = Formatter::intel();
+    let decoder = Decoder::new_real16();
+
+    for item in decoder.decode_all::<VisibleOperands>(CODE, LOAD_ADDRESS) {
+        let (ip, _raw_bytes, insn) = item?;
+        println!("0000:{:04X} {}", ip, fmt.format(Some(ip), &insn)?);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Smoke test for freestanding environments (kernel drivers, UEFI, ...).
+//!
+//! Built as a `#![no_std]` library without `alloc`:
+//!
+//! ```text
+//! cargo build --example freestanding --no-default-features --features freestanding,formatter
+//! ```
+
+#![no_std]
+
+use zydis::*;
+
+static DECODER: Decoder = Decoder::new64();
+
+/// Formats the first instruction in `code` into `out` as a NUL terminated
+/// string, returning the instruction length or 0 on failure.
+///
+/// # Safety
+///
+/// `code` and `out` must be valid for `code_len` and `out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn format_first_instruction(
+    code: *const u8,
+    code_len: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> usize {
+    let code = core::slice::from_raw_parts(code, code_len);
+    let out = core::slice::from_raw_parts_mut(out, out_len);
+
+    let Ok(Some(insn)) = DECODER.decode_first::<VisibleOperands>(code) else {
+        return 0;
+    };
+
+    let formatter = Formatter::intel();
+    let mut buffer = OutputBuffer::new(out);
+    match formatter.format_ex(None, &insn, &mut buffer, None) {
+        Ok(()) => usize::from(insn.length),
+        Err(_) => 0,
+    }
+}
@@ -0,0 +1,91 @@
+//! Runtime verification of the FFI struct layouts.
+
+use crate::ffi;
+use core::{alloc::Layout, fmt};
+
+/// A struct whose Rust mirror doesn't match the layout in the C library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbiMismatch {
+    /// Name of the C struct.
+    pub name: &'static str,
+    /// Layout of the Rust mirror.
+    pub rust: Layout,
+    /// Size reported by the C library.
+    pub c_size: usize,
+    /// Alignment reported by the C library, not necessarily a valid one.
+    pub c_align: usize,
+}
+
+impl fmt::Display for AbiMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "layout mismatch for {}: Rust has size {} and alignment {}, C has size {} and \
+             alignment {}",
+            self.name,
+            self.rust.size(),
+            self.rust.align(),
+            self.c_size,
+            self.c_align,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AbiMismatch {}
+
+type LayoutFn = unsafe extern "C" fn(*mut usize, *mut usize);
+
+/// Checks that the size and alignment of the Rust mirrors of the main C
+/// structs match the ones declared by the vendored Zydis headers.
+///
+/// The layouts are reported by helpers compiled against those headers by
+/// the build script. A mismatch means the bindings are out of sync with the
+/// vendored C library and using them would corrupt memory.
+///
+/// # Examples
+/// ```
+/// zydis::verify_abi().unwrap();
+/// ```
+pub fn verify_abi() -> Result<(), AbiMismatch> {
+    let checks: &[(&'static str, Layout, LayoutFn)] = &[
+        (
+            "ZydisDecodedInstruction",
+            Layout::new::<ffi::DecodedInstruction>(),
+            ffi::ZydisRsLayoutOfDecodedInstruction,
+        ),
+        (
+            "ZydisDecodedOperand",
+            Layout::new::<ffi::DecodedOperand>(),
+            ffi::ZydisRsLayoutOfDecodedOperand,
+        ),
+        #[cfg(feature = "encoder")]
+        (
+            "ZydisEncoderRequest",
+            Layout::new::<ffi::EncoderRequest>(),
+            ffi::ZydisRsLayoutOfEncoderRequest,
+        ),
+        #[cfg(feature = "formatter")]
+        (
+            "ZydisFormatter",
+            Layout::new::<ffi::Formatter>(),
+            ffi::ZydisRsLayoutOfFormatter,
+        ),
+    ];
+
+    for &(name, rust, layout_of) in checks {
+        let (mut size, mut align) = (0, 0);
+        unsafe { layout_of(&mut size, &mut align) };
+
+        if (size, align) != (rust.size(), rust.align()) {
+            return Err(AbiMismatch {
+                name,
+                rust,
+                c_size: size,
+                c_align: align,
+            });
+        }
+    }
+
+    Ok(())
+}
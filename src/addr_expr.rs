@@ -0,0 +1,181 @@
+//! Effective address expressions of memory operands.
+
+use crate::*;
+use core::fmt;
+
+/// The effective address expression `segment:[base+index*scale+disp]` of a
+/// memory operand.
+///
+/// Created via [`ffi::MemoryInfo::effective_address_expr`]. The [`Display`]
+/// implementation uses Intel syntax, e.g. `fs:[rax+rcx*4-0x10]`.
+///
+/// [`Display`]: fmt::Display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AddrExpr {
+    /// Segment register, if any.
+    pub segment: Option<Register>,
+    /// Base register, if any.
+    pub base: Option<Register>,
+    /// Index register and scale factor, if any.
+    pub index: Option<(Register, u8)>,
+    /// Signed displacement, `0` if absent.
+    pub disp: i64,
+}
+
+impl AddrExpr {
+    /// Whether the address is given by the displacement alone.
+    #[inline]
+    pub fn is_absolute(&self) -> bool {
+        self.base.is_none() && self.index.is_none()
+    }
+
+    /// Writes the expression in the syntax of the given formatter style.
+    ///
+    /// Both Intel styles use `seg:[base+index*scale+disp]`, AT&T uses
+    /// `%seg:disp(%base,%index,scale)`.
+    pub fn write_with(&self, style: FormatterStyle, f: &mut impl fmt::Write) -> fmt::Result {
+        if style == FormatterStyle::ATT {
+            return self.write_att(f);
+        }
+
+        if let Some(segment) = self.segment {
+            write!(f, "{segment}:")?;
+        }
+
+        f.write_str("[")?;
+        if let Some(base) = self.base {
+            write!(f, "{base}")?;
+        }
+        if let Some((index, scale)) = self.index {
+            if self.base.is_some() {
+                f.write_str("+")?;
+            }
+            write!(f, "{index}*{scale}")?;
+        }
+
+        if self.is_absolute() {
+            write!(f, "0x{:X}", self.disp as u64)?;
+        } else if self.disp != 0 {
+            let sign = if self.disp < 0 { '-' } else { '+' };
+            write!(f, "{sign}0x{:02X}", self.disp.unsigned_abs())?;
+        }
+        f.write_str("]")
+    }
+
+    fn write_att(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if let Some(segment) = self.segment {
+            write!(f, "%{segment}:")?;
+        }
+
+        if self.is_absolute() {
+            return write!(f, "0x{:X}", self.disp as u64);
+        }
+
+        if self.disp < 0 {
+            write!(f, "-0x{:02X}", self.disp.unsigned_abs())?;
+        } else if self.disp != 0 {
+            write!(f, "0x{:02X}", self.disp)?;
+        }
+
+        f.write_str("(")?;
+        if let Some(base) = self.base {
+            write!(f, "%{base}")?;
+        }
+        if let Some((index, scale)) = self.index {
+            write!(f, ",%{index},{scale}")?;
+        }
+        f.write_str(")")
+    }
+
+    /// Formats the expression in the syntax of the given formatter style.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let expr = AddrExpr {
+    ///     segment: Some(Register::FS),
+    ///     base: Some(Register::RAX),
+    ///     index: Some((Register::RCX, 4)),
+    ///     disp: -0x10,
+    /// };
+    ///
+    /// assert_eq!(expr.to_string(), "fs:[rax+rcx*4-0x10]");
+    /// assert_eq!(
+    ///     expr.to_string_with(FormatterStyle::ATT),
+    ///     "%fs:-0x10(%rax,%rcx,4)"
+    /// );
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_string_with(&self, style: FormatterStyle) -> alloc::string::String {
+        use alloc::string::ToString;
+        Styled(self, style).to_string()
+    }
+}
+
+/// Displays an [`AddrExpr`] in the given style.
+#[cfg(feature = "alloc")]
+struct Styled<'a>(&'a AddrExpr, FormatterStyle);
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for Styled<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.write_with(self.1, f)
+    }
+}
+
+impl fmt::Display for AddrExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(FormatterStyle::INTEL, f)
+    }
+}
+
+impl ffi::MemoryInfo {
+    /// Returns the effective address expression of this memory operand.
+    ///
+    /// The segment is always included, even if it's the default one. For
+    /// `MIB` operands, the index register isn't part of the address and is
+    /// omitted.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // mov eax, dword ptr [rbx+rsi*8+0x20]
+    /// static CODE: &[u8] = &[0x8B, 0x44, 0xF3, 0x20];
+    ///
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(CODE)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let ffi::DecodedOperandKind::Mem(mem) = &insn.operands()[1].kind else {
+    ///     unreachable!()
+    /// };
+    ///
+    /// let expr = mem.effective_address_expr();
+    /// assert_eq!(expr.index, Some((Register::RSI, 8)));
+    /// assert_eq!(expr.to_string(), "ds:[rbx+rsi*8+0x20]");
+    /// ```
+    pub fn effective_address_expr(&self) -> AddrExpr {
+        let reg = |reg: Register| (reg != Register::NONE).then_some(reg);
+        AddrExpr {
+            segment: reg(self.segment),
+            base: reg(self.base),
+            index: reg(self.index)
+                .filter(|_| self.ty != MemoryOperandType::MIB)
+                .map(|index| (index, self.scale)),
+            disp: if self.disp.has_displacement {
+                self.disp.displacement
+            } else {
+                0
+            },
+        }
+    }
+
+    /// Formats the effective address expression of this memory operand in the
+    /// syntax of the given formatter style.
+    ///
+    /// See [`MemoryInfo::effective_address_expr`](Self::effective_address_expr).
+    #[cfg(feature = "alloc")]
+    pub fn to_string_with(&self, style: FormatterStyle) -> alloc::string::String {
+        self.effective_address_expr().to_string_with(style)
+    }
+}
@@ -0,0 +1,194 @@
+//! Sparse address spaces made up of separately mapped regions.
+
+use crate::*;
+use alloc::{collections::BTreeMap, vec::Vec};
+use bitflags::bitflags;
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+bitflags! {
+    /// Access permissions of a [`Region`].
+    #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct Permissions: u8 {
+        const READ = 1 << 0;
+        const WRITE = 1 << 1;
+        const EXECUTE = 1 << 2;
+
+        const READ_EXECUTE = Self::READ.bits() | Self::EXECUTE.bits();
+        const READ_WRITE = Self::READ.bits() | Self::WRITE.bits();
+    }
+}
+
+/// A contiguous range of bytes mapped into an [`AddressSpace`].
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Region {
+    /// Address of the first byte.
+    pub start: u64,
+    /// The contents.
+    pub bytes: Vec<u8>,
+    /// The access permissions.
+    pub permissions: Permissions,
+}
+
+impl Region {
+    /// The address directly following the last byte.
+    #[inline]
+    pub fn end(&self) -> u64 {
+        self.start + self.bytes.len() as u64
+    }
+
+    /// Checks whether `addr` lies within this region.
+    #[inline]
+    pub fn contains(&self, addr: u64) -> bool {
+        (self.start..self.end()).contains(&addr)
+    }
+}
+
+/// Virtual address ranges mapped to separate buffers, e.g. the sections of
+/// an executable or the pages of a runtime memory dump.
+///
+/// Implements [`Memory`], so it can be decoded from directly, without
+/// flattening it into a single buffer. Instructions crossing adjacent
+/// regions are decoded as a whole.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// let mut space = AddressSpace::new();
+/// // .text: call [rip+0xFFA]; ret
+/// space
+///     .map(0x1000, vec![0xFF, 0x15, 0xFA, 0x0F, 0x00, 0x00, 0xC3], Permissions::READ_EXECUTE)
+///     .unwrap();
+/// // .data
+/// space.map(0x2000, vec![0; 8], Permissions::READ_WRITE).unwrap();
+///
+/// assert_eq!(space.region(0x2004).unwrap().start, 0x2000);
+/// assert_eq!(space.map(0x1004, vec![0; 16], Permissions::READ), Err(Status::InvalidArgument));
+///
+/// let decoder = Decoder::new64();
+/// let code = space.restrict(Permissions::EXECUTE);
+/// let mnemonics = decoder
+///     .decode_all_memory::<NoOperands, _>(&code, 0x1000)
+///     .map(|r| r.map(|(_, insn)| insn.mnemonic))
+///     .collect::<Result<Vec<_>>>()
+///     .unwrap();
+/// assert_eq!(mnemonics, [Mnemonic::CALL, Mnemonic::RET]);
+///
+/// // Data isn't executable.
+/// assert!(decoder.decode_memory::<NoOperands>(&code, 0x2000).unwrap().is_none());
+/// ```
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AddressSpace {
+    regions: BTreeMap<u64, Region>,
+}
+
+impl AddressSpace {
+    /// Creates an empty address space.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps `bytes` at `start`.
+    ///
+    /// Returns [`Status::InvalidArgument`] if `bytes` is empty, overlaps an
+    /// already mapped region or exceeds the end of the address space.
+    pub fn map(&mut self, start: u64, bytes: Vec<u8>, permissions: Permissions) -> Result<()> {
+        let end = start
+            .checked_add(bytes.len() as u64)
+            .filter(|&end| end > start)
+            .ok_or(Status::InvalidArgument)?;
+
+        let overlaps_prev = self
+            .regions
+            .range(..start)
+            .next_back()
+            .is_some_and(|(_, prev)| prev.end() > start);
+        let overlaps_next = self
+            .regions
+            .range(start..)
+            .next()
+            .is_some_and(|(&next, _)| next < end);
+        if overlaps_prev || overlaps_next {
+            return Err(Status::InvalidArgument);
+        }
+
+        self.regions.insert(
+            start,
+            Region {
+                start,
+                bytes,
+                permissions,
+            },
+        );
+        Ok(())
+    }
+
+    /// Removes the region starting at `start`, returning it.
+    #[inline]
+    pub fn unmap(&mut self, start: u64) -> Option<Region> {
+        self.regions.remove(&start)
+    }
+
+    /// Returns the region containing `addr`.
+    pub fn region(&self, addr: u64) -> Option<&Region> {
+        let (_, region) = self.regions.range(..=addr).next_back()?;
+        region.contains(addr).then_some(region)
+    }
+
+    /// Returns the contents of the region containing `addr` for
+    /// modification.
+    pub fn region_bytes_mut(&mut self, addr: u64) -> Option<&mut [u8]> {
+        let (_, region) = self.regions.range_mut(..=addr).next_back()?;
+        region.contains(addr).then_some(&mut region.bytes[..])
+    }
+
+    /// Returns an iterator over all regions, ordered by address.
+    #[inline]
+    pub fn regions(&self) -> impl Iterator<Item = &Region> + '_ {
+        self.regions.values()
+    }
+
+    /// Returns a view only exposing the regions with all of the given
+    /// permissions, e.g. to make sure only executable code is decoded.
+    #[inline]
+    pub fn restrict(&self, required: Permissions) -> RestrictedAddressSpace<'_> {
+        RestrictedAddressSpace {
+            space: self,
+            required,
+        }
+    }
+}
+
+impl Memory for AddressSpace {
+    fn read(&self, addr: u64, buf: &mut [u8]) -> Result<usize> {
+        match self.region(addr) {
+            Some(region) => region.bytes.read(addr - region.start, buf),
+            None => Ok(0),
+        }
+    }
+}
+
+/// View of an [`AddressSpace`] limited to regions with certain permissions.
+///
+/// Created via [`AddressSpace::restrict`].
+#[derive(Debug, Clone, Copy)]
+pub struct RestrictedAddressSpace<'space> {
+    space: &'space AddressSpace,
+    required: Permissions,
+}
+
+impl Memory for RestrictedAddressSpace<'_> {
+    fn read(&self, addr: u64, buf: &mut [u8]) -> Result<usize> {
+        match self.space.region(addr) {
+            Some(region) if region.permissions.contains(self.required) => {
+                region.bytes.read(addr - region.start, buf)
+            }
+            _ => Ok(0),
+        }
+    }
+}
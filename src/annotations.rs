@@ -0,0 +1,134 @@
+//! Labels, comments and user data attached to addresses.
+
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::ops::RangeBounds;
+
+/// Everything attached to a single address, see [`AnnotationMap`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Annotation<T> {
+    /// Name of the address, e.g. a function or jump target.
+    pub label: Option<String>,
+    /// Comments, in the order they were added.
+    pub comments: Vec<String>,
+    /// Arbitrary user metadata.
+    pub data: Option<T>,
+}
+
+impl<T> Default for Annotation<T> {
+    fn default() -> Self {
+        Self {
+            label: None,
+            comments: Vec::new(),
+            data: None,
+        }
+    }
+}
+
+/// Annotations of disassembly output, keyed by address.
+///
+/// The listing and HTML renderers interleave labels and comments with the
+/// instructions they belong to, see `listing::hexdump_annotated` and
+/// `html::write_annotated_report`. Annotations at addresses that aren't the
+/// start of an instruction aren't rendered. The user data `T` is never
+/// rendered, it's carried along for analyses.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// let mut annotations = AnnotationMap::<u32>::new();
+/// annotations.set_label(0x1000, "main");
+/// annotations.add_comment(0x1000, "entry point");
+/// annotations.set_data(0x1004, 42);
+///
+/// assert_eq!(annotations.label(0x1000), Some("main"));
+/// assert_eq!(annotations.comments(0x1000), ["entry point"]);
+/// assert_eq!(annotations.data(0x1004), Some(&42));
+/// assert_eq!(annotations.iter().count(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnnotationMap<T = ()> {
+    entries: BTreeMap<u64, Annotation<T>>,
+}
+
+impl<T> Default for AnnotationMap<T> {
+    fn default() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> AnnotationMap<T> {
+    /// Creates an empty map.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the annotation at `addr`, if any.
+    #[inline]
+    pub fn get(&self, addr: u64) -> Option<&Annotation<T>> {
+        self.entries.get(&addr)
+    }
+
+    /// Returns the annotation at `addr`, creating an empty one if needed.
+    #[inline]
+    pub fn entry(&mut self, addr: u64) -> &mut Annotation<T> {
+        self.entries.entry(addr).or_default()
+    }
+
+    /// Removes and returns the annotation at `addr`.
+    #[inline]
+    pub fn remove(&mut self, addr: u64) -> Option<Annotation<T>> {
+        self.entries.remove(&addr)
+    }
+
+    /// Sets the label of `addr`, replacing any previous one.
+    pub fn set_label(&mut self, addr: u64, label: impl Into<String>) {
+        self.entry(addr).label = Some(label.into());
+    }
+
+    /// Appends a comment to `addr`.
+    pub fn add_comment(&mut self, addr: u64, comment: impl Into<String>) {
+        self.entry(addr).comments.push(comment.into());
+    }
+
+    /// Sets the user data of `addr`, returning the previous value.
+    pub fn set_data(&mut self, addr: u64, data: T) -> Option<T> {
+        self.entry(addr).data.replace(data)
+    }
+
+    /// Returns the label of `addr`.
+    pub fn label(&self, addr: u64) -> Option<&str> {
+        self.get(addr)?.label.as_deref()
+    }
+
+    /// Returns the comments of `addr`.
+    pub fn comments(&self, addr: u64) -> &[String] {
+        self.get(addr)
+            .map_or(&[], |annotation| &annotation.comments)
+    }
+
+    /// Returns the user data of `addr`.
+    pub fn data(&self, addr: u64) -> Option<&T> {
+        self.get(addr)?.data.as_ref()
+    }
+
+    /// Returns an iterator over all annotated addresses, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &Annotation<T>)> + '_ {
+        self.entries
+            .iter()
+            .map(|(&addr, annotation)| (addr, annotation))
+    }
+
+    /// Returns an iterator over the annotated addresses within `range`, in
+    /// ascending order.
+    pub fn range(
+        &self,
+        range: impl RangeBounds<u64>,
+    ) -> impl Iterator<Item = (u64, &Annotation<T>)> + '_ {
+        self.entries
+            .range(range)
+            .map(|(&addr, annotation)| (addr, annotation))
+    }
+}
@@ -0,0 +1,241 @@
+//! Architecture-neutral decoder and instruction traits.
+//!
+//! Frameworks supporting multiple architectures can be written against
+//! [`ArchDecoder`] and [`ArchInstruction`], with this crate providing the x86
+//! implementation.
+
+use crate::*;
+use core::fmt;
+
+/// How an instruction affects control flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlowKind {
+    /// Execution continues with the next instruction.
+    Sequential,
+    /// Unconditional jump.
+    Jump,
+    /// Conditional jump, falling through if not taken.
+    ConditionalJump,
+    /// Call, assumed to return to the next instruction.
+    Call,
+    /// Return from a function or system call.
+    Return,
+    /// Execution stops, e.g. `hlt` or `ud2`.
+    Stop,
+}
+
+/// The kind and location of an operand, see [`OperandSummary`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperandSummaryKind<R> {
+    /// A register.
+    Register(R),
+    /// A memory location addressed by `base + index * scale + displacement`.
+    Memory {
+        /// The base register, if any.
+        base: Option<R>,
+        /// The index register, if any.
+        index: Option<R>,
+        /// The scale applied to the index register, or `0` without index.
+        scale: u8,
+        /// The displacement, or `0` without displacement.
+        displacement: i64,
+    },
+    /// An immediate, sign-extended to 64 bits if signed.
+    Immediate(u64),
+    /// Any other operand, e.g. an x86 far pointer.
+    Other,
+}
+
+/// An architecture-neutral summary of an operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OperandSummary<R> {
+    /// The kind and location of the operand.
+    pub kind: OperandSummaryKind<R>,
+    /// The size of the operand, in bits.
+    pub size: u16,
+    /// Whether the operand is (possibly) read.
+    pub read: bool,
+    /// Whether the operand is (possibly) written.
+    pub written: bool,
+}
+
+/// A decoded instruction of any architecture.
+pub trait ArchInstruction {
+    /// The register type of the architecture.
+    type Register: Copy + Eq + fmt::Debug;
+
+    /// Returns the length of the instruction, in bytes.
+    fn length(&self) -> usize;
+
+    /// Returns the lowercase mnemonic, e.g. `"mov"`.
+    fn mnemonic_str(&self) -> &str;
+
+    /// Returns how the instruction affects control flow.
+    fn flow_kind(&self) -> FlowKind;
+
+    /// Returns the statically known target of a jump or call, using `ip` as
+    /// the address of the instruction.
+    fn branch_target(&self, ip: u64) -> Option<u64>;
+
+    /// Returns a summary of the operand at `index`, or `None` if there's no
+    /// such operand.
+    fn operand_summary(&self, index: usize) -> Option<OperandSummary<Self::Register>>;
+
+    /// Returns an iterator over the operand summaries.
+    fn operand_summaries(&self) -> OperandSummaries<'_, Self> {
+        OperandSummaries {
+            insn: self,
+            index: 0,
+        }
+    }
+}
+
+/// Iterator created by [`ArchInstruction::operand_summaries`].
+pub struct OperandSummaries<'a, I: ArchInstruction + ?Sized> {
+    insn: &'a I,
+    index: usize,
+}
+
+impl<I: ArchInstruction + ?Sized> Iterator for OperandSummaries<'_, I> {
+    type Item = OperandSummary<I::Register>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let summary = self.insn.operand_summary(self.index)?;
+        self.index += 1;
+        Some(summary)
+    }
+}
+
+/// A decoder of any architecture.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// fn describe<D: ArchDecoder>(decoder: &D, code: &[u8]) -> (usize, FlowKind, Option<u64>) {
+///     let insn = decoder.decode_instruction(code).unwrap().unwrap();
+///     (insn.length(), insn.flow_kind(), insn.branch_target(0x1000))
+/// }
+///
+/// let decoder = Decoder::new64();
+/// // jz 0x1010
+/// assert_eq!(
+///     describe(&decoder, &[0x74, 0x0E]),
+///     (2, FlowKind::ConditionalJump, Some(0x1010))
+/// );
+/// // call rax
+/// assert_eq!(describe(&decoder, &[0xFF, 0xD0]), (2, FlowKind::Call, None));
+///
+/// // add eax, [rbx+0x10]
+/// let insn = decoder
+///     .decode_instruction(&[0x03, 0x43, 0x10])
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(insn.mnemonic_str(), "add");
+/// let operands: Vec<_> = insn.operand_summaries().map(|op| op.kind).collect();
+/// assert_eq!(
+///     operands,
+///     [
+///         OperandSummaryKind::Register(Register::EAX),
+///         OperandSummaryKind::Memory {
+///             base: Some(Register::RBX),
+///             index: None,
+///             scale: 0,
+///             displacement: 0x10,
+///         },
+///     ]
+/// );
+/// ```
+pub trait ArchDecoder {
+    /// The instruction type produced by the decoder.
+    type Instruction: ArchInstruction;
+    /// The error returned for undecodable bytes.
+    type Error: fmt::Debug;
+
+    /// Returns the maximum length of an instruction, in bytes.
+    fn max_instruction_length(&self) -> usize;
+
+    /// Decodes the first instruction in `bytes`.
+    ///
+    /// Returns `Ok(None)` if `bytes` is empty.
+    fn decode_instruction(
+        &self,
+        bytes: &[u8],
+    ) -> core::result::Result<Option<Self::Instruction>, Self::Error>;
+}
+
+/// Summaries are only available for the operands stored by `O`, e.g. none
+/// for [`NoOperands`].
+impl<O: Operands> ArchInstruction for Instruction<O> {
+    type Register = Register;
+
+    #[inline]
+    fn length(&self) -> usize {
+        usize::from(self.length)
+    }
+
+    fn mnemonic_str(&self) -> &str {
+        self.mnemonic.static_string().unwrap_or("invalid")
+    }
+
+    fn flow_kind(&self) -> FlowKind {
+        match self.meta.category {
+            InstructionCategory::RET | InstructionCategory::SYSRET => FlowKind::Return,
+            InstructionCategory::CALL => FlowKind::Call,
+            InstructionCategory::UNCOND_BR => FlowKind::Jump,
+            InstructionCategory::COND_BR => FlowKind::ConditionalJump,
+            _ if self.halts_fallthrough() => FlowKind::Stop,
+            _ => FlowKind::Sequential,
+        }
+    }
+
+    fn branch_target(&self, ip: u64) -> Option<u64> {
+        self.successors(ip)
+            .iter()
+            .find_map(|successor| match successor {
+                Successor::Branch(target) => Some(*target),
+                _ => None,
+            })
+    }
+
+    fn operand_summary(&self, index: usize) -> Option<OperandSummary<Register>> {
+        let op = self.operands().get(index)?;
+        let register = |reg: Register| (reg != Register::NONE).then_some(reg);
+        let kind = match &op.kind {
+            ffi::DecodedOperandKind::Reg(reg) => OperandSummaryKind::Register(*reg),
+            ffi::DecodedOperandKind::Mem(mem) => OperandSummaryKind::Memory {
+                base: register(mem.base),
+                index: register(mem.index),
+                scale: mem.scale,
+                displacement: mem.disp.displacement,
+            },
+            ffi::DecodedOperandKind::Imm(imm) => OperandSummaryKind::Immediate(imm.value),
+            ffi::DecodedOperandKind::Ptr(_) | ffi::DecodedOperandKind::Unused => {
+                OperandSummaryKind::Other
+            }
+        };
+
+        Some(OperandSummary {
+            kind,
+            size: op.size,
+            read: op.action.intersects(OperandAction::MASK_READ),
+            written: op.action.intersects(OperandAction::MASK_WRITE),
+        })
+    }
+}
+
+/// Decodes instructions with their visible operands.
+#[cfg(feature = "full-decoder")]
+impl ArchDecoder for Decoder {
+    type Error = Status;
+    type Instruction = Instruction<VisibleOperands>;
+
+    #[inline]
+    fn max_instruction_length(&self) -> usize {
+        MAX_INSTRUCTION_LENGTH
+    }
+
+    #[inline]
+    fn decode_instruction(&self, bytes: &[u8]) -> Result<Option<Self::Instruction>> {
+        self.decode_first(bytes)
+    }
+}
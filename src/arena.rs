@@ -0,0 +1,195 @@
+//! Arena-backed operand storage for bulk decoding.
+
+use crate::*;
+use alloc::boxed::Box;
+use bumpalo::Bump;
+use core::{alloc::Layout, fmt, hash, ptr::NonNull, slice};
+
+/// Operand storage referencing operands allocated in a [`Bump`] arena.
+///
+/// [`AllOperands`] stores room for [`MAX_OPERAND_COUNT`] operands inline in
+/// every [`Instruction`], even though most instructions only have a few.
+/// Instructions decoded via [`Decoder::decode_first_in`] or
+/// [`Decoder::decode_all_in`] instead allocate exactly the operands they have
+/// in the given arena, keeping the instructions themselves small.
+///
+/// All (visible and hidden) operands are decoded. When decoded through the
+/// regular [`Operands`] APIs (e.g. [`Decoder::decode_first`]) there is no
+/// arena to allocate from, so the operands are placed on the heap instead.
+#[derive(Clone)]
+pub struct ArenaOperands<'bump>(Storage<'bump>);
+
+#[derive(Clone)]
+enum Storage<'bump> {
+    Arena(&'bump [ffi::DecodedOperand]),
+    Heap(Box<[ffi::DecodedOperand]>),
+}
+
+impl<'bump> ArenaOperands<'bump> {
+    fn decode_in(
+        bump: &'bump Bump,
+        decoder: &ffi::Decoder,
+        ctx: &ffi::DecoderContext,
+        insn: &ffi::DecodedInstruction,
+    ) -> Result<Self> {
+        let count = usize::from(insn.operand_count);
+        if count == 0 {
+            return Ok(Self(Storage::Arena(&[])));
+        }
+
+        let layout = Layout::array::<ffi::DecodedOperand>(count).map_err(|_| Status::OutOfRange)?;
+        let ptr: NonNull<ffi::DecodedOperand> = bump.alloc_layout(layout).cast();
+
+        unsafe {
+            ffi::ZydisDecoderDecodeOperands(decoder, ctx, insn, ptr.as_ptr(), insn.operand_count)
+                .as_result()?;
+            for index in 0..count {
+                ffi::DecodedOperand::check_variants(ptr.as_ptr().add(index))?;
+            }
+
+            Ok(Self(Storage::Arena(slice::from_raw_parts(
+                ptr.as_ptr(),
+                count,
+            ))))
+        }
+    }
+}
+
+impl Operands for ArenaOperands<'_> {
+    fn decode(
+        decoder: &ffi::Decoder,
+        ctx: &ffi::DecoderContext,
+        insn: &ffi::DecodedInstruction,
+    ) -> Result<Self> {
+        let ops = AllOperands::decode(decoder, ctx, insn)?;
+        Ok(Self(Storage::Heap(ops.operands().into())))
+    }
+
+    fn operands(&self) -> &[ffi::DecodedOperand] {
+        match &self.0 {
+            Storage::Arena(ops) => ops,
+            Storage::Heap(ops) => ops,
+        }
+    }
+}
+
+impl PartialEq for ArenaOperands<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.operands().eq(other.operands())
+    }
+}
+
+impl Eq for ArenaOperands<'_> {}
+
+impl hash::Hash for ArenaOperands<'_> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.operands().hash(state);
+    }
+}
+
+impl fmt::Debug for ArenaOperands<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ArenaOperands")
+            .field(&self.operands())
+            .finish()
+    }
+}
+
+impl Decoder {
+    /// Decodes the first instruction in the given buffer, allocating its
+    /// operands in `bump`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // lea eax, [rbp-0x01]
+    /// static LEA: &[u8] = &[0x8D, 0x45, 0xFF];
+    /// let bump = bumpalo::Bump::new();
+    /// let decoder = Decoder::new64();
+    ///
+    /// let insn = decoder.decode_first_in(LEA, &bump).unwrap().unwrap();
+    /// assert_eq!(insn.mnemonic, Mnemonic::LEA);
+    /// assert_eq!(insn.visible_operands().len(), 2);
+    /// ```
+    #[inline]
+    pub fn decode_first_in<'bump>(
+        &self,
+        buffer: &[u8],
+        bump: &'bump Bump,
+    ) -> Result<Option<Instruction<ArenaOperands<'bump>>>> {
+        let Some((ctx, info)) = self.decode_raw(buffer)? else {
+            return Ok(None);
+        };
+
+        let operands = ArenaOperands::decode_in(bump, self.raw(), &ctx, &info)?;
+        Ok(Some(Instruction::from_parts(info, operands)))
+    }
+
+    /// Returns an iterator over all the instructions in the buffer,
+    /// allocating their operands in `bump`.
+    ///
+    /// Unlike [`Decoder::decode_all`], iteration stops after the first error.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // push rcx; lea eax, [rbp-0x01]; int3
+    /// static CODE: &[u8] = &[0x51, 0x8D, 0x45, 0xFF, 0xCC];
+    /// let bump = bumpalo::Bump::new();
+    /// let decoder = Decoder::new64();
+    ///
+    /// let insns = decoder
+    ///     .decode_all_in(CODE, 0x1000, &bump)
+    ///     .collect::<Result<Vec<_>>>()
+    ///     .unwrap();
+    /// assert_eq!(insns.len(), 3);
+    /// assert_eq!(insns[1].0, 0x1001);
+    /// ```
+    #[inline]
+    pub fn decode_all_in<'this, 'buffer, 'bump>(
+        &'this self,
+        buffer: &'buffer [u8],
+        ip: u64,
+        bump: &'bump Bump,
+    ) -> ArenaInstructionIter<'this, 'buffer, 'bump> {
+        ArenaInstructionIter {
+            decoder: self,
+            buffer,
+            ip,
+            bump,
+        }
+    }
+}
+
+/// Iterator decoding instructions in a buffer into a [`Bump`] arena.
+///
+/// Created via [`Decoder::decode_all_in`].
+#[derive(Clone)]
+pub struct ArenaInstructionIter<'decoder, 'buffer, 'bump> {
+    decoder: &'decoder Decoder,
+    buffer: &'buffer [u8],
+    ip: u64,
+    bump: &'bump Bump,
+}
+
+impl<'decoder, 'buffer, 'bump> Iterator for ArenaInstructionIter<'decoder, 'buffer, 'bump> {
+    type Item = Result<(u64, &'buffer [u8], Instruction<ArenaOperands<'bump>>)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.decoder.decode_first_in(self.buffer, self.bump) {
+            Ok(Some(insn)) => {
+                let ip = self.ip;
+                let (insn_bytes, new_buffer) = self.buffer.split_at(usize::from(insn.length));
+                self.buffer = new_buffer;
+                self.ip += u64::from(insn.length);
+                Some(Ok((ip, insn_bytes, insn)))
+            }
+            Ok(None) => None,
+            Err(e) => {
+                self.buffer = &[];
+                Some(Err(e))
+            }
+        }
+    }
+}
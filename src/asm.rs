@@ -0,0 +1,424 @@
+//! Code generation on top of the encoder.
+
+use crate::*;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+mod source;
+
+pub mod patch;
+pub mod pic;
+pub mod templates;
+
+pub use source::*;
+
+/// Marks operands referring to a [`Label`] instead of a value.
+const LABEL_MARKER: ffi::OperandPointer = ffi::OperandPointer {
+    segment: 0xFFFF,
+    offset: 0x4C41_424C,
+};
+
+/// Source of the ids distinguishing the labels of different buffers.
+static NEXT_BUFFER_ID: AtomicU32 = AtomicU32::new(0);
+
+/// A position in a [`CodeBuffer`] that can be branched to before it's known.
+///
+/// Labels are created via [`CodeBuffer::new_label`] and convert into
+/// operands, so they can be used in the [`insn64`] and [`insn32`] macros:
+/// `insn64!(JNZ (label))`. Such operands are only meaningful when the request
+/// is pushed into the buffer that created the label, encoding the request in
+/// any other way fails with [`Status::InvalidArgument`].
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// let mut code = asm::CodeBuffer::new(0x1000);
+/// let label = code.new_label();
+/// code.bind(label).unwrap();
+///
+/// assert_eq!(insn64!(JMP (label)).encode().unwrap_err(), Status::InvalidArgument);
+/// assert_eq!(
+///     code.push(insn64!(MOV RAX, (label))).unwrap_err(),
+///     Status::InvalidArgument
+/// );
+/// assert_eq!(code.push(insn64!(JMP (label))).unwrap(), 2);
+///
+/// let mut other = asm::CodeBuffer::new(0x1000);
+/// other.new_label();
+/// assert_eq!(
+///     other.push(insn64!(JMP (label))).unwrap_err(),
+///     Status::InvalidArgument
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Label {
+    /// Id of the buffer that created the label.
+    buffer: u32,
+    /// Index of the label in that buffer.
+    index: u32,
+}
+
+impl From<Label> for EncoderOperand {
+    fn from(label: Label) -> Self {
+        // Deliberately not an immediate, so the encoder rejects it.
+        let mut op = EncoderOperand::imm(u64::from(label.buffer) << 32 | u64::from(label.index));
+        op.ty = OperandType::UNUSED;
+        op.ptr = LABEL_MARKER;
+        op
+    }
+}
+
+/// A pending reference to a label that wasn't bound yet.
+#[derive(Debug, Clone)]
+struct Fixup {
+    label: Label,
+    /// Index of the referencing instruction.
+    insn: usize,
+    /// Offset of the relative immediate.
+    field: usize,
+    /// Size of the relative immediate, in bytes.
+    width: usize,
+    /// Offset of the end of the referencing instruction.
+    end: usize,
+}
+
+/// A growing buffer of encoded instructions with support for labels.
+///
+/// Branches to bound labels are encoded with the smallest possible width.
+/// Branches to labels that aren't bound yet use a 32-bit displacement that's
+/// patched once the label is bound. Branches without a 32-bit form, e.g.
+/// `loop`, use an 8-bit one. A width set on the request is always respected.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// let mut code = asm::CodeBuffer::new(0x1000);
+/// let top = code.new_label();
+/// let done = code.new_label();
+///
+/// code.bind(top).unwrap();
+/// code.push(insn64!(DEC RCX)).unwrap();
+/// code.push(insn64!(JZ (done))).unwrap();
+/// code.push(insn64!(JMP (top))).unwrap();
+/// code.bind(done).unwrap();
+/// code.push(insn64!(RET)).unwrap();
+///
+/// assert_eq!(code.label_address(done), Some(0x100B));
+/// assert_eq!(
+///     code.finish().unwrap(),
+///     [
+///         0x48, 0xFF, 0xC9, // dec rcx
+///         0x0F, 0x84, 0x02, 0x00, 0x00, 0x00, // jz done
+///         0xEB, 0xF5, // jmp top
+///         0xC3, // ret
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct CodeBuffer {
+    /// Id shared with the labels created by this buffer.
+    id: u32,
+    base: u64,
+    bytes: Vec<u8>,
+    /// Number of instructions pushed so far.
+    insns: usize,
+    labels: Vec<Option<usize>>,
+    /// References to labels that aren't bound yet, by instruction.
+    fixups: Vec<Fixup>,
+}
+
+impl CodeBuffer {
+    /// Creates an empty buffer whose first byte will be placed at `base`.
+    pub fn new(base: u64) -> Self {
+        Self {
+            id: NEXT_BUFFER_ID.fetch_add(1, Ordering::Relaxed),
+            base,
+            bytes: Vec::new(),
+            insns: 0,
+            labels: Vec::new(),
+            fixups: Vec::new(),
+        }
+    }
+
+    /// Address of the first byte.
+    #[inline]
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    /// Address of the next instruction.
+    #[inline]
+    pub fn current_address(&self) -> u64 {
+        self.base.wrapping_add(self.bytes.len() as u64)
+    }
+
+    /// The code emitted so far.
+    ///
+    /// References to labels that aren't bound yet contain placeholders.
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Number of bytes emitted so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether nothing was emitted yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Creates a new, unbound label.
+    pub fn new_label(&mut self) -> Label {
+        let label = Label {
+            buffer: self.id,
+            index: self.labels.len() as u32,
+        };
+        self.labels.push(None);
+        label
+    }
+
+    /// Returns the address of `label`, if it's bound.
+    pub fn label_address(&self, label: Label) -> Option<u64> {
+        let offset = self.label_offset(label).ok()??;
+        Some(self.base.wrapping_add(offset as u64))
+    }
+
+    /// Returns the offset of `label`, if it's bound.
+    ///
+    /// Returns [`Status::InvalidArgument`] if the label was created by a
+    /// different buffer.
+    fn label_offset(&self, label: Label) -> Result<Option<usize>> {
+        if label.buffer != self.id {
+            return Err(Status::InvalidArgument);
+        }
+        self.labels
+            .get(label.index as usize)
+            .copied()
+            .ok_or(Status::InvalidArgument)
+    }
+
+    /// Binds `label` to the current position and patches all branches
+    /// referring to it.
+    ///
+    /// Returns [`Status::InvalidArgument`] if the label was created by a
+    /// different buffer, [`Status::InvalidOperation`] if it's already bound
+    /// and [`Status::OutOfRange`] if it's out of reach of a previously emitted
+    /// branch. On failure the buffer remains untouched.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let mut code = asm::CodeBuffer::new(0x1000);
+    /// let far = code.new_label();
+    /// code.push(insn64!(JMP (far)).set_branch_width(BranchWidth::_8))
+    ///     .unwrap();
+    /// code.pad_to(0x100).unwrap();
+    ///
+    /// assert_eq!(code.bind(far), Err(Status::OutOfRange));
+    /// assert_eq!(code.label_address(far), None);
+    /// assert_eq!(code.finish(), Err(Status::NotFound));
+    /// ```
+    pub fn bind(&mut self, label: Label) -> Result<()> {
+        let target = self.bytes.len();
+        if self.label_offset(label)?.is_some() {
+            return Err(Status::InvalidOperation);
+        }
+
+        let rel = |fixup: &Fixup| target as i64 - fixup.end as i64;
+        // Sign-extending the immediate must reproduce `rel`.
+        let fits = |fixup: &Fixup| matches!(rel(fixup) >> (8 * fixup.width - 1), 0 | -1);
+        if !self
+            .fixups
+            .iter()
+            .filter(|fixup| fixup.label == label)
+            .all(fits)
+        {
+            return Err(Status::OutOfRange);
+        }
+
+        let bytes = &mut self.bytes;
+        self.fixups.retain(|fixup| {
+            if fixup.label != label {
+                return true;
+            }
+            bytes[fixup.field..fixup.field + fixup.width]
+                .copy_from_slice(&rel(fixup).to_le_bytes()[..fixup.width]);
+            false
+        });
+        self.labels[label.index as usize] = Some(target);
+        Ok(())
+    }
+
+    /// Encodes `request` and appends it, returning its length.
+    ///
+    /// Label operands are resolved as described on [`CodeBuffer`]. Other
+    /// relative operands and `rip`-relative displacements are interpreted as
+    /// absolute addresses, like in [`EncoderRequest::encode_absolute`].
+    ///
+    /// Returns [`Status::InvalidArgument`] if a label was created by a
+    /// different buffer or is used by anything but a relative branch. On
+    /// failure the buffer remains untouched.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let mut code = asm::CodeBuffer::new(0x1000);
+    /// let done = code.new_label();
+    /// code.push(insn64!(LOOP (done))).unwrap();
+    /// code.push(insn64!(NOP)).unwrap();
+    /// code.bind(done).unwrap();
+    ///
+    /// assert_eq!(code.finish().unwrap(), [0xE2, 0x01, 0x90]);
+    /// ```
+    pub fn push(&mut self, mut request: EncoderRequest) -> Result<usize> {
+        let ip = self.current_address();
+        let mut has_label = false;
+        let mut pending = None;
+        for op in request.operands_mut() {
+            if op.ty != OperandType::UNUSED || op.ptr != LABEL_MARKER {
+                continue;
+            }
+
+            let label = Label {
+                buffer: (op.imm >> 32) as u32,
+                index: op.imm as u32,
+            };
+            let target = match self.label_offset(label)? {
+                Some(offset) => self.base.wrapping_add(offset as u64),
+                None => {
+                    pending = Some(label);
+                    ip
+                }
+            };
+            *op = EncoderOperand::imm(target);
+            has_label = true;
+        }
+
+        let mut buf = [0; MAX_INSTRUCTION_LENGTH];
+        let length = if pending.is_some() && request.branch_width == BranchWidth::NONE {
+            // Reserve a 32-bit displacement, unless the branch only has an
+            // 8-bit form, e.g. `loop` or `jrcxz`.
+            let mut encode = |width| {
+                request
+                    .clone()
+                    .set_branch_width(width)
+                    .encode_absolute_into(&mut buf, ip)
+            };
+            encode(BranchWidth::_32).or_else(|_| encode(BranchWidth::_8))?
+        } else {
+            request.encode_absolute_into(&mut buf, ip)?
+        };
+
+        if has_label {
+            let (offset, width) = relative_immediate(request.machine_mode, &buf[..length])?
+                .ok_or(Status::InvalidArgument)?;
+            if let Some(label) = pending {
+                let start = self.bytes.len();
+                self.fixups.push(Fixup {
+                    label,
+                    insn: self.insns,
+                    field: start + offset,
+                    width,
+                    end: start + length,
+                });
+            }
+        }
+
+        self.bytes.extend_from_slice(&buf[..length]);
+        self.insns += 1;
+        Ok(length)
+    }
+
+    /// Appends raw bytes, e.g. data.
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    /// Appends `nop`s until the buffer is `len` bytes long, returning the
+    /// number of bytes appended.
+    ///
+    /// Returns [`Status::InvalidArgument`] if the buffer is already longer.
+    pub fn pad_to(&mut self, len: usize) -> Result<usize> {
+        let start = self.bytes.len();
+        if len < start {
+            return Err(Status::InvalidArgument);
+        }
+        self.bytes.resize(len, 0);
+        nop_fill(&mut self.bytes[start..])?;
+        Ok(len - start)
+    }
+
+    /// Appends `nop`s until the current address is a multiple of
+    /// `alignment`, returning the number of bytes appended.
+    ///
+    /// Returns [`Status::InvalidArgument`] if `alignment` is zero and
+    /// [`Status::OutOfRange`] if the aligned address exceeds the address
+    /// space.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let mut code = asm::CodeBuffer::new(0x1000);
+    /// code.push(insn64!(RET)).unwrap();
+    /// assert_eq!(code.align_to(16).unwrap(), 15);
+    /// assert_eq!(code.current_address(), 0x1010);
+    /// assert_eq!(code.align_to(16).unwrap(), 0);
+    /// ```
+    pub fn align_to(&mut self, alignment: u64) -> Result<usize> {
+        if alignment == 0 {
+            return Err(Status::InvalidArgument);
+        }
+        let address = self.current_address();
+        let len = address
+            .checked_next_multiple_of(alignment)
+            .and_then(|aligned| usize::try_from(aligned - address).ok())
+            .and_then(|padding| self.bytes.len().checked_add(padding))
+            .ok_or(Status::OutOfRange)?;
+        self.pad_to(len)
+    }
+
+    /// Returns the indices of the instructions referring to labels that
+    /// aren't bound yet, along with the labels.
+    pub fn pending_references(&self) -> impl Iterator<Item = (usize, Label)> + '_ {
+        self.fixups.iter().map(|fixup| (fixup.insn, fixup.label))
+    }
+
+    /// Returns the final code.
+    ///
+    /// Returns [`Status::NotFound`] if a referenced label was never bound, see
+    /// [`CodeBuffer::pending_references`].
+    pub fn finish(self) -> Result<Vec<u8>> {
+        if !self.fixups.is_empty() {
+            return Err(Status::NotFound);
+        }
+        Ok(self.bytes)
+    }
+}
+
+/// Returns the offset and size in bytes of the relative immediate of the
+/// branch in `bytes`, or `None` if it's no such branch.
+fn relative_immediate(machine_mode: MachineMode, bytes: &[u8]) -> Result<Option<(usize, usize)>> {
+    let decoder = Decoder::new(machine_mode, default_stack_width(machine_mode))?;
+    let Some(insn) = decoder.decode_first::<NoOperands>(bytes)? else {
+        return Ok(None);
+    };
+    Ok(insn
+        .raw
+        .imm
+        .iter()
+        .find(|imm| imm.is_relative && imm.size >= 8)
+        .map(|imm| (usize::from(imm.offset), usize::from(imm.size / 8))))
+}
+
+/// Fills `buf` with as few `nop`s as possible.
+pub(crate) fn nop_fill(buf: &mut [u8]) -> Result<()> {
+    if buf.is_empty() {
+        return Ok(());
+    }
+    unsafe { ffi::ZydisEncoderNopFill(buf.as_mut_ptr().cast(), buf.len()).as_result() }
+}
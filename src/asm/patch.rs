@@ -0,0 +1,175 @@
+//! Planning of patches to code that may be executing concurrently.
+
+use crate::*;
+use alloc::vec::Vec;
+
+/// A single aligned 8-byte store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WriteStep {
+    /// Address of the store, a multiple of 8.
+    pub address: u64,
+    /// The value to store, in native (little endian) byte order.
+    pub value: u64,
+}
+
+/// A patch split into stores that are atomic on x86.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PatchPlan {
+    /// The bytes written at the patch site once all steps are applied.
+    pub bytes: Vec<u8>,
+    /// The stores to perform, in order.
+    pub steps: Vec<WriteStep>,
+}
+
+impl PatchPlan {
+    /// Whether the patch is applied by a single store.
+    #[inline]
+    pub fn is_atomic(&self) -> bool {
+        self.steps.len() == 1
+    }
+}
+
+/// Plans writing `patch` to `site` within `code`, which is located at
+/// `base`.
+///
+/// If the patch lies within a single aligned 8-byte word, it's applied by
+/// a single store of that word. Otherwise the patch is staged: the first
+/// word is stored with a guard at `site` (a `jmp $` or, if that doesn't
+/// fit the word, an `int3`), then the remaining words, and finally the
+/// first word with the actual patch. Threads reaching `site` in between spin
+/// or trap instead of running partially patched code. Threads already
+/// executing within the patched range aren't accounted for.
+///
+/// The caller has to serialize instruction fetch between the steps, e.g.
+/// via `membarrier` on Linux or `FlushInstructionCache` on Windows.
+///
+/// `code` must cover all words touched by the patch. Returns
+/// [`Status::OutOfRange`] if it doesn't and [`Status::InvalidArgument`] if
+/// `patch` is empty.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// use zydis::asm::patch;
+///
+/// let code = [0x90; 16];
+/// let plan = patch::plan(&code, 0x1000, 0x1006, &[0xE9, 0xF5, 0x0F, 0x00, 0x00]).unwrap();
+/// assert!(!plan.is_atomic());
+///
+/// let steps: Vec<_> = plan
+///     .steps
+///     .iter()
+///     .map(|step| (step.address, step.value.to_le_bytes()))
+///     .collect();
+/// assert_eq!(
+///     steps,
+///     [
+///         (0x1000, [0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0xEB, 0xFE]),
+///         (0x1008, [0x0F, 0x00, 0x00, 0x90, 0x90, 0x90, 0x90, 0x90]),
+///         (0x1000, [0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0xE9, 0xF5]),
+///     ]
+/// );
+/// ```
+pub fn plan(code: &[u8], base: u64, site: u64, patch: &[u8]) -> Result<PatchPlan> {
+    if patch.is_empty() {
+        return Err(Status::InvalidArgument);
+    }
+
+    let start = site & !7;
+    let end = site
+        .checked_add(patch.len() as u64)
+        .and_then(|end| end.checked_next_multiple_of(8))
+        .ok_or(Status::OutOfRange)?;
+    let offset = usize::try_from(start.checked_sub(base).ok_or(Status::OutOfRange)?)
+        .map_err(|_| Status::OutOfRange)?;
+    let window = code
+        .get(offset..)
+        .and_then(|code| code.get(..(end - start) as usize))
+        .ok_or(Status::OutOfRange)?;
+
+    let at = (site - start) as usize;
+    let mut patched = window.to_vec();
+    patched[at..at + patch.len()].copy_from_slice(patch);
+
+    let store = |bytes: &[u8], word: usize| WriteStep {
+        address: start + 8 * word as u64,
+        value: bytes
+            .iter()
+            .skip(8 * word)
+            .take(8)
+            .enumerate()
+            .fold(0, |value, (idx, &byte)| {
+                value | u64::from(byte) << (8 * idx)
+            }),
+    };
+
+    let words = window.len() / 8;
+    let mut steps = Vec::with_capacity(words + 1);
+    if words > 1 {
+        let mut guarded = window.to_vec();
+        if at + 2 <= 8 {
+            guarded[at..at + 2].copy_from_slice(&[0xEB, 0xFE]);
+        } else {
+            guarded[at] = 0xCC;
+        }
+        steps.push(store(&guarded, 0));
+        steps.extend((1..words).map(|word| store(&patched, word)));
+    }
+    steps.push(store(&patched, 0));
+
+    Ok(PatchPlan {
+        bytes: patched[at..at + patch.len()].to_vec(),
+        steps,
+    })
+}
+
+/// Plans replacing the instruction(s) at `site` with a jump to `target`.
+///
+/// The jump is encoded in the shortest form able to reach `target`. The
+/// remainder of the last overwritten instruction is filled with `nop`s.
+/// See [`plan`] for how the patch is applied and the requirements on
+/// `code`.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// use zydis::asm::patch;
+///
+/// let code = [0x90; 16];
+/// let plan = patch::plan_jump(&Decoder::new64(), &code, 0x1000, 0x1002, 0x1010).unwrap();
+/// assert!(plan.is_atomic());
+/// assert_eq!(plan.bytes, [0xEB, 0x0C]);
+/// assert_eq!(
+///     plan.steps[0].value.to_le_bytes(),
+///     [0x90, 0x90, 0xEB, 0x0C, 0x90, 0x90, 0x90, 0x90]
+/// );
+/// ```
+pub fn plan_jump(
+    decoder: &Decoder,
+    code: &[u8],
+    base: u64,
+    site: u64,
+    target: u64,
+) -> Result<PatchPlan> {
+    let offset = usize::try_from(site.checked_sub(base).ok_or(Status::OutOfRange)?)
+        .map_err(|_| Status::OutOfRange)?;
+    let mut insns =
+        decoder.decode_all::<NoOperands>(code.get(offset..).ok_or(Status::OutOfRange)?, site);
+
+    let (_, _, first) = insns.next().ok_or(Status::OutOfRange)??;
+    let mut bytes = EncoderRequest::new(first.machine_mode, Mnemonic::JMP)
+        .add_operand(target)
+        .encode_absolute(site)?;
+
+    let mut overwritten = usize::from(first.length);
+    while overwritten < bytes.len() {
+        let (_, _, insn) = insns.next().ok_or(Status::OutOfRange)??;
+        overwritten += usize::from(insn.length);
+    }
+
+    let jump_len = bytes.len();
+    bytes.resize(overwritten, 0);
+    super::nop_fill(&mut bytes[jump_len..])?;
+
+    plan(code, base, site, &bytes)
+}
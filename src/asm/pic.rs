@@ -0,0 +1,131 @@
+//! Conversion of absolute addressing into position independent code.
+
+use super::{CodeBuffer, Label};
+use crate::*;
+use alloc::vec::Vec;
+
+/// Result of [`rewrite`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rewritten {
+    /// The rewritten code.
+    pub code: Vec<u8>,
+    /// Original addresses of instructions still using absolute memory
+    /// addressing, e.g. because they use an index register or the target is
+    /// out of reach of a 32-bit displacement.
+    pub unconverted: Vec<u64>,
+}
+
+/// Rewrites 64-bit code at `base` to use `rip`-relative instead of absolute
+/// memory operands, for placement at `new_base`.
+///
+/// All instructions are re-encoded. Memory operands and branch targets
+/// outside of the code keep referring to the same absolute addresses, while
+/// branches to instructions within the code follow them to their new
+/// location. Absolute immediates (e.g. `mov rax, 0x1000`) are left alone, as
+/// there is no telling whether they are addresses.
+///
+/// Returns [`Status::InvalidArgument`] for code that isn't 64-bit and the
+/// decoding or encoding error otherwise.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// static CODE: &[u8] = &[
+///     0x8B, 0x04, 0x25, 0x00, 0x20, 0x00, 0x00, // mov eax, dword ptr [0x2000]
+///     0x8B, 0x04, 0x8D, 0x00, 0x20, 0x00, 0x00, // mov eax, dword ptr [rcx*4+0x2000]
+///     0xC3, // ret
+/// ];
+///
+/// let rewritten = asm::pic::rewrite(&Decoder::new64(), CODE, 0x1000, 0x1000).unwrap();
+/// assert_eq!(
+///     rewritten.code,
+///     [
+///         0x8B, 0x05, 0xFA, 0x0F, 0x00, 0x00, // mov eax, dword ptr [rip+0xFFA]
+///         0x8B, 0x04, 0x8D, 0x00, 0x20, 0x00, 0x00, // unchanged
+///         0xC3,
+///     ]
+/// );
+/// assert_eq!(rewritten.unconverted, [0x1007]);
+/// ```
+pub fn rewrite(decoder: &Decoder, bytes: &[u8], base: u64, new_base: u64) -> Result<Rewritten> {
+    let insns = decoder
+        .decode_all::<VisibleOperands>(bytes, base)
+        .map(|res| res.map(|(ip, _, insn)| (ip, insn)))
+        .collect::<Result<Vec<_>>>()?;
+    if insns
+        .iter()
+        .any(|(_, insn)| insn.machine_mode != MachineMode::LONG_64)
+    {
+        return Err(Status::InvalidArgument);
+    }
+
+    let mut code = CodeBuffer::new(new_base);
+    let labels: Vec<(u64, Label)> = insns
+        .iter()
+        .map(|&(ip, _)| (ip, code.new_label()))
+        .collect();
+    let label_at = |addr: u64| {
+        labels
+            .binary_search_by_key(&addr, |&(ip, _)| ip)
+            .ok()
+            .map(|idx| labels[idx].1)
+    };
+
+    let mut unconverted = Vec::new();
+    for ((ip, insn), &(_, label)) in insns.iter().zip(&labels) {
+        code.bind(label)?;
+
+        let original = EncoderRequest::from_instruction(insn)?;
+        let mut request = original.clone();
+        let mut converted = false;
+        for (dec, enc) in insn.visible_operands().iter().zip(request.operands_mut()) {
+            match &dec.kind {
+                ffi::DecodedOperandKind::Imm(imm) if imm.is_relative => {
+                    let target = insn.calc_absolute_address(*ip, dec)?;
+                    *enc = match label_at(target) {
+                        Some(label) => label.into(),
+                        None => EncoderOperand::imm(target),
+                    };
+                }
+                ffi::DecodedOperandKind::Mem(mem) if mem.base == Register::RIP => {
+                    enc.mem.displacement = insn.calc_absolute_address(*ip, dec)? as i64;
+                }
+                ffi::DecodedOperandKind::Mem(mem)
+                    if mem.base == Register::NONE
+                        && mem.disp.has_displacement
+                        && mem.ty != MemoryOperandType::MIB =>
+                {
+                    if mem.index == Register::NONE {
+                        enc.mem.base = Register::RIP;
+                        enc.mem.displacement = insn.calc_absolute_address(*ip, dec)? as i64;
+                        converted = true;
+                    } else {
+                        unconverted.push(*ip);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if insn.raw.imm.iter().any(|imm| imm.is_relative) {
+            request = request
+                .set_branch_type(BranchType::NONE)
+                .set_branch_width(BranchWidth::NONE);
+        }
+
+        match code.push(request) {
+            Err(_) if converted => {
+                unconverted.push(*ip);
+                code.push(original)?;
+            }
+            res => {
+                res?;
+            }
+        }
+    }
+
+    Ok(Rewritten {
+        code: code.finish()?,
+        unconverted,
+    })
+}
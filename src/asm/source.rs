@@ -0,0 +1,675 @@
+//! A small assembler for Intel syntax source text.
+
+use crate::*;
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+/// Number of passes after which instructions may no longer shrink.
+const SHRINK_PASSES: usize = 4;
+/// Number of passes after which the layout is assumed to never settle.
+const MAX_PASSES: usize = 16;
+
+/// Output of [`assemble_source`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assembled {
+    /// The assembled code and data.
+    pub code: Vec<u8>,
+    /// Addresses of all labels.
+    pub symbols: BTreeMap<String, u64>,
+}
+
+/// Error returned by [`assemble_source`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceError {
+    /// The 1-based line the error occurred on, or 0 if the error isn't
+    /// specific to a line.
+    pub line: usize,
+    /// What went wrong.
+    pub status: Status,
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.status)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SourceError {}
+
+/// Assembles Intel syntax source text, placing it at `base`.
+///
+/// Each line holds any number of `label:` definitions, optionally followed
+/// by an instruction or directive. Comments start with `;` or `#`.
+/// Supported are:
+///
+/// - instructions with legacy prefixes (`lock`, `rep`, `repe`, `repne`,
+///   `notrack`, ...), register, immediate, `far seg:off` and memory operands
+///   in the form `size ptr seg:[base + index * scale + disp]`,
+/// - `db`, `dw`, `dd` and `dq` with comma separated values (and strings for
+///   `db`),
+/// - `align n`, which pads with `nop`s, and `bits 32` / `bits 64`.
+///
+/// Wherever a number is accepted, a label plus an optional offset can be
+/// used instead. Branch targets are absolute addresses, as are memory
+/// displacements, so use `[rip + label]` for `rip`-relative addressing.
+/// Labels may be referenced before their definition; the code is assembled
+/// repeatedly until all addresses settle.
+///
+/// Unknown mnemonics, registers and labels are reported as
+/// [`Status::NotFound`], duplicate labels as [`Status::InvalidOperation`],
+/// values that don't fit as [`Status::OutOfRange`] and malformed lines as
+/// [`Status::InvalidArgument`]. Instructions that fail to encode report the
+/// encoder's error.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// let source = "
+///     bits 64
+/// start:
+///     xor eax, eax        ; zero the counter
+/// again:
+///     inc eax
+///     cmp eax, 10
+///     jne again
+///     mov rcx, qword ptr [rip + data]
+///     ret
+/// data:
+///     dq start
+/// ";
+///
+/// let out = asm::assemble_source(source, 0x1000).unwrap();
+/// assert_eq!(out.symbols["again"], 0x1002);
+/// assert_eq!(out.symbols["data"], 0x1011);
+/// assert_eq!(
+///     out.code,
+///     [
+///         0x31, 0xC0, // xor eax, eax
+///         0xFF, 0xC0, // inc eax
+///         0x83, 0xF8, 0x0A, // cmp eax, 10
+///         0x75, 0xF9, // jne again
+///         0x48, 0x8B, 0x0D, 0x01, 0x00, 0x00, 0x00, // mov rcx, [rip + data]
+///         0xC3, // ret
+///         0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // dq start
+///     ]
+/// );
+///
+/// let err = asm::assemble_source("nop\nmov eax, [rcx * 3]", 0).unwrap_err();
+/// assert_eq!(err.line, 2);
+/// assert_eq!(err.status, Status::InvalidArgument);
+/// ```
+pub fn assemble_source(text: &str, base: u64) -> core::result::Result<Assembled, SourceError> {
+    let names = Names::new();
+    let mut items = Vec::new();
+    let mut labels = BTreeSet::new();
+    for (idx, line) in text.lines().enumerate() {
+        let fail = |status| SourceError {
+            line: idx + 1,
+            status,
+        };
+        let tokens = tokenize(line).map_err(fail)?;
+        for item in parse_line(&tokens, &names).map_err(fail)? {
+            if let Item::Label(name) = &item {
+                if !labels.insert(name.clone()) {
+                    return Err(fail(Status::InvalidOperation));
+                }
+            }
+            items.push((idx + 1, item));
+        }
+    }
+
+    let mut previous = BTreeMap::new();
+    let mut lengths = alloc::vec![0; items.len()];
+    for pass in 0..MAX_PASSES {
+        let mut code = Vec::new();
+        let mut current = BTreeMap::new();
+        let mut mode = MachineMode::LONG_64;
+        for (idx, (line, item)) in items.iter().enumerate() {
+            let fail = |status| SourceError {
+                line: *line,
+                status,
+            };
+            let address = base.wrapping_add(code.len() as u64);
+            let resolve = |expr: &Expr| -> Result<u64> {
+                let value = match &expr.symbol {
+                    None => 0,
+                    Some(symbol) => current
+                        .get(symbol.as_str())
+                        .or_else(|| previous.get(symbol.as_str()))
+                        .copied()
+                        .or_else(|| labels.contains(symbol).then_some(address))
+                        .ok_or(Status::NotFound)?,
+                };
+                Ok(value.wrapping_add(expr.offset as u64))
+            };
+
+            match item {
+                Item::Label(name) => {
+                    current.insert(name.as_str(), address);
+                }
+                Item::Mode(new) => mode = *new,
+                Item::Align(alignment) => {
+                    let padding = address
+                        .checked_next_multiple_of(*alignment)
+                        .and_then(|aligned| usize::try_from(aligned - address).ok())
+                        .ok_or(fail(Status::OutOfRange))?;
+                    let start = code.len();
+                    code.resize(start + padding, 0);
+                    super::nop_fill(&mut code[start..]).map_err(fail)?;
+                }
+                Item::Data { width, values } => {
+                    for value in values {
+                        match value {
+                            DataValue::Bytes(bytes) => code.extend_from_slice(bytes),
+                            DataValue::Expr(expr) => {
+                                let value = resolve(expr).map_err(fail)?;
+                                let bits = 8 * *width as u32;
+                                let fits = bits == 64
+                                    || value >> bits == 0
+                                    || (value as i64) >> (bits - 1) == -1;
+                                if !fits {
+                                    return Err(fail(Status::OutOfRange));
+                                }
+                                code.extend_from_slice(&value.to_le_bytes()[..*width]);
+                            }
+                        }
+                    }
+                }
+                Item::Insn {
+                    mnemonic,
+                    prefixes,
+                    operands,
+                } => {
+                    let mut prefixes = *prefixes;
+                    let mut request = EncoderRequest::new(mode, *mnemonic);
+                    for operand in operands {
+                        let operand = match operand {
+                            Operand::Reg(reg) => EncoderOperand::reg(*reg),
+                            Operand::Imm(expr) => EncoderOperand::imm(resolve(expr).map_err(fail)?),
+                            Operand::Ptr(segment, offset) => EncoderOperand::ptr(*segment, *offset),
+                            Operand::Mem(mem) => {
+                                prefixes |= mem.segment;
+                                let mut raw = EncoderOperand::ZERO_MEM.clone();
+                                raw.size = mem.size;
+                                raw.base = mem.base;
+                                raw.index = mem.index;
+                                raw.scale = mem.scale;
+                                raw.displacement = resolve(&mem.disp).map_err(fail)? as i64;
+                                EncoderOperand::mem_custom(raw)
+                            }
+                        };
+                        request = request.add_operand(operand);
+                    }
+                    request = request.set_prefixes(prefixes);
+
+                    let mut buf = [0; MAX_INSTRUCTION_LENGTH];
+                    let mut length = request
+                        .encode_absolute_into(&mut buf, address)
+                        .map_err(fail)?;
+                    if pass >= SHRINK_PASSES && length < lengths[idx] {
+                        // Keep the layout from oscillating.
+                        super::nop_fill(&mut buf[length..lengths[idx]]).map_err(fail)?;
+                        length = lengths[idx];
+                    }
+                    lengths[idx] = length;
+                    code.extend_from_slice(&buf[..length]);
+                }
+            }
+        }
+
+        if pass > 0 && current == previous {
+            return Ok(Assembled {
+                code,
+                symbols: current
+                    .into_iter()
+                    .map(|(name, address)| (name.to_string(), address))
+                    .collect(),
+            });
+        }
+        previous = current;
+    }
+
+    Err(SourceError {
+        line: 0,
+        status: Status::Failed,
+    })
+}
+
+/// Lookup tables for mnemonic and register names.
+struct Names {
+    mnemonics: BTreeMap<&'static str, Mnemonic>,
+    registers: BTreeMap<&'static str, Register>,
+}
+
+impl Names {
+    fn new() -> Self {
+        let mnemonics = (0..=MNEMONIC_MAX_VALUE as u32)
+            .filter_map(|value| Mnemonic::try_from(value).ok())
+            .filter_map(|mnemonic| Some((mnemonic.short_string()?.as_str(), mnemonic)))
+            .collect();
+        let registers = Register::all()
+            .filter_map(|reg| Some((reg.short_string()?.as_str(), reg)))
+            .collect();
+        Self {
+            mnemonics,
+            registers,
+        }
+    }
+
+    fn register(&self, name: &str) -> Option<Register> {
+        self.registers
+            .get(name.to_ascii_lowercase().as_str())
+            .copied()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Num(u64),
+    Str(Vec<u8>),
+    Punct(char),
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        chars.next();
+        match c {
+            ';' | '#' => break,
+            c if c.is_whitespace() => (),
+            '"' | '\'' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, q)) if q == c => break,
+                        Some((_, ch)) => s.push(ch),
+                        None => return Err(Status::InvalidArgument),
+                    }
+                }
+                tokens.push(Token::Str(s.into_bytes()));
+            }
+            '+' | '-' | '*' | '[' | ']' | ':' | ',' => tokens.push(Token::Punct(c)),
+            c if c.is_ascii_alphanumeric() || c == '_' || c == '.' => {
+                let mut end = start + 1;
+                while let Some(&(i, ch)) = chars.peek() {
+                    if !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '.') {
+                        break;
+                    }
+                    end = i + 1;
+                    chars.next();
+                }
+                let word = &line[start..end];
+                tokens.push(if c.is_ascii_digit() {
+                    Token::Num(parse_number(word)?)
+                } else {
+                    Token::Ident(word.to_string())
+                });
+            }
+            _ => return Err(Status::InvalidArgument),
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_number(word: &str) -> Result<u64> {
+    let word = word.replace('_', "").to_ascii_lowercase();
+    let (digits, radix) = if let Some(hex) = word.strip_prefix("0x") {
+        (hex, 16)
+    } else if let Some(bin) = word.strip_prefix("0b") {
+        (bin, 2)
+    } else if let Some(oct) = word.strip_prefix("0o") {
+        (oct, 8)
+    } else if let Some(hex) = word.strip_suffix('h') {
+        (hex, 16)
+    } else {
+        (word.as_str(), 10)
+    };
+    u64::from_str_radix(digits, radix).map_err(|_| Status::InvalidArgument)
+}
+
+/// A label plus an offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Expr {
+    symbol: Option<String>,
+    offset: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Mem {
+    size: u16,
+    segment: InstructionAttributes,
+    base: Register,
+    index: Register,
+    scale: u8,
+    disp: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operand {
+    Reg(Register),
+    Imm(Expr),
+    Ptr(u16, u32),
+    Mem(Mem),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DataValue {
+    Bytes(Vec<u8>),
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Item {
+    Label(String),
+    Mode(MachineMode),
+    Align(u64),
+    Data {
+        width: usize,
+        values: Vec<DataValue>,
+    },
+    Insn {
+        mnemonic: Mnemonic,
+        prefixes: InstructionAttributes,
+        operands: Vec<Operand>,
+    },
+}
+
+fn parse_line(mut tokens: &[Token], names: &Names) -> Result<Vec<Item>> {
+    let mut items = Vec::new();
+    while let [Token::Ident(label), Token::Punct(':'), tail @ ..] = tokens {
+        items.push(Item::Label(label.clone()));
+        tokens = tail;
+    }
+
+    let mut prefixes = InstructionAttributes::empty();
+    let mnemonic = loop {
+        let [Token::Ident(word), tail @ ..] = tokens else {
+            return if tokens.is_empty() {
+                Ok(items)
+            } else {
+                Err(Status::InvalidArgument)
+            };
+        };
+        tokens = tail;
+
+        let word = word.to_ascii_lowercase();
+        let prefix = match word.as_str() {
+            "lock" => InstructionAttributes::HAS_LOCK,
+            "rep" => InstructionAttributes::HAS_REP,
+            "repe" | "repz" => InstructionAttributes::HAS_REPE,
+            "repne" | "repnz" => InstructionAttributes::HAS_REPNE,
+            "notrack" => InstructionAttributes::HAS_NOTRACK,
+            "bnd" => InstructionAttributes::HAS_BND,
+            "xacquire" => InstructionAttributes::HAS_XACQUIRE,
+            "xrelease" => InstructionAttributes::HAS_XRELEASE,
+            _ => break word,
+        };
+        prefixes |= prefix;
+    };
+
+    let item = match mnemonic.as_str() {
+        "db" | "dw" | "dd" | "dq" if prefixes.is_empty() => {
+            let width = match mnemonic.as_str() {
+                "db" => 1,
+                "dw" => 2,
+                "dd" => 4,
+                _ => 8,
+            };
+            let values = split_operands(tokens)?
+                .into_iter()
+                .map(|value| match value {
+                    [Token::Str(bytes)] if width == 1 => Ok(DataValue::Bytes(bytes.clone())),
+                    _ => Ok(DataValue::Expr(parse_imm(value, names)?)),
+                })
+                .collect::<Result<_>>()?;
+            Item::Data { width, values }
+        }
+        "align" if prefixes.is_empty() => match tokens {
+            [Token::Num(alignment)] if *alignment != 0 => Item::Align(*alignment),
+            _ => return Err(Status::InvalidArgument),
+        },
+        "bits" if prefixes.is_empty() => match tokens {
+            [Token::Num(32)] => Item::Mode(MachineMode::LONG_COMPAT_32),
+            [Token::Num(64)] => Item::Mode(MachineMode::LONG_64),
+            _ => return Err(Status::InvalidArgument),
+        },
+        _ => {
+            let mnemonic = *names
+                .mnemonics
+                .get(mnemonic.as_str())
+                .ok_or(Status::NotFound)?;
+            let operands = split_operands(tokens)?
+                .into_iter()
+                .map(|operand| parse_operand(operand, names))
+                .collect::<Result<Vec<_>>>()?;
+            if operands.len() > ENCODER_MAX_OPERANDS {
+                return Err(Status::InvalidArgument);
+            }
+            Item::Insn {
+                mnemonic,
+                prefixes,
+                operands,
+            }
+        }
+    };
+    items.push(item);
+    Ok(items)
+}
+
+/// Splits at commas outside of brackets.
+fn split_operands(tokens: &[Token]) -> Result<Vec<&[Token]>> {
+    if tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut operands = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Punct('[') => depth += 1,
+            Token::Punct(']') => depth -= 1,
+            Token::Punct(',') if depth == 0 => {
+                operands.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    operands.push(&tokens[start..]);
+
+    if operands.iter().any(|operand| operand.is_empty()) {
+        return Err(Status::InvalidArgument);
+    }
+    Ok(operands)
+}
+
+fn parse_operand(tokens: &[Token], names: &Names) -> Result<Operand> {
+    match tokens {
+        [Token::Ident(far), Token::Num(segment), Token::Punct(':'), Token::Num(offset)]
+            if far.eq_ignore_ascii_case("far") =>
+        {
+            return Ok(Operand::Ptr(
+                u16::try_from(*segment).map_err(|_| Status::OutOfRange)?,
+                u32::try_from(*offset).map_err(|_| Status::OutOfRange)?,
+            ));
+        }
+        [Token::Ident(name)] => {
+            if let Some(reg) = names.register(name) {
+                return Ok(Operand::Reg(reg));
+            }
+        }
+        _ => (),
+    }
+
+    let mut tokens = tokens;
+    let mut size = 0;
+    if let [Token::Ident(word), tail @ ..] = tokens {
+        if let Some(bytes) = size_keyword(word) {
+            size = bytes;
+            tokens = tail;
+            if let [Token::Ident(ptr), tail @ ..] = tokens {
+                if ptr.eq_ignore_ascii_case("ptr") {
+                    tokens = tail;
+                }
+            }
+        }
+    }
+
+    let mut segment = InstructionAttributes::empty();
+    if let [Token::Ident(name), Token::Punct(':'), tail @ ..] = tokens {
+        segment = segment_prefix(names.register(name).ok_or(Status::NotFound)?)?;
+        tokens = tail;
+    }
+
+    let [Token::Punct('['), inner @ .., Token::Punct(']')] = tokens else {
+        if size != 0 || !segment.is_empty() {
+            return Err(Status::InvalidArgument);
+        }
+        return Ok(Operand::Imm(parse_imm(tokens, names)?));
+    };
+
+    let mut inner = inner;
+    if let [Token::Ident(name), Token::Punct(':'), tail @ ..] = inner {
+        if !segment.is_empty() {
+            return Err(Status::InvalidArgument);
+        }
+        segment = segment_prefix(names.register(name).ok_or(Status::NotFound)?)?;
+        inner = tail;
+    }
+
+    let (base, index, scale, disp) = parse_terms(inner, names)?;
+    Ok(Operand::Mem(Mem {
+        size,
+        segment,
+        base,
+        index,
+        scale,
+        disp,
+    }))
+}
+
+fn parse_imm(tokens: &[Token], names: &Names) -> Result<Expr> {
+    match parse_terms(tokens, names)? {
+        (Register::NONE, Register::NONE, _, expr) => Ok(expr),
+        _ => Err(Status::InvalidArgument),
+    }
+}
+
+/// Parses `base + index * scale + disp` in any order, where the
+/// displacement may consist of several numbers and a single label.
+fn parse_terms(tokens: &[Token], names: &Names) -> Result<(Register, Register, u8, Expr)> {
+    let mut base = Register::NONE;
+    let mut index = Register::NONE;
+    let mut scale = 0;
+    let mut disp = Expr {
+        symbol: None,
+        offset: 0,
+    };
+
+    let mut tokens = tokens;
+    let mut first = true;
+    while !tokens.is_empty() {
+        let negative = match tokens {
+            [Token::Punct('+'), tail @ ..] => {
+                tokens = tail;
+                false
+            }
+            [Token::Punct('-'), tail @ ..] => {
+                tokens = tail;
+                true
+            }
+            _ if first => false,
+            _ => return Err(Status::InvalidArgument),
+        };
+        first = false;
+
+        let scaled = match tokens {
+            [Token::Ident(reg), Token::Punct('*'), Token::Num(factor), tail @ ..]
+            | [Token::Num(factor), Token::Punct('*'), Token::Ident(reg), tail @ ..] => {
+                tokens = tail;
+                Some((names.register(reg).ok_or(Status::NotFound)?, *factor))
+            }
+            _ => None,
+        };
+        if let Some((reg, factor)) = scaled {
+            if negative || index != Register::NONE || ![1, 2, 4, 8].contains(&factor) {
+                return Err(Status::InvalidArgument);
+            }
+            index = reg;
+            scale = factor as u8;
+            continue;
+        }
+
+        match tokens {
+            [Token::Num(value), tail @ ..] => {
+                tokens = tail;
+                let value = *value as i64;
+                disp.offset = if negative {
+                    disp.offset.wrapping_sub(value)
+                } else {
+                    disp.offset.wrapping_add(value)
+                };
+            }
+            [Token::Ident(name), tail @ ..] => {
+                tokens = tail;
+                if let Some(reg) = names.register(name) {
+                    if negative {
+                        return Err(Status::InvalidArgument);
+                    } else if base == Register::NONE {
+                        base = reg;
+                    } else if index == Register::NONE {
+                        index = reg;
+                        scale = 1;
+                    } else {
+                        return Err(Status::InvalidArgument);
+                    }
+                } else if negative || disp.symbol.is_some() {
+                    return Err(Status::InvalidArgument);
+                } else {
+                    disp.symbol = Some(name.clone());
+                }
+            }
+            _ => return Err(Status::InvalidArgument),
+        }
+    }
+
+    if first {
+        return Err(Status::InvalidArgument);
+    }
+    Ok((base, index, scale, disp))
+}
+
+fn size_keyword(word: &str) -> Option<u16> {
+    Some(match word.to_ascii_lowercase().as_str() {
+        "byte" => 1,
+        "word" => 2,
+        "dword" => 4,
+        "fword" => 6,
+        "qword" => 8,
+        "tbyte" => 10,
+        "xmmword" => 16,
+        "ymmword" => 32,
+        "zmmword" => 64,
+        _ => return None,
+    })
+}
+
+fn segment_prefix(reg: Register) -> Result<InstructionAttributes> {
+    Ok(match reg {
+        Register::ES => InstructionAttributes::HAS_SEGMENT_ES,
+        Register::CS => InstructionAttributes::HAS_SEGMENT_CS,
+        Register::SS => InstructionAttributes::HAS_SEGMENT_SS,
+        Register::DS => InstructionAttributes::HAS_SEGMENT_DS,
+        Register::FS => InstructionAttributes::HAS_SEGMENT_FS,
+        Register::GS => InstructionAttributes::HAS_SEGMENT_GS,
+        _ => return Err(Status::InvalidArgument),
+    })
+}
@@ -0,0 +1,312 @@
+//! Standard function prologues and epilogues.
+
+use crate::*;
+use alloc::vec::Vec;
+
+/// A 64-bit calling convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Abi {
+    /// System V AMD64, used by Linux, macOS and the BSDs.
+    SysV,
+    /// Microsoft x64, used by Windows.
+    Win64,
+}
+
+impl Abi {
+    /// Registers a function must preserve.
+    ///
+    /// For [`Abi::Win64`], `XMM6` to `XMM15` must be preserved as well.
+    pub fn callee_saved(self) -> &'static [Register] {
+        match self {
+            Abi::SysV => &[
+                Register::RBX,
+                Register::RBP,
+                Register::R12,
+                Register::R13,
+                Register::R14,
+                Register::R15,
+            ],
+            Abi::Win64 => &[
+                Register::RBX,
+                Register::RBP,
+                Register::RDI,
+                Register::RSI,
+                Register::R12,
+                Register::R13,
+                Register::R14,
+                Register::R15,
+            ],
+        }
+    }
+
+    /// Bytes the caller reserves below the return address for the callee
+    /// to spill its register arguments to.
+    pub fn shadow_space(self) -> u32 {
+        match self {
+            Abi::SysV => 0,
+            Abi::Win64 => 32,
+        }
+    }
+}
+
+/// Layout of an `rbp` based stack frame.
+///
+/// The prologue pushes `rbp`, sets up the frame pointer, pushes the saved
+/// registers and reserves space for the locals, plus shadow space for
+/// [`Abi::Win64`]. The reservation is rounded up so that `rsp` is 16-byte
+/// aligned after the prologue, as required at call sites.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// use zydis::asm::{self, templates::{Abi, Frame}};
+///
+/// let frame = Frame {
+///     abi: Abi::SysV,
+///     locals: 16,
+///     saved: &[Register::RBX],
+/// };
+/// assert_eq!(frame.stack_adjustment(), 24);
+///
+/// let mut code = asm::CodeBuffer::new(0);
+/// for request in frame.prologue().into_iter().chain(frame.epilogue()) {
+///     code.push(request).unwrap();
+/// }
+/// assert_eq!(
+///     code.bytes(),
+///     [
+///         0x55, // push rbp
+///         0x48, 0x89, 0xE5, // mov rbp, rsp
+///         0x53, // push rbx
+///         0x48, 0x83, 0xEC, 0x18, // sub rsp, 0x18
+///         0x48, 0x8D, 0x65, 0xF8, // lea rsp, [rbp-0x08]
+///         0x5B, // pop rbx
+///         0x5D, // pop rbp
+///         0xC3, // ret
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Frame<'a> {
+    /// Calling convention of the function.
+    pub abi: Abi,
+    /// Bytes of stack space needed for locals.
+    pub locals: u32,
+    /// Callee-saved 64-bit registers used by the function, not including
+    /// `rbp`.
+    pub saved: &'a [Register],
+}
+
+impl Frame<'_> {
+    /// Bytes subtracted from `rsp` after pushing the saved registers.
+    pub fn stack_adjustment(&self) -> u32 {
+        let pushed = 8 * self.saved.len() as u32;
+        let total = pushed + self.locals + self.abi.shadow_space();
+        total.next_multiple_of(16) - pushed
+    }
+
+    /// Returns the prologue setting up this frame.
+    pub fn prologue(&self) -> Vec<EncoderRequest> {
+        let mut insns = Vec::with_capacity(3 + self.saved.len());
+        insns.push(insn64!(PUSH RBP));
+        insns.push(insn64!(MOV RBP, RSP));
+        for &reg in self.saved {
+            insns.push(insn64!(PUSH(reg)));
+        }
+
+        let adjustment = self.stack_adjustment();
+        if adjustment != 0 {
+            insns.push(insn64!(SUB RSP, (adjustment)));
+        }
+
+        insns
+    }
+
+    /// Returns the epilogue tearing down this frame and returning.
+    ///
+    /// `rsp` is restored from `rbp`, so the epilogue stays valid if the
+    /// function adjusted the stack pointer in between.
+    pub fn epilogue(&self) -> Vec<EncoderRequest> {
+        let mut insns = Vec::with_capacity(3 + self.saved.len());
+        if self.stack_adjustment() != 0 {
+            if self.saved.is_empty() {
+                insns.push(insn64!(MOV RSP, RBP));
+            } else {
+                let offset = -8 * self.saved.len() as i64;
+                insns.push(insn64!(LEA RSP, qword ptr [RBP + (offset)]));
+            }
+        }
+        for &reg in self.saved.iter().rev() {
+            insns.push(insn64!(POP(reg)));
+        }
+        insns.push(insn64!(POP RBP));
+        insns.push(insn64!(RET));
+        insns
+    }
+}
+
+/// Source of an integer argument passed by [`call_stub`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Arg {
+    /// Value of a 64-bit general purpose register on entry to the stub.
+    Reg(Register),
+    /// A constant.
+    Imm(u64),
+    /// The qword at `[rsp + offset]` on entry to the stub.
+    Stack(i32),
+}
+
+impl Abi {
+    /// Registers used for the first integer arguments, in order.
+    pub fn int_args(self) -> &'static [Register] {
+        match self {
+            Abi::SysV => &[
+                Register::RDI,
+                Register::RSI,
+                Register::RDX,
+                Register::RCX,
+                Register::R8,
+                Register::R9,
+            ],
+            Abi::Win64 => &[Register::RCX, Register::RDX, Register::R8, Register::R9],
+        }
+    }
+}
+
+/// Returns a stub calling the function at `target` with the given integer
+/// arguments.
+///
+/// The stub aligns the stack, passes the arguments in registers and stack
+/// slots as required by `abi` (reserving shadow space for [`Abi::Win64`]),
+/// calls `target` via `rax` and restores `rsp` afterwards. The return value
+/// is left in `rax`. `r10`, `r11` and the argument registers are clobbered,
+/// and `al` isn't set for variadic SysV functions.
+///
+/// Register arguments may be passed in any order, swaps included. Returns
+/// [`Status::InvalidArgument`] if `rsp` or `rbp` is used as a source, since
+/// the stub uses them for its own frame, or if `r11`, `r10` and `rax` are all
+/// used as sources, leaving no scratch register.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// use zydis::asm::templates::{call_stub, Abi, Arg};
+///
+/// // f(rdx, rcx, 7)
+/// let args = [Arg::Reg(Register::RDX), Arg::Reg(Register::RCX), Arg::Imm(7)];
+/// let stub = call_stub(Abi::Win64, 0x1_4000_1000, &args).unwrap();
+///
+/// assert_eq!(
+///     stub[4..],
+///     [
+///         insn64!(MOV R11, RCX),
+///         insn64!(MOV RCX, RDX),
+///         insn64!(MOV RDX, R11),
+///         insn64!(MOV R8, 7),
+///         insn64!(MOV RAX, 0x1_4000_1000u64),
+///         insn64!(CALL RAX),
+///         insn64!(MOV RSP, RBP),
+///         insn64!(POP RBP),
+///     ]
+/// );
+///
+/// // No scratch register left.
+/// let args = [Register::R11, Register::R10, Register::RAX].map(Arg::Reg);
+/// assert_eq!(
+///     call_stub(Abi::Win64, 0, &args).unwrap_err(),
+///     Status::InvalidArgument
+/// );
+/// ```
+pub fn call_stub(abi: Abi, target: u64, args: &[Arg]) -> Result<Vec<EncoderRequest>> {
+    if args
+        .iter()
+        .any(|arg| matches!(arg, Arg::Reg(Register::RSP | Register::RBP)))
+    {
+        return Err(Status::InvalidArgument);
+    }
+
+    let arg_regs = abi.int_args();
+    let split = args.len().min(arg_regs.len());
+    let (reg_args, stack_args) = args.split_at(split);
+
+    // Scratch register that isn't the source of any argument.
+    let scratch = [Register::R11, Register::R10, Register::RAX]
+        .into_iter()
+        .find(|&reg| !args.contains(&Arg::Reg(reg)))
+        .ok_or(Status::InvalidArgument)?;
+
+    let mut insns = Vec::new();
+    insns.push(insn64!(PUSH RBP));
+    insns.push(insn64!(MOV RBP, RSP));
+    insns.push(insn64!(AND RSP, -16));
+
+    let shadow = abi.shadow_space();
+    let reserved = (shadow + 8 * stack_args.len() as u32).next_multiple_of(16);
+    if reserved != 0 {
+        insns.push(insn64!(SUB RSP, (reserved)));
+    }
+
+    // Entry `rsp` is 8 bytes above `rbp` after pushing it.
+    let entry_slot = |offset: i32| mem!(qword ptr [RBP + (i64::from(offset) + 8)]);
+
+    // Stack arguments go first, while all source registers are intact.
+    for (idx, arg) in stack_args.iter().enumerate() {
+        let disp = i64::from(shadow) + 8 * idx as i64;
+        let value = match *arg {
+            Arg::Reg(reg) => reg,
+            Arg::Imm(imm) => {
+                insns.push(insn64!(MOV(scratch), (imm)));
+                scratch
+            }
+            Arg::Stack(offset) => {
+                insns.push(insn64!(MOV(scratch), (entry_slot(offset))));
+                scratch
+            }
+        };
+        insns.push(insn64!(MOV qword ptr [RSP + (disp)], (value)));
+    }
+
+    // Register to register moves, breaking cycles via the scratch register.
+    let mut moves: Vec<(Register, Register)> = reg_args
+        .iter()
+        .zip(arg_regs)
+        .filter_map(|(arg, &dst)| match *arg {
+            Arg::Reg(src) if src != dst => Some((dst, src)),
+            _ => None,
+        })
+        .collect();
+    while !moves.is_empty() {
+        let ready = moves
+            .iter()
+            .position(|&(dst, _)| !moves.iter().any(|&(_, src)| src == dst));
+        match ready {
+            Some(idx) => {
+                let (dst, src) = moves.remove(idx);
+                insns.push(insn64!(MOV(dst), (src)));
+            }
+            None => {
+                let blocked = moves[0].0;
+                insns.push(insn64!(MOV(scratch), (blocked)));
+                for (_, src) in &mut moves {
+                    if *src == blocked {
+                        *src = scratch;
+                    }
+                }
+            }
+        }
+    }
+
+    for (arg, &dst) in reg_args.iter().zip(arg_regs) {
+        match *arg {
+            Arg::Reg(_) => (),
+            Arg::Imm(imm) => insns.push(insn64!(MOV(dst), (imm))),
+            Arg::Stack(offset) => insns.push(insn64!(MOV(dst), (entry_slot(offset)))),
+        }
+    }
+
+    insns.push(insn64!(MOV RAX, (target)));
+    insns.push(insn64!(CALL RAX));
+    insns.push(insn64!(MOV RSP, RBP));
+    insns.push(insn64!(POP RBP));
+    Ok(insns)
+}
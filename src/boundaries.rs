@@ -0,0 +1,138 @@
+//! Verification of externally provided instruction boundaries.
+
+use crate::*;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A problem with a claimed instruction start, see
+/// [`Decoder::validate_boundaries`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundaryIssue {
+    /// The claimed start lies outside of the code.
+    OutOfRange {
+        /// The claimed start.
+        start: u64,
+    },
+    /// No valid instruction starts at the claimed start, or it is cut short
+    /// by the end of the code ([`Status::NoMoreData`]).
+    Invalid {
+        /// The claimed start.
+        start: u64,
+        /// The error returned by the decoder.
+        status: Status,
+    },
+    /// Another claimed start lies inside of the instruction at `start`,
+    /// which ends at `end`.
+    Overlap {
+        /// The claimed start of the instruction.
+        start: u64,
+        /// The address following the instruction.
+        end: u64,
+        /// The other claimed start, lying inside of the instruction.
+        other: u64,
+    },
+}
+
+/// Result of [`Decoder::validate_boundaries`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoundaryReport {
+    /// Problems with individual claimed starts, ordered by address.
+    pub issues: Vec<BoundaryIssue>,
+    /// Byte ranges not covered by any valid claimed instruction, ordered by
+    /// address.
+    pub unreachable: Vec<Range<u64>>,
+}
+
+impl BoundaryReport {
+    /// Checks whether the claimed starts cover the code exactly.
+    #[inline]
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty() && self.unreachable.is_empty()
+    }
+}
+
+impl Decoder {
+    /// Checks claimed instruction starts, e.g. from a symbol table, a coverage
+    /// map or another disassembler, against the code.
+    ///
+    /// An instruction is decoded at every claimed start. Starts that don't
+    /// decode or that lie inside of another claimed instruction are reported
+    /// as issues. Bytes covered by none of the valid claimed instructions
+    /// are reported as unreachable. `base` is the address of the first byte.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // push rcx; lea eax, [rbp-0x01]; int3; int3
+    /// static CODE: &[u8] = &[0x51, 0x8D, 0x45, 0xFF, 0xCC, 0xCC];
+    ///
+    /// let claimed = [0x1000, 0x1001, 0x1003];
+    /// let report = Decoder::new64().validate_boundaries(CODE, 0x1000, &claimed);
+    /// assert_eq!(
+    ///     report.issues,
+    ///     [BoundaryIssue::Overlap { start: 0x1001, end: 0x1004, other: 0x1003 }]
+    /// );
+    /// assert_eq!(report.unreachable, [0x1005..0x1006]);
+    /// ```
+    pub fn validate_boundaries(
+        &self,
+        bytes: &[u8],
+        base: u64,
+        claimed_starts: &[u64],
+    ) -> BoundaryReport {
+        let mut starts = claimed_starts.to_vec();
+        starts.sort_unstable();
+        starts.dedup();
+
+        let mut report = BoundaryReport::default();
+        let mut covered = alloc::vec![false; bytes.len()];
+
+        for (i, &start) in starts.iter().enumerate() {
+            let Some(offset) = start
+                .checked_sub(base)
+                .and_then(|offset| usize::try_from(offset).ok())
+                .filter(|&offset| offset < bytes.len())
+            else {
+                report.issues.push(BoundaryIssue::OutOfRange { start });
+                continue;
+            };
+
+            let length = match self.decode_first::<NoOperands>(&bytes[offset..]) {
+                Ok(Some(insn)) => usize::from(insn.length),
+                Ok(None) => {
+                    let status = Status::NoMoreData;
+                    report.issues.push(BoundaryIssue::Invalid { start, status });
+                    continue;
+                }
+                Err(status) => {
+                    report.issues.push(BoundaryIssue::Invalid { start, status });
+                    continue;
+                }
+            };
+
+            covered[offset..offset + length].fill(true);
+
+            let end = start + length as u64;
+            if let Some(&other) = starts.get(i + 1).filter(|&&other| other < end) {
+                report
+                    .issues
+                    .push(BoundaryIssue::Overlap { start, end, other });
+            }
+        }
+
+        let mut offset = 0;
+        while let Some(gap) = covered[offset..].iter().position(|&c| !c) {
+            let gap_start = offset + gap;
+            let gap_len = covered[gap_start..]
+                .iter()
+                .position(|&c| c)
+                .unwrap_or(covered.len() - gap_start);
+            offset = gap_start + gap_len;
+            report
+                .unreachable
+                .push(base + gap_start as u64..base + offset as u64);
+        }
+
+        report
+    }
+}
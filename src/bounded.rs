@@ -0,0 +1,69 @@
+//! Allocation-free output into fixed capacity [`heapless`] containers.
+//!
+//! `heapless::Vec` implements [`Extend`], but panics once full. The methods
+//! here report [`Status::InsufficientBufferSize`] instead and leave the
+//! container untouched on failure.
+
+use crate::*;
+
+#[cfg(feature = "encoder")]
+impl EncoderRequest {
+    /// Appends the encoded instruction to the given fixed capacity buffer.
+    ///
+    /// On failure the output buffer remains untouched.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let mut code = heapless::Vec::<u8, 4>::new();
+    /// insn64!(XOR RAX, RAX).encode_heapless(&mut code).unwrap();
+    /// assert_eq!(code, [0x48, 0x31, 0xC0]);
+    ///
+    /// let res = insn64!(MOV RAX, 0x1337).encode_heapless(&mut code);
+    /// assert_eq!(res, Err(Status::InsufficientBufferSize));
+    /// assert_eq!(code.len(), 3);
+    /// ```
+    pub fn encode_heapless<const N: usize>(&self, buf: &mut heapless::Vec<u8, N>) -> Result<usize> {
+        let mut tmp = [0; MAX_INSTRUCTION_LENGTH];
+        let length = self.encode_into(&mut tmp)?;
+        buf.extend_from_slice(&tmp[..length])
+            .map_err(|_| Status::InsufficientBufferSize)?;
+        Ok(length)
+    }
+}
+
+#[cfg(feature = "formatter")]
+impl<UserData> Formatter<UserData> {
+    /// Appends the formatted instruction to the given fixed capacity string.
+    ///
+    /// The `ip` may be `None`, in which case relative address formatting is
+    /// used. Otherwise absolute addresses are used. On failure the string
+    /// remains untouched.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// static CODE: &[u8] = &[0x48, 0x31, 0xC0];
+    ///
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(CODE)
+    ///     .unwrap()
+    ///     .unwrap();
+    ///
+    /// let mut text = heapless::String::<32>::new();
+    /// Formatter::intel().format_heapless(None, &insn, &mut text).unwrap();
+    /// assert_eq!(text, "xor rax, rax");
+    /// ```
+    pub fn format_heapless<O: Operands, const N: usize>(
+        &self,
+        ip: Option<u64>,
+        insn: &Instruction<O>,
+        out: &mut heapless::String<N>,
+    ) -> Result<()> {
+        crate::formatter::with_scratch_buffer(|buffer| {
+            self.format_ex(ip, insn, buffer, None)?;
+            out.push_str(buffer.as_str()?)
+                .map_err(|_| Status::InsufficientBufferSize)
+        })
+    }
+}
@@ -0,0 +1,169 @@
+//! Memoization of decoding results for repeatedly decoded code.
+
+use crate::*;
+use std::collections::{HashMap, VecDeque};
+
+/// Decoder configuration and the bytes an instruction was decoded from.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    decoder: ffi::Decoder,
+    window: [u8; MAX_INSTRUCTION_LENGTH],
+    window_len: u8,
+}
+
+impl CacheKey {
+    fn new(decoder: &Decoder, buffer: &[u8]) -> Self {
+        let window_len = buffer.len().min(MAX_INSTRUCTION_LENGTH);
+        let mut window = [0; MAX_INSTRUCTION_LENGTH];
+        window[..window_len].copy_from_slice(&buffer[..window_len]);
+        Self {
+            decoder: decoder.raw().clone(),
+            window,
+            window_len: window_len as u8,
+        }
+    }
+}
+
+/// Hit and miss counters of a [`DecodeCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CacheStats {
+    /// Number of lookups answered from the cache.
+    pub hits: u64,
+    /// Number of lookups that required decoding.
+    pub misses: u64,
+    /// Number of entries dropped to stay within the capacity.
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// The share of lookups answered from the cache, or `0.0` if there were
+    /// no lookups yet.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+/// Cache mapping decoder configurations and instruction bytes to decoded
+/// instructions.
+///
+/// Tools like tracers decode the same hot code over and over again. With a
+/// cache in front of the decoder, repeated decoding of identical bytes is a
+/// hash map lookup. Entries are keyed by content rather than address, so
+/// identical code at different addresses shares an entry and modified code
+/// never yields stale results. The key covers the first
+/// [`MAX_INSTRUCTION_LENGTH`] bytes of the buffer, so identical instructions
+/// followed by different bytes get separate entries.
+///
+/// Once `capacity` entries are stored, the oldest entry is evicted for every
+/// new one. Failed decoding attempts aren't cached.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// static CODE: &[u8] = &[0x51, 0x8D, 0x45, 0xFF];
+/// let decoder = Decoder::new64();
+/// let mut cache = DecodeCache::<VisibleOperands>::new(1024);
+///
+/// for _ in 0..4 {
+///     let insn = cache.decode(&decoder, CODE).unwrap().unwrap();
+///     assert_eq!(insn.mnemonic, Mnemonic::PUSH);
+/// }
+///
+/// assert_eq!(cache.stats().misses, 1);
+/// assert_eq!(cache.stats().hit_rate(), 0.75);
+/// ```
+#[derive(Clone)]
+pub struct DecodeCache<O: Operands = VisibleOperands> {
+    entries: HashMap<CacheKey, Instruction<O>>,
+    order: VecDeque<CacheKey>,
+    capacity: usize,
+    stats: CacheStats,
+}
+
+impl<O: Operands> DecodeCache<O> {
+    /// Creates an empty cache holding up to `capacity` instructions, but at
+    /// least one.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: capacity.max(1),
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Decodes the first instruction in `buffer` like
+    /// [`Decoder::decode_first`], answering from the cache if possible.
+    pub fn decode(&mut self, decoder: &Decoder, buffer: &[u8]) -> Result<Option<&Instruction<O>>> {
+        let key = CacheKey::new(decoder, buffer);
+        if self.entries.contains_key(&key) {
+            self.stats.hits += 1;
+            return Ok(self.entries.get(&key));
+        }
+
+        self.stats.misses += 1;
+        let Some(insn) = decoder.decode_first(buffer)? else {
+            return Ok(None);
+        };
+
+        self.shrink_to(self.capacity - 1);
+        self.order.push_back(key.clone());
+        Ok(Some(self.entries.entry(key).or_insert(insn)))
+    }
+
+    /// The maximum number of cached instructions.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the capacity (but at least one), evicting the oldest entries
+    /// if necessary.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.shrink_to(self.capacity);
+    }
+
+    /// The number of cached instructions.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks whether the cache is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all entries. The statistics are kept.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    /// Returns the hit and miss counters.
+    #[inline]
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Resets the hit and miss counters.
+    #[inline]
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    fn shrink_to(&mut self, len: usize) {
+        while self.entries.len() > len {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+            self.stats.evictions += 1;
+        }
+    }
+}
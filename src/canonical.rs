@@ -0,0 +1,98 @@
+//! Normalization of semantically equivalent instruction encodings.
+
+use crate::*;
+use alloc::vec::Vec;
+
+/// Prefixes without effect on the semantics of an instruction.
+const HINT_PREFIXES: InstructionAttributes =
+    InstructionAttributes::HAS_BRANCH_TAKEN.union(InstructionAttributes::HAS_BRANCH_NOT_TAKEN);
+
+impl<const N: usize> Instruction<OperandArrayVec<N>> {
+    /// Converts the instruction into an encoder request for its canonical
+    /// encoding.
+    ///
+    /// Equivalent encodings of the same instruction, e.g. with redundant
+    /// prefixes or using an alternative opcode, yield identical requests,
+    /// which makes them suitable for hashing, diffing or signature
+    /// generation:
+    ///
+    /// - Segment overrides are only kept if they change the segment of a
+    ///   memory access, i.e. `fs`/`gs` in 64 bit mode and non-default
+    ///   segments otherwise. Duplicate overrides collapse into the effective
+    ///   one.
+    /// - Branch hints are dropped.
+    /// - Legacy prefixes the instruction ignores and meaningless `REX`
+    ///   prefixes are dropped, as the encoder emits only required ones.
+    ///
+    /// Fails under the same conditions as [`EncoderRequest::from_instruction`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let decoder = Decoder::new64();
+    /// let canonical = |code: &[u8]| {
+    ///     decoder
+    ///         .decode_first::<VisibleOperands>(code)
+    ///         .unwrap()
+    ///         .unwrap()
+    ///         .canonical_bytes()
+    ///         .unwrap()
+    /// };
+    ///
+    /// // mov rax, rcx
+    /// assert_eq!(canonical(&[0x48, 0x89, 0xC8]), [0x48, 0x89, 0xC8]);
+    /// // mov rax, rcx using the alternative `8B /r` form
+    /// assert_eq!(canonical(&[0x48, 0x8B, 0xC1]), [0x48, 0x89, 0xC8]);
+    /// // es ds mov rax, [rcx]
+    /// assert_eq!(canonical(&[0x26, 0x3E, 0x48, 0x8B, 0x01]), [0x48, 0x8B, 0x01]);
+    /// // mov rax, fs:[rcx]
+    /// assert_eq!(canonical(&[0x64, 0x48, 0x8B, 0x01]), [0x64, 0x48, 0x8B, 0x01]);
+    /// ```
+    pub fn canonicalize(&self) -> Result<EncoderRequest> {
+        let request = EncoderRequest::from_instruction(self)?;
+
+        let segment = self
+            .visible_operands()
+            .iter()
+            .find_map(|op| match &op.kind {
+                ffi::DecodedOperandKind::Mem(mem) if mem.ty != MemoryOperandType::AGEN => {
+                    Some(self.segment_prefix(mem))
+                }
+                _ => None,
+            });
+
+        let prefixes = request
+            .prefixes
+            .difference(InstructionAttributes::HAS_SEGMENT | HINT_PREFIXES)
+            .union(segment.unwrap_or(InstructionAttributes::empty()));
+        Ok(request.set_prefixes(prefixes))
+    }
+
+    /// Re-encodes the instruction using its canonical encoding.
+    ///
+    /// See [`Instruction::canonicalize`].
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>> {
+        self.canonicalize()?.encode()
+    }
+
+    /// The segment prefix required to access `mem`, if any.
+    fn segment_prefix(&self, mem: &ffi::MemoryInfo) -> InstructionAttributes {
+        let default = match mem.base {
+            Register::SP | Register::ESP | Register::RSP => Register::SS,
+            Register::BP | Register::EBP | Register::RBP => Register::SS,
+            _ => Register::DS,
+        };
+
+        match mem.segment {
+            Register::FS => InstructionAttributes::HAS_SEGMENT_FS,
+            Register::GS => InstructionAttributes::HAS_SEGMENT_GS,
+            _ if self.machine_mode == MachineMode::LONG_64 => InstructionAttributes::empty(),
+            segment if segment == default => InstructionAttributes::empty(),
+            Register::CS => InstructionAttributes::HAS_SEGMENT_CS,
+            Register::SS => InstructionAttributes::HAS_SEGMENT_SS,
+            Register::DS => InstructionAttributes::HAS_SEGMENT_DS,
+            Register::ES => InstructionAttributes::HAS_SEGMENT_ES,
+            _ => InstructionAttributes::empty(),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Heuristic classification of byte ranges as code or data.
+
+use crate::*;
+use core::ops::Range;
+use std::vec::Vec;
+
+/// Whether a byte range most likely contains code or data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegionKind {
+    /// Executable instructions.
+    Code,
+    /// Anything else, e.g. tables, strings or padding.
+    Data,
+}
+
+/// Code likelihood of a byte range, along with the metrics it's based on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CodeScore {
+    /// Share of bytes covered by successfully decoded instructions.
+    pub decoded_ratio: f64,
+    /// Number of failed decoding attempts per byte.
+    pub invalid_density: f64,
+    /// Shannon entropy of the bytes, in bits per byte (`0.0..=8.0`).
+    pub entropy: f64,
+    /// Combined score between `0.0` (data) and `1.0` (code).
+    pub score: f64,
+}
+
+impl CodeScore {
+    /// Classifies the range, treating scores of `0.5` and above as code.
+    #[inline]
+    pub fn kind(&self) -> RegionKind {
+        if self.score >= 0.5 {
+            RegionKind::Code
+        } else {
+            RegionKind::Data
+        }
+    }
+}
+
+/// A classified range produced by [`classify_regions`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassifiedRegion {
+    /// Address range covered by the region.
+    pub range: Range<u64>,
+    /// The classification of the region.
+    pub kind: RegionKind,
+    /// Score of the whole region.
+    pub score: CodeScore,
+}
+
+/// Scores how likely `bytes` contains code.
+///
+/// The bytes are decoded linearly, skipping a single byte after every
+/// decoding failure. The score combines the share of successfully decoded
+/// bytes, the density of decoding failures, the share of instructions
+/// decoded from zero fill (`add [rax], al`) and the byte entropy: real code
+/// rarely has very low (padding, zero fill) or very high (compressed or
+/// encrypted data) entropy.
+///
+/// This is a heuristic and best applied to ranges of at least a few dozen
+/// bytes.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// static CODE: &[u8] = &[
+///     0x55, 0x48, 0x89, 0xE5, 0x48, 0x83, 0xEC, 0x10, 0x89, 0x7D, 0xFC, 0x8B,
+///     0x45, 0xFC, 0x83, 0xC0, 0x01, 0x48, 0x83, 0xC4, 0x10, 0x5D, 0xC3,
+/// ];
+/// let decoder = Decoder::new64();
+///
+/// assert_eq!(score_code(&decoder, CODE).kind(), RegionKind::Code);
+/// assert_eq!(score_code(&decoder, &[0; 64]).kind(), RegionKind::Data);
+/// ```
+pub fn score_code(decoder: &Decoder, bytes: &[u8]) -> CodeScore {
+    if bytes.is_empty() {
+        return CodeScore {
+            decoded_ratio: 0.0,
+            invalid_density: 0.0,
+            entropy: 0.0,
+            score: 0.0,
+        };
+    }
+
+    let decoder = MinimalDecoder::from(decoder.clone());
+    let (mut decoded, mut invalid, mut insns, mut zero_fill) = (0usize, 0usize, 0usize, 0usize);
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        match decoder.decode(rest) {
+            Ok(Some(insn)) => {
+                let length = usize::from(insn.length);
+                if rest[..length].iter().all(|&b| b == 0) {
+                    zero_fill += 1;
+                }
+                decoded += length;
+                insns += 1;
+                rest = &rest[length..];
+            }
+            _ => {
+                invalid += 1;
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    let len = bytes.len() as f64;
+    let decoded_ratio = decoded as f64 / len;
+    let invalid_density = invalid as f64 / len;
+    let zero_fill_ratio = if insns == 0 {
+        0.0
+    } else {
+        zero_fill as f64 / insns as f64
+    };
+    let entropy = entropy(bytes);
+
+    let entropy_factor = match entropy {
+        e if e < 1.0 => 0.0,
+        e if e < 3.0 => (e - 1.0) / 2.0,
+        e if e <= 7.5 => 1.0,
+        e => 1.0 - (e - 7.5),
+    };
+
+    let score = decoded_ratio
+        * (1.0 - (4.0 * invalid_density).min(1.0))
+        * (1.0 - zero_fill_ratio)
+        * entropy_factor;
+
+    CodeScore {
+        decoded_ratio,
+        invalid_density,
+        entropy,
+        score,
+    }
+}
+
+/// Splits `bytes` into windows of `window` bytes, scores each and merges
+/// adjacent windows of the same kind into regions.
+///
+/// `base` is the address of the first byte.
+///
+/// # Panics
+///
+/// If `window` is `0`.
+pub fn classify_regions(
+    decoder: &Decoder,
+    bytes: &[u8],
+    base: u64,
+    window: usize,
+) -> Vec<ClassifiedRegion> {
+    assert_ne!(window, 0, "window size must be non-zero");
+
+    let mut regions: Vec<(Range<usize>, RegionKind)> = Vec::new();
+    for (idx, chunk) in bytes.chunks(window).enumerate() {
+        let start = idx * window;
+        let kind = score_code(decoder, chunk).kind();
+        match regions.last_mut() {
+            Some((range, last)) if *last == kind => range.end = start + chunk.len(),
+            _ => regions.push((start..start + chunk.len(), kind)),
+        }
+    }
+
+    regions
+        .into_iter()
+        .map(|(range, kind)| ClassifiedRegion {
+            range: base + range.start as u64..base + range.end as u64,
+            kind,
+            score: score_code(decoder, &bytes[range]),
+        })
+        .collect()
+}
+
+/// Shannon entropy of `bytes` in bits per byte.
+fn entropy(bytes: &[u8]) -> f64 {
+    let mut histogram = [0usize; 256];
+    for &b in bytes {
+        histogram[usize::from(b)] += 1;
+    }
+
+    let len = bytes.len() as f64;
+    histogram
+        .iter()
+        .filter(|&&n| n != 0)
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entropy_bounds() {
+        assert_eq!(entropy(&[0x90; 32]), 0.0);
+        assert_eq!(entropy(&[0x00, 0xFF]), 1.0);
+
+        let all: Vec<u8> = (0..=255).collect();
+        assert_eq!(entropy(&all), 8.0);
+    }
+}
@@ -0,0 +1,193 @@
+//! Lazily decoded view of mutable code, e.g. for tracking self-modifying code.
+
+use crate::*;
+use alloc::{collections::BTreeMap, vec::Vec};
+
+/// A straight-line sequence of instructions, see [`CodeView::block`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BasicBlock {
+    /// Address of the first instruction.
+    pub start: u64,
+    /// Address directly following the last instruction.
+    pub end: u64,
+    /// Addresses of all instructions in the block, in order.
+    pub instructions: Vec<u64>,
+    /// The successors of the last instruction.
+    ///
+    /// If the block was cut short by the end of the code or an undecodable
+    /// instruction, this is the fallthrough into that location.
+    pub successors: Successors,
+}
+
+/// Code that is decoded on demand and can be modified afterwards.
+///
+/// Decoded instructions and basic blocks are cached. [`CodeView::write`]
+/// only invalidates the cached entries overlapping the modified bytes, which
+/// are then re-decoded lazily on their next access. This avoids rebuilding
+/// the whole model when tracking self-modifying code.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// // nop; nop; ret
+/// let mut view = CodeView::new(Decoder::new64(), vec![0x90, 0x90, 0xC3], 0x1000);
+/// assert_eq!(view.block(0x1000).unwrap().end, 0x1003);
+///
+/// // Patch the second nop into a ret.
+/// view.write(0x1001, &[0xC3]).unwrap();
+/// assert!(view.is_decoded(0x1000));
+/// assert!(!view.is_decoded(0x1001));
+///
+/// assert_eq!(view.instruction(0x1001).unwrap().mnemonic, Mnemonic::RET);
+/// assert_eq!(view.block(0x1000).unwrap().end, 0x1002);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CodeView {
+    decoder: Decoder,
+    base: u64,
+    bytes: Vec<u8>,
+    instructions: BTreeMap<u64, Instruction<VisibleOperands>>,
+    blocks: BTreeMap<u64, BasicBlock>,
+}
+
+impl CodeView {
+    /// Creates a view of `bytes`, with the first byte located at `base`.
+    pub fn new(decoder: Decoder, bytes: Vec<u8>, base: u64) -> Self {
+        Self {
+            decoder,
+            base,
+            bytes,
+            instructions: BTreeMap::new(),
+            blocks: BTreeMap::new(),
+        }
+    }
+
+    /// The address of the first byte.
+    #[inline]
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    /// The current code.
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Checks whether the instruction at `ip` is currently cached.
+    #[inline]
+    pub fn is_decoded(&self, ip: u64) -> bool {
+        self.instructions.contains_key(&ip)
+    }
+
+    /// Returns the instruction at `ip`, decoding it if it isn't cached.
+    ///
+    /// Returns [`Status::OutOfRange`] if `ip` is outside of the code.
+    pub fn instruction(&mut self, ip: u64) -> Result<&Instruction<VisibleOperands>> {
+        if !self.instructions.contains_key(&ip) {
+            let offset = self.offset(ip)?;
+            let insn = self
+                .decoder
+                .decode_first(&self.bytes[offset..])?
+                .ok_or(Status::OutOfRange)?;
+            self.instructions.insert(ip, insn);
+        }
+
+        Ok(&self.instructions[&ip])
+    }
+
+    /// Returns the basic block starting at `ip`, decoding it if it isn't
+    /// cached.
+    ///
+    /// The block ends after the first instruction that doesn't simply fall
+    /// through to the next one, or before the end of the code or an
+    /// undecodable instruction. Blocks are not split at jump targets.
+    pub fn block(&mut self, ip: u64) -> Result<&BasicBlock> {
+        if !self.blocks.contains_key(&ip) {
+            let block = self.decode_block(ip)?;
+            self.blocks.insert(ip, block);
+        }
+
+        Ok(&self.blocks[&ip])
+    }
+
+    fn decode_block(&mut self, start: u64) -> Result<BasicBlock> {
+        let mut instructions = Vec::new();
+        let mut ip = start;
+        let mut insn = self.instruction(ip)?;
+
+        let (end, successors) = loop {
+            let successors = insn.successors(ip);
+            let next = insn.next_ip(ip);
+            instructions.push(ip);
+
+            if *successors != [Successor::Fallthrough(next)] {
+                break (next, successors);
+            }
+            match self.instruction(next) {
+                Ok(next_insn) => insn = next_insn,
+                Err(_) => break (next, successors),
+            }
+            ip = next;
+        };
+
+        Ok(BasicBlock {
+            start,
+            end,
+            successors,
+            instructions,
+        })
+    }
+
+    /// Overwrites the code at `ip` with `bytes`.
+    ///
+    /// Cached instructions and blocks that may have read the written range
+    /// are invalidated, including blocks that ended up to
+    /// `MAX_INSTRUCTION_LENGTH - 1` bytes before it because decoding failed
+    /// there. Returns [`Status::OutOfRange`] if the range isn't
+    /// completely inside of the code, in which case nothing is written.
+    pub fn write(&mut self, ip: u64, bytes: &[u8]) -> Result<()> {
+        let offset = self.offset(ip)?;
+        let end = ip
+            .checked_add(bytes.len() as u64)
+            .ok_or(Status::OutOfRange)?;
+        let dst = offset
+            .checked_add(bytes.len())
+            .and_then(|end| self.bytes.get_mut(offset..end))
+            .ok_or(Status::OutOfRange)?;
+        dst.copy_from_slice(bytes);
+
+        // Instructions, and blocks cut short by an undecodable instruction,
+        // starting this far before `ip` may have read the written bytes.
+        let first = ip.saturating_sub(MAX_INSTRUCTION_LENGTH as u64 - 1);
+        let stale: Vec<u64> = self
+            .instructions
+            .range(first..end)
+            .filter(|(&addr, insn)| insn.next_ip(addr) > ip)
+            .map(|(&addr, _)| addr)
+            .collect();
+        for addr in stale {
+            self.instructions.remove(&addr);
+        }
+
+        self.blocks
+            .retain(|_, block| block.end < first || block.start >= end);
+
+        Ok(())
+    }
+
+    fn offset(&self, ip: u64) -> Result<usize> {
+        ip.checked_sub(self.base)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .filter(|&offset| offset < self.bytes.len())
+            .ok_or(Status::OutOfRange)
+    }
+}
+
+/// Exposes the current code, e.g. for [`Decoder::decode_all_memory`].
+impl Memory for CodeView {
+    #[inline]
+    fn read(&self, addr: u64, buf: &mut [u8]) -> Result<usize> {
+        self.bytes.read(addr.wrapping_sub(self.base), buf)
+    }
+}
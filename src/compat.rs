@@ -0,0 +1,107 @@
+//! Detection of mismatches between the bindings and the linked C library.
+
+use crate::*;
+
+/// The major and minor version of Zydis these bindings were written for.
+pub const EXPECTED_VERSION: (u16, u16) = (4, 1);
+
+/// Features of the C library required by the enabled crate features.
+const REQUIRED_FEATURES: &[Feature] = &[
+    Feature::DECODER,
+    #[cfg(feature = "encoder")]
+    Feature::ENCODER,
+    #[cfg(feature = "formatter")]
+    Feature::FORMATTER,
+];
+
+/// All features of the C library.
+const ALL_FEATURES: [Feature; FEATURE_MAX_VALUE + 1] = [
+    Feature::DECODER,
+    Feature::ENCODER,
+    Feature::FORMATTER,
+    Feature::AVX512,
+    Feature::KNC,
+    Feature::SEGMENT,
+];
+
+impl Feature {
+    /// Checks whether the linked C library was built with this feature.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// assert!(Feature::DECODER.is_enabled());
+    /// ```
+    #[inline]
+    pub fn is_enabled(self) -> bool {
+        unsafe { ffi::ZydisIsFeatureEnabled(self) == Status::True }
+    }
+}
+
+/// The state of a single feature of the C library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FeatureCheck {
+    /// The feature.
+    pub feature: Feature,
+    /// Whether the enabled crate features rely on it.
+    pub required: bool,
+    /// Whether the C library was built with it.
+    pub enabled: bool,
+}
+
+/// Result of [`check_runtime_compatibility`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompatibilityReport {
+    /// Version of the linked C library, see [`version`].
+    pub library_version: (u16, u16, u16, u16),
+    /// See [`EXPECTED_VERSION`].
+    pub expected_version: (u16, u16),
+    /// The state of all features of the C library.
+    pub features: [FeatureCheck; FEATURE_MAX_VALUE + 1],
+}
+
+impl CompatibilityReport {
+    /// Checks whether the library version is compatible, i.e. has the
+    /// expected major version and at least the expected minor version.
+    pub fn is_version_compatible(&self) -> bool {
+        let (major, minor, ..) = self.library_version;
+        major == self.expected_version.0 && minor >= self.expected_version.1
+    }
+
+    /// Returns the features required by the bindings but missing in the
+    /// C library.
+    pub fn missing_features(&self) -> impl Iterator<Item = Feature> + '_ {
+        self.features
+            .iter()
+            .filter(|check| check.required && !check.enabled)
+            .map(|check| check.feature)
+    }
+
+    /// Checks whether the version is compatible and no feature is missing.
+    pub fn is_compatible(&self) -> bool {
+        self.is_version_compatible() && self.missing_features().next().is_none()
+    }
+}
+
+/// Compares the version and features of the linked C library against the
+/// ones these bindings were built for.
+///
+/// The vendored C library always matches. This is mostly useful when linking
+/// a C library that was built separately.
+///
+/// # Examples
+/// ```
+/// let report = zydis::check_runtime_compatibility();
+/// assert!(report.is_compatible(), "{report:?}");
+/// ```
+pub fn check_runtime_compatibility() -> CompatibilityReport {
+    CompatibilityReport {
+        library_version: version(),
+        expected_version: EXPECTED_VERSION,
+        features: ALL_FEATURES.map(|feature| FeatureCheck {
+            feature,
+            required: REQUIRED_FEATURES.contains(&feature),
+            enabled: feature.is_enabled(),
+        }),
+    }
+}
@@ -0,0 +1,129 @@
+//! Extraction of immediates and displacements from code.
+
+use crate::*;
+use alloc::vec::Vec;
+
+/// Where a [`Constant`] is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConstantKind {
+    /// An immediate operand.
+    Immediate,
+    /// The displacement of a memory operand.
+    Displacement,
+}
+
+/// An immediate or displacement found by [`Decoder::extract_constants`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Constant {
+    /// Address of the instruction.
+    pub ip: u64,
+    /// Index of the operand the constant belongs to.
+    pub operand_index: usize,
+    /// Whether the constant is an immediate or a displacement.
+    pub kind: ConstantKind,
+    /// The value, sign-extended to 64 bits if `is_signed` is set.
+    pub value: u64,
+    /// Whether the value is signed. Displacements are always signed.
+    pub is_signed: bool,
+    /// Whether the value is relative to the next instruction, e.g. a branch
+    /// offset.
+    pub is_relative: bool,
+    /// Offset of the constant within the instruction, in bytes.
+    pub offset: u8,
+    /// Physical size of the constant, in bits.
+    pub size: u8,
+}
+
+impl Constant {
+    /// Returns the value as a signed integer.
+    #[inline]
+    pub fn value_i64(&self) -> i64 {
+        self.value as i64
+    }
+
+    /// Returns the address of the constant's bytes.
+    #[inline]
+    pub fn address(&self) -> u64 {
+        self.ip.wrapping_add(self.offset.into())
+    }
+}
+
+impl Decoder {
+    /// Returns every immediate and displacement encoded in `bytes`, e.g. to
+    /// hunt for magic numbers, string offsets or cryptographic constants.
+    ///
+    /// The buffer is decoded linearly, starting at `base`. Bytes that don't
+    /// decode to a valid instruction are skipped one at a time. Implicit
+    /// constants that aren't encoded in the instruction, like the `1` in
+    /// `shl eax, 1`, aren't reported.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // mov eax, 0x67452301; mov ecx, [rbx+0x10]; jmp -2
+    /// static CODE: &[u8] = &[
+    ///     0xB8, 0x01, 0x23, 0x45, 0x67, 0x8B, 0x4B, 0x10, 0xEB, 0xFE,
+    /// ];
+    ///
+    /// let constants = Decoder::new64().extract_constants(CODE, 0x1000);
+    /// let found: Vec<_> = constants
+    ///     .iter()
+    ///     .map(|c| (c.address(), c.kind, c.value_i64(), c.is_relative))
+    ///     .collect();
+    /// assert_eq!(
+    ///     found,
+    ///     [
+    ///         (0x1001, ConstantKind::Immediate, 0x67452301, false),
+    ///         (0x1007, ConstantKind::Displacement, 0x10, false),
+    ///         (0x1009, ConstantKind::Immediate, -2, true),
+    ///     ]
+    /// );
+    /// ```
+    pub fn extract_constants(&self, bytes: &[u8], base: u64) -> Vec<Constant> {
+        let mut constants = Vec::new();
+        let mut offset = 0;
+
+        while offset < bytes.len() {
+            let ip = base.wrapping_add(offset as u64);
+            let Ok(Some(insn)) = self.decode_first::<VisibleOperands>(&bytes[offset..]) else {
+                offset += 1;
+                continue;
+            };
+            offset += usize::from(insn.length);
+
+            for (operand_index, op) in insn.operands().iter().enumerate() {
+                let constant = match &op.kind {
+                    ffi::DecodedOperandKind::Imm(imm) => {
+                        let Some(raw) = insn.imm_info_for_operand(operand_index) else {
+                            continue;
+                        };
+                        Constant {
+                            ip,
+                            operand_index,
+                            kind: ConstantKind::Immediate,
+                            value: imm.value,
+                            is_signed: imm.is_signed,
+                            is_relative: imm.is_relative,
+                            offset: raw.offset,
+                            size: raw.size,
+                        }
+                    }
+                    ffi::DecodedOperandKind::Mem(mem) if mem.disp.has_displacement => Constant {
+                        ip,
+                        operand_index,
+                        kind: ConstantKind::Displacement,
+                        value: mem.disp.displacement as u64,
+                        is_signed: true,
+                        is_relative: matches!(mem.base, Register::RIP | Register::EIP),
+                        offset: insn.raw.disp.offset,
+                        size: insn.raw.disp.size,
+                    },
+                    _ => continue,
+                };
+                constants.push(constant);
+            }
+        }
+
+        constants
+    }
+}
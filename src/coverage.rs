@@ -0,0 +1,177 @@
+//! Code coverage from traces of executed addresses.
+
+use crate::*;
+use alloc::{format, vec::Vec};
+
+/// Execution count of a single instruction, see [`Coverage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InstructionHits {
+    /// Address of the instruction.
+    pub ip: u64,
+    /// Length of the instruction.
+    pub length: u8,
+    /// Number of times the instruction was executed.
+    pub hits: u64,
+}
+
+/// Per-instruction and per-byte coverage of decoded code.
+///
+/// Built from the instructions of a code region, then fed with executed
+/// addresses, e.g. from a tracer. Every executed address counts as a hit of
+/// the instruction starting there. A byte is covered if it's part of an
+/// instruction with at least one hit.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// // push rcx; lea eax, [rbp-0x01]; int3
+/// static CODE: &[u8] = &[0x51, 0x8D, 0x45, 0xFF, 0xCC];
+///
+/// let decoder = Decoder::new64();
+/// let mut coverage = Coverage::new(
+///     decoder
+///         .decode_all::<NoOperands>(CODE, 0x1000)
+///         .map(|r| r.map(|(ip, _, insn)| (ip, insn.length)).unwrap()),
+/// );
+/// coverage.record_all([0x1000, 0x1001, 0x1000, 0x2000]);
+///
+/// assert_eq!(coverage.hits(0x1000), 2);
+/// assert_eq!(coverage.unknown_hits(), 1);
+/// assert!(coverage.is_byte_covered(0x1003));
+/// assert!(!coverage.is_byte_covered(0x1004));
+/// assert_eq!(coverage.byte_bitmap(), [0b01111]);
+/// assert_eq!(coverage.instruction_bitmap(), [true, true, false]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Coverage {
+    instructions: Vec<InstructionHits>,
+    unknown_hits: u64,
+}
+
+impl Coverage {
+    /// Creates coverage without hits for the given instructions, passed as
+    /// address and length.
+    pub fn new(instructions: impl IntoIterator<Item = (u64, u8)>) -> Self {
+        let mut instructions: Vec<_> = instructions
+            .into_iter()
+            .map(|(ip, length)| InstructionHits {
+                ip,
+                length,
+                hits: 0,
+            })
+            .collect();
+        instructions.sort_unstable_by_key(|insn| insn.ip);
+        instructions.dedup_by_key(|insn| insn.ip);
+
+        Self {
+            instructions,
+            unknown_hits: 0,
+        }
+    }
+
+    /// Records an execution of the instruction at `ip`.
+    pub fn record(&mut self, ip: u64) {
+        match self.instructions.binary_search_by_key(&ip, |insn| insn.ip) {
+            Ok(idx) => self.instructions[idx].hits += 1,
+            Err(_) => self.unknown_hits += 1,
+        }
+    }
+
+    /// Records an execution of every address in `trace`.
+    pub fn record_all(&mut self, trace: impl IntoIterator<Item = u64>) {
+        for ip in trace {
+            self.record(ip);
+        }
+    }
+
+    /// Returns the number of hits of the instruction at `ip`.
+    pub fn hits(&self, ip: u64) -> u64 {
+        self.instructions
+            .binary_search_by_key(&ip, |insn| insn.ip)
+            .map_or(0, |idx| self.instructions[idx].hits)
+    }
+
+    /// Returns the number of recorded addresses that aren't the start of a
+    /// known instruction.
+    #[inline]
+    pub fn unknown_hits(&self) -> u64 {
+        self.unknown_hits
+    }
+
+    /// Returns all instructions with their hits, ordered by address.
+    #[inline]
+    pub fn instructions(&self) -> &[InstructionHits] {
+        &self.instructions
+    }
+
+    /// Returns whether each instruction was executed, in the order of
+    /// [`Coverage::instructions`].
+    pub fn instruction_bitmap(&self) -> Vec<bool> {
+        self.instructions.iter().map(|insn| insn.hits > 0).collect()
+    }
+
+    /// Returns the address of the first instruction, which is the address of
+    /// bit `0` in [`Coverage::byte_bitmap`].
+    #[inline]
+    pub fn base(&self) -> u64 {
+        self.instructions.first().map_or(0, |insn| insn.ip)
+    }
+
+    /// Checks whether the byte at `addr` belongs to an executed instruction.
+    pub fn is_byte_covered(&self, addr: u64) -> bool {
+        let idx = self.instructions.partition_point(|insn| insn.ip <= addr);
+        idx.checked_sub(1)
+            .map(|idx| &self.instructions[idx])
+            .is_some_and(|insn| insn.hits > 0 && addr < insn.ip + u64::from(insn.length))
+    }
+
+    /// Returns the byte coverage as a bitmap, where bit `i % 8` of byte
+    /// `i / 8` is set if the byte at `base + i` is covered, with `base` being
+    /// [`Coverage::base`].
+    ///
+    /// The bitmap spans from the first to the end of the last instruction.
+    pub fn byte_bitmap(&self) -> Vec<u8> {
+        let base = self.base();
+        let end = self
+            .instructions
+            .iter()
+            .map(|insn| insn.ip + u64::from(insn.length))
+            .max()
+            .unwrap_or(base);
+
+        let mut bitmap = alloc::vec![0u8; (end - base).div_ceil(8) as usize];
+        for insn in self.instructions.iter().filter(|insn| insn.hits > 0) {
+            let start = insn.ip - base;
+            for bit in start..start + u64::from(insn.length) {
+                bitmap[(bit / 8) as usize] |= 1 << (bit % 8);
+            }
+        }
+        bitmap
+    }
+
+    /// Returns the share of executed instructions, or `0.0` if there are no
+    /// instructions.
+    pub fn instruction_ratio(&self) -> f64 {
+        match self.instructions.len() {
+            0 => 0.0,
+            total => {
+                let covered = self.instructions.iter().filter(|insn| insn.hits > 0);
+                covered.count() as f64 / total as f64
+            }
+        }
+    }
+
+    /// Returns the hit counts as annotations, with a `hits: <count>` comment
+    /// and the count as data for every instruction.
+    ///
+    /// Used by `listing::hexdump_coverage` to render a listing with hit
+    /// counts.
+    pub fn annotations(&self) -> AnnotationMap<u64> {
+        let mut annotations = AnnotationMap::new();
+        for insn in &self.instructions {
+            annotations.add_comment(insn.ip, format!("hits: {}", insn.hits));
+            annotations.set_data(insn.ip, insn.hits);
+        }
+        annotations
+    }
+}
@@ -1,23 +1,53 @@
 use crate::*;
 use core::{fmt, marker::PhantomData, mem::MaybeUninit, ops};
 #[cfg(feature = "full-decoder")]
-use core::{hash, mem, ptr};
+use core::{hash, slice};
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
 
 /// Decodes raw instruction bytes into a machine-readable struct.
+#[cfg_attr(
+    feature = "serialization",
+    derive(Deserialize, Serialize),
+    serde(try_from = "ffi::Decoder", into = "ffi::Decoder")
+)]
 #[derive(Clone, Debug)]
-pub struct Decoder(ffi::Decoder);
+pub struct Decoder {
+    raw: ffi::Decoder,
+    #[cfg(feature = "decoder-stats")]
+    counters: DecoderCounters,
+}
 
 impl Decoder {
     /// Creates a new [`Decoder`] with custom machine mode and stack width.
+    ///
+    /// This is a `const fn`, so decoders can be created in `const` and
+    /// `static` items.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// static DECODER: Decoder = match Decoder::new(MachineMode::LEGACY_16, StackWidth::_16) {
+    ///     Ok(decoder) => decoder,
+    ///     Err(_) => panic!("invalid mode"),
+    /// };
+    ///
+    /// assert_eq!(DECODER.machine_mode(), MachineMode::LEGACY_16);
+    /// assert!(Decoder::new(MachineMode::LONG_64, StackWidth::_32).is_err());
+    /// ```
     #[inline]
-    pub fn new(machine_mode: MachineMode, stack_width: StackWidth) -> Result<Self> {
-        unsafe {
-            let mut decoder = MaybeUninit::uninit();
-            let status = ffi::ZydisDecoderInit(decoder.as_mut_ptr(), machine_mode, stack_width);
-            if status.is_error() {
-                return Err(status);
-            }
-            Ok(Self(decoder.assume_init()))
+    pub const fn new(machine_mode: MachineMode, stack_width: StackWidth) -> Result<Self> {
+        match ffi::Decoder::init(machine_mode, stack_width) {
+            Some(raw) => Ok(Self::from_raw(raw)),
+            None => Err(Status::InvalidArgument),
+        }
+    }
+
+    const fn from_raw(raw: ffi::Decoder) -> Self {
+        Self {
+            raw,
+            #[cfg(feature = "decoder-stats")]
+            counters: DecoderCounters::new(),
         }
     }
 
@@ -26,29 +56,95 @@ impl Decoder {
     /// Machine mode is `MachineMode::LONG_COMPAT_32` and stack width is
     /// `StackWidth::_32`.
     #[inline]
-    pub fn new32() -> Self {
-        Self::new(MachineMode::LONG_COMPAT_32, StackWidth::_32)
-            .expect("init with valid mode combination cannot fail")
+    pub const fn new32() -> Self {
+        Self::from_raw(ffi::Decoder::init_unchecked(
+            MachineMode::LONG_COMPAT_32,
+            StackWidth::_32,
+        ))
     }
 
     /// Creating a typical 64 bit decoder.
     ///
     /// Machine mode is `MachineMode::LONG_64` and stack width is
     /// `StackWidth::_64`.
-    pub fn new64() -> Self {
-        Self::new(MachineMode::LONG_64, StackWidth::_64)
-            .expect("init with valid mode combination cannot fail")
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// static DECODER: Decoder = Decoder::new64();
+    ///
+    /// let insn = DECODER.decode_first::<NoOperands>(&[0xCC]).unwrap().unwrap();
+    /// assert_eq!(insn.mnemonic, Mnemonic::INT3);
+    /// ```
+    pub const fn new64() -> Self {
+        Self::from_raw(ffi::Decoder::init_unchecked(
+            MachineMode::LONG_64,
+            StackWidth::_64,
+        ))
+    }
+
+    /// Creating a 16 bit real mode decoder, e.g. for boot sectors.
+    ///
+    /// Machine mode is `MachineMode::REAL_16` and stack width is
+    /// `StackWidth::_16`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // mov sp, 0x7C00
+    /// static CODE: &[u8] = &[0xBC, 0x00, 0x7C];
+    ///
+    /// let insn = Decoder::new_real16()
+    ///     .decode_first::<VisibleOperands>(CODE)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(insn.operands()[0].kind, ffi::DecodedOperandKind::Reg(Register::SP));
+    /// ```
+    #[inline]
+    pub const fn new_real16() -> Self {
+        Self::from_raw(ffi::Decoder::init_unchecked(
+            MachineMode::REAL_16,
+            StackWidth::_16,
+        ))
     }
 
     /// Enables or disables decoder modes.
     #[inline]
     pub fn enable_mode(&mut self, mode: DecoderMode, value: bool) -> Result<&mut Self> {
         unsafe {
-            ffi::ZydisDecoderEnableMode(&mut self.0, mode, value as _).as_result()?;
+            ffi::ZydisDecoderEnableMode(&mut self.raw, mode, value as _).as_result()?;
             Ok(self)
         }
     }
 
+    /// Returns the machine mode this decoder was created with.
+    #[inline]
+    pub fn machine_mode(&self) -> MachineMode {
+        self.raw.machine_mode()
+    }
+
+    /// Returns the stack width this decoder was created with.
+    #[inline]
+    pub fn stack_width(&self) -> StackWidth {
+        self.raw.stack_width()
+    }
+
+    /// Checks whether the given decoder mode is enabled.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let mut decoder = Decoder::new64();
+    /// assert!(!decoder.is_mode_enabled(DecoderMode::KNC));
+    ///
+    /// decoder.enable_mode(DecoderMode::KNC, true).unwrap();
+    /// assert!(decoder.is_mode_enabled(DecoderMode::KNC));
+    /// ```
+    #[inline]
+    pub fn is_mode_enabled(&self, mode: DecoderMode) -> bool {
+        self.raw.is_mode_enabled(mode)
+    }
+
     /// Decodes the first instruction in the given buffer.
     ///
     /// # Examples
@@ -62,35 +158,94 @@ impl Decoder {
     /// ```
     #[inline]
     pub fn decode_first<O: Operands>(&self, buffer: &[u8]) -> Result<Option<Instruction<O>>> {
+        let Some((ctx, info)) = self.decode_raw(buffer)? else {
+            return Ok(None);
+        };
+
+        let operands = O::decode(&self.raw, &ctx, &info)?;
+        Ok(Some(Instruction { info, operands }))
+    }
+
+    /// Decodes the first instruction in the given buffer without operands,
+    /// returning the context required to decode them later.
+    #[inline]
+    pub(crate) fn decode_raw(
+        &self,
+        buffer: &[u8],
+    ) -> Result<Option<(ffi::DecoderContext, ffi::DecodedInstruction)>> {
         let mut uninit_ctx = MaybeUninit::<ffi::DecoderContext>::uninit();
         let mut uninit_insn = MaybeUninit::<ffi::DecodedInstruction>::uninit();
 
         unsafe {
             match ffi::ZydisDecoderDecodeInstruction(
-                &self.0,
+                &self.raw,
                 uninit_ctx.as_mut_ptr(),
                 buffer.as_ptr() as _,
                 buffer.len(),
                 uninit_insn.as_mut_ptr(),
             ) {
                 Status::NoMoreData => return Ok(None),
-                x if x.is_error() => return Err(x),
+                x if x.is_error() => {
+                    #[cfg(feature = "decoder-stats")]
+                    self.counters.record_error(x);
+                    return Err(x);
+                }
                 _ => (),
             }
 
-            let operands = O::decode(
-                &self.0,
-                uninit_ctx.assume_init_ref(),
-                uninit_insn.assume_init_ref(),
-            );
-
-            Ok(Some(Instruction {
-                info: uninit_insn.assume_init(),
-                operands,
-            }))
+            ffi::DecodedInstruction::check_variants(uninit_insn.as_ptr())?;
+            let insn = uninit_insn.assume_init();
+            #[cfg(feature = "decoder-stats")]
+            self.counters.record_instruction(insn.length);
+            Ok(Some((uninit_ctx.assume_init(), insn)))
         }
     }
 
+    /// Returns the underlying FFI decoder.
+    #[inline]
+    pub fn raw(&self) -> &ffi::Decoder {
+        &self.raw
+    }
+
+    /// Returns the number of instructions and bytes decoded and errors
+    /// encountered by this decoder so far.
+    ///
+    /// Every decoding attempt through the decoder is counted, including
+    /// those made by iterators like [`Decoder::decode_all`]. Decoders used
+    /// internally, e.g. by [`Decoder::lengths`], count separately.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // push rcx; lea eax, [rbp-0x01]; ud2; <invalid in 64 bit mode>
+    /// static CODE: &[u8] = &[0x51, 0x8D, 0x45, 0xFF, 0x0F, 0x0B, 0x06];
+    /// let decoder = Decoder::new64();
+    ///
+    /// let decoded = decoder.decode_all::<NoOperands>(CODE, 0).take_while(Result::is_ok);
+    /// assert_eq!(decoded.count(), 3);
+    ///
+    /// let stats = decoder.stats();
+    /// assert_eq!(stats.instructions, 3);
+    /// assert_eq!(stats.bytes, 6);
+    /// assert_eq!(stats.total_errors(), 1);
+    /// assert_eq!(stats.errors(Status::NoMoreData), 0);
+    ///
+    /// decoder.reset_stats();
+    /// assert_eq!(decoder.stats(), DecoderStats::default());
+    /// ```
+    #[cfg(feature = "decoder-stats")]
+    #[inline]
+    pub fn stats(&self) -> DecoderStats {
+        self.counters.snapshot()
+    }
+
+    /// Resets all counters to zero.
+    #[cfg(feature = "decoder-stats")]
+    #[inline]
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+    }
+
     /// Returns an iterator over all the instructions in the buffer.
     ///
     /// If you don't know the instruction pointer or simply want to track the
@@ -108,17 +263,171 @@ impl Decoder {
             _marker: PhantomData,
         }
     }
+
+    /// Returns an iterator over the lengths of all instructions in the buffer.
+    ///
+    /// Decoding happens in [`DecoderMode::MINIMAL`], skipping operands and
+    /// all extended instruction info. This makes it considerably cheaper than
+    /// [`Decoder::decode_all`] for mapping out instruction boundaries.
+    ///
+    /// The iterator stops after yielding the first error.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // push rcx; lea eax, [rbp-0x01]; int3
+    /// static CODE: &[u8] = &[0x51, 0x8D, 0x45, 0xFF, 0xCC];
+    /// let decoder = Decoder::new64();
+    ///
+    /// let lengths: Result<Vec<u8>> = decoder.lengths(CODE).collect();
+    /// assert_eq!(lengths.unwrap(), [1, 3, 1]);
+    /// ```
+    pub fn lengths<'buffer>(&self, buffer: &'buffer [u8]) -> InstructionLengths<'buffer> {
+        InstructionLengths {
+            decoder: MinimalDecoder::from(self.clone()),
+            buffer,
+        }
+    }
+
+    /// Returns an iterator over the addresses of all instructions in the
+    /// buffer matching `predicate`.
+    ///
+    /// The buffer is decoded linearly, starting at `ip`. Bytes that don't
+    /// decode to a valid instruction are skipped one at a time.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // push rcx; call [rip+0x10]; call rax
+    /// static CODE: &[u8] = &[0x51, 0xFF, 0x15, 0x10, 0x00, 0x00, 0x00, 0xFF, 0xD0];
+    /// let decoder = Decoder::new64();
+    ///
+    /// let rip_relative_calls: Vec<u64> = decoder
+    ///     .scan(CODE, 0x1000, |insn| {
+    ///         insn.mnemonic == Mnemonic::CALL
+    ///             && matches!(
+    ///                 &insn.operands()[0].kind,
+    ///                 ffi::DecodedOperandKind::Mem(mem) if mem.base == Register::RIP
+    ///             )
+    ///     })
+    ///     .collect();
+    /// assert_eq!(rip_relative_calls, [0x1001]);
+    /// ```
+    #[cfg(feature = "full-decoder")]
+    pub fn scan<'this, 'buffer, P>(
+        &'this self,
+        buffer: &'buffer [u8],
+        ip: u64,
+        predicate: P,
+    ) -> Scan<'this, 'buffer, P>
+    where
+        P: FnMut(&Instruction<AllOperands>) -> bool,
+    {
+        Scan {
+            decoder: self,
+            buffer,
+            ip,
+            predicate,
+        }
+    }
+}
+
+impl TryFrom<ffi::Decoder> for Decoder {
+    type Error = Status;
+
+    /// Rebuilds a decoder from its raw state, e.g. a deserialized one.
+    ///
+    /// The configuration is re-applied through the C library, so invalid
+    /// states are rejected rather than used as is.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let mut decoder = Decoder::new32();
+    /// decoder.enable_mode(DecoderMode::AMD_BRANCHES, true).unwrap();
+    ///
+    /// let rebuilt = Decoder::try_from(decoder.raw().clone()).unwrap();
+    /// assert_eq!(rebuilt.raw(), decoder.raw());
+    /// ```
+    fn try_from(raw: ffi::Decoder) -> Result<Self> {
+        if raw.has_unknown_modes() {
+            return Err(Status::InvalidArgument);
+        }
+
+        let mut decoder = Self::new(raw.machine_mode(), raw.stack_width())?;
+        for mode in ffi::DECODER_MODES {
+            decoder.enable_mode(mode, raw.is_mode_enabled(mode))?;
+        }
+        Ok(decoder)
+    }
+}
+
+impl From<Decoder> for ffi::Decoder {
+    #[inline]
+    fn from(decoder: Decoder) -> Self {
+        decoder.raw
+    }
+}
+
+/// Iterator over the addresses of instructions matching a predicate.
+///
+/// Created via [`Decoder::scan`].
+#[cfg(feature = "full-decoder")]
+#[derive(Clone)]
+pub struct Scan<'decoder, 'buffer, P> {
+    decoder: &'decoder Decoder,
+    buffer: &'buffer [u8],
+    ip: u64,
+    predicate: P,
+}
+
+#[cfg(feature = "full-decoder")]
+impl<P> Iterator for Scan<'_, '_, P>
+where
+    P: FnMut(&Instruction<AllOperands>) -> bool,
+{
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.buffer.is_empty() {
+            let ip = self.ip;
+            let (length, matched) = match self.decoder.decode_first(self.buffer) {
+                Ok(Some(insn)) => (usize::from(insn.length), (self.predicate)(&insn)),
+                _ => (1, false),
+            };
+
+            self.buffer = &self.buffer[length..];
+            self.ip += length as u64;
+            if matched {
+                return Some(ip);
+            }
+        }
+
+        None
+    }
 }
 
 /// Iterator decoding instructions in a buffer.
 ///
 /// Created via [`Decoder::decode_all`].
-#[derive(Clone)]
 pub struct InstructionIter<'decoder, 'buffer, O: Operands> {
     decoder: &'decoder Decoder,
     buffer: &'buffer [u8],
     ip: u64,
-    _marker: PhantomData<*const O>,
+    _marker: PhantomData<fn() -> O>,
+}
+
+// Not derived, as that would require `O: Clone`.
+impl<O: Operands> Clone for InstructionIter<'_, '_, O> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            decoder: self.decoder,
+            buffer: self.buffer,
+            ip: self.ip,
+            _marker: PhantomData,
+        }
+    }
 }
 
 impl<'decoder, 'buffer, O: Operands> Iterator for InstructionIter<'decoder, 'buffer, O> {
@@ -140,6 +449,282 @@ impl<'decoder, 'buffer, O: Operands> Iterator for InstructionIter<'decoder, 'buf
     }
 }
 
+impl<'decoder, 'buffer, O: Operands> InstructionIter<'decoder, 'buffer, O> {
+    /// Returns the bytes that weren't decoded yet.
+    ///
+    /// Together with [`InstructionIter::current_ip`], this allows pausing
+    /// decoding and resuming it later via [`Decoder::decode_all`], possibly
+    /// with a different decoder or operand type.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // push rcx; ret; lea eax, [rbp-0x01]
+    /// static CODE: &[u8] = &[0x51, 0xC3, 0x8D, 0x45, 0xFF];
+    /// let decoder = Decoder::new64();
+    ///
+    /// let mut iter = decoder.decode_all::<NoOperands>(CODE, 0x1000);
+    /// for item in iter.by_ref() {
+    ///     if item.unwrap().2.mnemonic == Mnemonic::RET {
+    ///         break;
+    ///     }
+    /// }
+    /// assert_eq!(iter.remaining_buffer(), &CODE[2..]);
+    /// assert_eq!(iter.current_ip(), 0x1002);
+    ///
+    /// let mut resumed =
+    ///     decoder.decode_all::<VisibleOperands>(iter.remaining_buffer(), iter.current_ip());
+    /// let (ip, _, insn) = resumed.next().unwrap().unwrap();
+    /// assert_eq!((ip, insn.mnemonic), (0x1002, Mnemonic::LEA));
+    /// ```
+    #[inline]
+    pub fn remaining_buffer(&self) -> &'buffer [u8] {
+        self.buffer
+    }
+
+    /// Returns the address of the next instruction to decode.
+    #[inline]
+    pub fn current_ip(&self) -> u64 {
+        self.ip
+    }
+
+    /// Yields [`DecodedItem`]s with named fields instead of tuples.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// static CODE: &[u8] = &[0x51, 0x8D, 0x45, 0xFF];
+    ///
+    /// for item in Decoder::new64().decode_all::<VisibleOperands>(CODE, 0x1000).items() {
+    ///     let item = item.unwrap();
+    ///     println!("{:X}: {}", item.ip, item.text(&Formatter::intel()).unwrap());
+    /// }
+    /// ```
+    pub fn items(self) -> impl Iterator<Item = Result<DecodedItem<'buffer, O>>> + 'decoder
+    where
+        'buffer: 'decoder,
+        O: 'decoder,
+    {
+        self.map(|item| item.map(DecodedItem::from))
+    }
+}
+
+/// An instruction decoded by [`Decoder::decode_all`], see
+/// [`InstructionIter::items`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DecodedItem<'buffer, O: Operands> {
+    /// Address of the instruction.
+    pub ip: u64,
+    /// The raw instruction bytes.
+    pub bytes: &'buffer [u8],
+    /// The decoded instruction.
+    pub insn: Instruction<O>,
+}
+
+impl<O: Operands> DecodedItem<'_, O> {
+    /// Address of the next instruction.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// static CODE: &[u8] = &[0x8D, 0x45, 0xFF];
+    ///
+    /// let decoder = Decoder::new64();
+    /// let mut items = decoder.decode_all::<NoOperands>(CODE, 0x1000).items();
+    /// assert_eq!(items.next().unwrap().unwrap().end_ip(), 0x1003);
+    /// ```
+    #[inline]
+    pub fn end_ip(&self) -> u64 {
+        self.ip + u64::from(self.insn.length)
+    }
+
+    /// Formats the instruction using absolute addresses.
+    #[cfg(all(feature = "formatter", feature = "alloc"))]
+    pub fn text<UserData>(&self, formatter: &Formatter<UserData>) -> Result<alloc::string::String> {
+        formatter.format(Some(self.ip), &self.insn)
+    }
+}
+
+impl<'buffer, O: Operands> From<(u64, &'buffer [u8], Instruction<O>)> for DecodedItem<'buffer, O> {
+    #[inline]
+    fn from((ip, bytes, insn): (u64, &'buffer [u8], Instruction<O>)) -> Self {
+        Self { ip, bytes, insn }
+    }
+}
+
+/// Decoder restricted to [`DecoderMode::MINIMAL`].
+///
+/// This is the cheapest decoding tier offered by Zydis: no operands and no
+/// semantic information are decoded. Only the mnemonic, the length and a
+/// subset of the attributes (e.g. [`InstructionAttributes::IS_RELATIVE`] and
+/// the prefix attributes) are available.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// static INT3: &[u8] = &[0xCC];
+/// let decoder = MinimalDecoder::new64();
+///
+/// let insn = decoder.decode(INT3).unwrap().unwrap();
+/// assert_eq!(insn.mnemonic, Mnemonic::INT3);
+/// assert_eq!(insn.length, 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MinimalDecoder(Decoder);
+
+impl MinimalDecoder {
+    /// Creates a new [`MinimalDecoder`] with custom machine mode and stack
+    /// width.
+    #[inline]
+    pub fn new(machine_mode: MachineMode, stack_width: StackWidth) -> Result<Self> {
+        Decoder::new(machine_mode, stack_width).map(Self::from)
+    }
+
+    /// Creating a typical 32 bit minimal decoder.
+    ///
+    /// See [`Decoder::new32`].
+    #[inline]
+    pub const fn new32() -> Self {
+        Self::from_decoder(Decoder::new32())
+    }
+
+    /// Creating a typical 64 bit minimal decoder.
+    ///
+    /// See [`Decoder::new64`].
+    #[inline]
+    pub const fn new64() -> Self {
+        Self::from_decoder(Decoder::new64())
+    }
+
+    /// Creating a 16 bit real mode minimal decoder.
+    ///
+    /// See [`Decoder::new_real16`].
+    #[inline]
+    pub const fn new_real16() -> Self {
+        Self::from_decoder(Decoder::new_real16())
+    }
+
+    /// Enables minimal mode on `decoder`, usable in `const` contexts.
+    #[inline]
+    const fn from_decoder(decoder: Decoder) -> Self {
+        let mut decoder = decoder;
+        decoder.raw = decoder.raw.with_mode(DecoderMode::MINIMAL, true);
+        Self(decoder)
+    }
+
+    /// Decodes the first instruction in the given buffer.
+    #[inline]
+    pub fn decode(&self, buffer: &[u8]) -> Result<Option<MinimalInstruction>> {
+        Ok(self
+            .0
+            .decode_first::<NoOperands>(buffer)?
+            .map(|insn| MinimalInstruction {
+                mnemonic: insn.mnemonic,
+                length: insn.length,
+                attributes: insn.attributes,
+            }))
+    }
+
+    /// Returns an iterator over all the instructions in the buffer.
+    ///
+    /// Unlike [`InstructionIter`], the iterator stops after yielding the first
+    /// error.
+    #[inline]
+    pub fn decode_all<'this, 'buffer>(
+        &'this self,
+        buffer: &'buffer [u8],
+        ip: u64,
+    ) -> MinimalInstructionIter<'this, 'buffer> {
+        MinimalInstructionIter {
+            decoder: self,
+            buffer,
+            ip,
+        }
+    }
+}
+
+/// Converts a [`Decoder`] into a [`MinimalDecoder`], enabling
+/// [`DecoderMode::MINIMAL`].
+impl From<Decoder> for MinimalDecoder {
+    fn from(decoder: Decoder) -> Self {
+        Self::from_decoder(decoder)
+    }
+}
+
+/// Slimmed down instruction info produced by the [`MinimalDecoder`].
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MinimalInstruction {
+    /// The instruction-mnemonic.
+    pub mnemonic: Mnemonic,
+    /// The length of the decoded instruction.
+    pub length: u8,
+    /// Instruction attributes.
+    ///
+    /// Only attributes determined without semantic analysis are populated.
+    pub attributes: InstructionAttributes,
+}
+
+/// Iterator decoding minimal instructions in a buffer.
+///
+/// Created via [`MinimalDecoder::decode_all`].
+#[derive(Clone)]
+pub struct MinimalInstructionIter<'decoder, 'buffer> {
+    decoder: &'decoder MinimalDecoder,
+    buffer: &'buffer [u8],
+    ip: u64,
+}
+
+impl<'decoder, 'buffer> Iterator for MinimalInstructionIter<'decoder, 'buffer> {
+    type Item = Result<(u64, &'buffer [u8], MinimalInstruction)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.decoder.decode(self.buffer) {
+            Ok(Some(insn)) => {
+                let ip = self.ip;
+                let (insn_bytes, new_buffer) = self.buffer.split_at(usize::from(insn.length));
+                self.buffer = new_buffer;
+                self.ip += u64::from(insn.length);
+                Some(Ok((ip, insn_bytes, insn)))
+            }
+            Ok(None) => None,
+            Err(e) => {
+                self.buffer = &[];
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Iterator yielding the lengths of the instructions in a buffer.
+///
+/// Created via [`Decoder::lengths`].
+#[derive(Clone)]
+pub struct InstructionLengths<'buffer> {
+    decoder: MinimalDecoder,
+    buffer: &'buffer [u8],
+}
+
+impl<'buffer> Iterator for InstructionLengths<'buffer> {
+    type Item = Result<u8>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.decoder.decode(self.buffer) {
+            Ok(Some(insn)) => {
+                self.buffer = &self.buffer[usize::from(insn.length)..];
+                Some(Ok(insn.length))
+            }
+            Ok(None) => None,
+            Err(e) => {
+                self.buffer = &[];
+                Some(Err(e))
+            }
+        }
+    }
+}
+
 /// Convenience alias for an instruction with full operand information.
 #[cfg(feature = "full-decoder")]
 pub type FullInstruction = Instruction<AllOperands>;
@@ -194,11 +779,11 @@ impl<O: Operands> ops::Deref for Instruction<O> {
 impl<const N: usize> fmt::Display for Instruction<OperandArrayVec<N>> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let fmt = Formatter::intel();
-        let mut buffer = [0u8; 256];
-        let mut buffer = OutputBuffer::new(&mut buffer);
-        fmt.format_ex(None, self, &mut buffer, None)
-            .map_err(|_| fmt::Error)?;
-        f.write_str(buffer.as_str().map_err(|_| fmt::Error)?)
+        crate::formatter::with_scratch_buffer(|buffer| {
+            fmt.format_ex(None, self, buffer, None)
+                .map_err(|_| fmt::Error)?;
+            f.write_str(buffer.as_str().map_err(|_| fmt::Error)?)
+        })
     }
 }
 
@@ -221,6 +806,66 @@ impl<const N: usize> Instruction<OperandArrayVec<N>> {
 }
 
 impl<O: Operands> Instruction<O> {
+    /// Decodes this instruction again from `bytes`, storing operands in `P`.
+    ///
+    /// `bytes` must start with this instruction and `decoder` must be
+    /// configured like the one that decoded it, otherwise
+    /// [`Status::InvalidArgument`] is returned.
+    #[inline]
+    pub fn redecode_operands<P: Operands>(
+        &self,
+        decoder: &Decoder,
+        bytes: &[u8],
+    ) -> Result<Instruction<P>> {
+        let insn = decoder
+            .decode_first::<P>(bytes)?
+            .ok_or(Status::InvalidArgument)?;
+        if insn.info != self.info {
+            return Err(Status::InvalidArgument);
+        }
+        Ok(insn)
+    }
+
+    /// Decodes this instruction again from `bytes` with all operands,
+    /// including hidden ones.
+    ///
+    /// This allows decoding only visible operands in the common case, while
+    /// occasionally inspecting implicit ones. See
+    /// [`Instruction::redecode_operands`] for the requirements.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// static PUSH: &[u8] = &[0x51]; // push rcx
+    /// let decoder = Decoder::new64();
+    ///
+    /// let insn = decoder.decode_first::<VisibleOperands>(PUSH).unwrap().unwrap();
+    /// assert_eq!(insn.operands().len(), 1);
+    ///
+    /// let full = insn.with_all_operands(&decoder, PUSH).unwrap();
+    /// assert_eq!(full.operands().len(), usize::from(full.operand_count));
+    /// assert!(full.operands()[1..].iter().all(|op| op.visibility != OperandVisibility::EXPLICIT));
+    ///
+    /// assert_eq!(
+    ///     insn.with_all_operands(&decoder, &[0x52]).unwrap_err(),
+    ///     Status::InvalidArgument
+    /// );
+    /// ```
+    #[cfg(feature = "full-decoder")]
+    #[inline]
+    pub fn with_all_operands(&self, decoder: &Decoder, bytes: &[u8]) -> Result<FullInstruction> {
+        self.redecode_operands(decoder, bytes)
+    }
+}
+
+impl<O: Operands> Instruction<O> {
+    /// Assembles an instruction from separately decoded parts.
+    #[cfg(feature = "bumpalo")]
+    #[inline]
+    pub(crate) fn from_parts(info: ffi::DecodedInstruction, operands: O) -> Self {
+        Self { info, operands }
+    }
+
     /// Returns offsets and sizes of all logical instruction segments.
     #[inline]
     pub fn segments(&self) -> Result<ffi::InstructionSegments> {
@@ -241,21 +886,241 @@ impl<O: Operands> Instruction<O> {
 
     /// Retrieve the visible operands.
     ///
-    /// If `O` is [`NoOperands`], this always returns an empty slice.
+    /// If `O` is [`NoOperands`], this always returns an empty slice. If `O`
+    /// truncated the operands, only the stored ones are returned.
     #[inline]
     pub fn visible_operands(&self) -> &[ffi::DecodedOperand] {
-        let visible = self.info.operand_count_visible as usize;
-        &self.operands.operands()[..visible]
+        let operands = self.operands.operands();
+        let visible = (self.info.operand_count_visible as usize).min(operands.len());
+        &operands[..visible]
+    }
+
+    /// Extracts the commonly used scalar fields into a [`CompactInstruction`].
+    #[inline]
+    pub fn to_compact(&self) -> CompactInstruction {
+        CompactInstruction::from(&self.info)
+    }
+
+    /// Returns the raw immediate the operand at `operand_index` was decoded
+    /// from.
+    ///
+    /// Immediate operands use the `raw.imm` slots in order of appearance,
+    /// while `IS4` register operands are encoded in the upper bits of the
+    /// first slot. Returns `None` for out of bounds operands, operands that
+    /// aren't encoded in an immediate (including implicit constants like
+    /// the `1` in `shl eax, 1`) and operands not stored by `O`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // enter 0x10, 0x02
+    /// static CODE: &[u8] = &[0xC8, 0x10, 0x00, 0x02];
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(CODE)
+    ///     .unwrap()
+    ///     .unwrap();
+    ///
+    /// let frame = insn.imm_info_for_operand(0).unwrap();
+    /// assert_eq!((frame.value, frame.size, frame.offset), (0x10, 16, 1));
+    /// let level = insn.imm_info_for_operand(1).unwrap();
+    /// assert_eq!((level.value, level.size, level.offset), (0x02, 8, 3));
+    ///
+    /// let segments = insn.segments().unwrap();
+    /// let imm_values: Vec<_> = segments
+    ///     .into_iter()
+    ///     .filter_map(|s| insn.imm_info_for_segment(s))
+    ///     .map(|imm| imm.value)
+    ///     .collect();
+    /// assert_eq!(imm_values, [0x10, 0x02]);
+    /// ```
+    pub fn imm_info_for_operand(&self, operand_index: usize) -> Option<&ffi::RawImmediateInfo> {
+        let is_encoded_imm = |op: &ffi::DecodedOperand| {
+            matches!(op.kind, ffi::DecodedOperandKind::Imm(_))
+                && op.encoding != OperandEncoding::NONE
+        };
+
+        let operands = self.operands();
+        let operand = operands.get(operand_index)?;
+        let slot = match operand.kind {
+            ffi::DecodedOperandKind::Reg(_) if operand.encoding == OperandEncoding::IS4 => 0,
+            _ if is_encoded_imm(operand) => operands[..operand_index]
+                .iter()
+                .filter(|op| is_encoded_imm(op))
+                .count(),
+            _ => return None,
+        };
+
+        self.raw.imm.get(slot).filter(|imm| imm.size != 0)
+    }
+
+    /// Returns the raw immediate corresponding to an
+    /// [`InstructionSegment::IMMEDIATE`] segment of this instruction.
+    ///
+    /// Returns `None` for segments of any other type. See
+    /// [`Instruction::imm_info_for_operand`] for an example.
+    pub fn imm_info_for_segment(
+        &self,
+        segment: &ffi::InstructionSegmentsElement,
+    ) -> Option<&ffi::RawImmediateInfo> {
+        if segment.ty != InstructionSegment::IMMEDIATE {
+            return None;
+        }
+
+        self.raw
+            .imm
+            .iter()
+            .find(|imm| imm.size != 0 && imm.offset == segment.offset)
+    }
+
+    /// Calculates the absolute address for the operand at `operand_index`,
+    /// using `ip` as the address of this instruction.
+    ///
+    /// Returns [`Status::OutOfRange`] if the operand isn't stored by `O`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // jmp 0x1234 (at 0x1000)
+    /// static CODE: &[u8] = &[0xE9, 0x2F, 0x02, 0x00, 0x00];
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(CODE)
+    ///     .unwrap()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(insn.absolute_address(0x1000, 0), Ok(0x1234));
+    /// assert_eq!(insn.absolute_address(0x1000, 5), Err(Status::OutOfRange));
+    /// ```
+    pub fn absolute_address(&self, ip: u64, operand_index: usize) -> Result<u64> {
+        let operand = self
+            .operands()
+            .get(operand_index)
+            .ok_or(Status::OutOfRange)?;
+        self.info.calc_absolute_address(ip, operand)
+    }
+
+    /// Returns the indices and absolute addresses of all visible operands
+    /// referring to an address, using `ip` as the address of this
+    /// instruction.
+    ///
+    /// These are relative immediates and memory operands without runtime
+    /// dependent registers, e.g. `[rip+0x10]` or `[0x1000]`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // lea rax, [rip+0x10] (at 0x1000)
+    /// static CODE: &[u8] = &[0x48, 0x8D, 0x05, 0x10, 0x00, 0x00, 0x00];
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(CODE)
+    ///     .unwrap()
+    ///     .unwrap();
+    ///
+    /// let addrs: Vec<_> = insn.absolute_addresses(0x1000).collect();
+    /// assert_eq!(addrs, [(1, 0x1017)]);
+    /// ```
+    pub fn absolute_addresses(&self, ip: u64) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.visible_operands()
+            .iter()
+            .enumerate()
+            .filter(|(_, op)| match &op.kind {
+                ffi::DecodedOperandKind::Imm(imm) => imm.is_relative,
+                ffi::DecodedOperandKind::Mem(_) => true,
+                _ => false,
+            })
+            .filter_map(move |(idx, op)| Some((idx, self.info.calc_absolute_address(ip, op).ok()?)))
+    }
+}
+
+/// Compact copy of the scalar fields of an instruction.
+///
+/// Even [`Instruction<NoOperands>`] carries the complete
+/// [`ffi::DecodedInstruction`], including the AVX and raw encoding details.
+/// Pipelines that only care about mnemonics, lengths and classification can
+/// store this type instead, at a fraction of the size.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// // push rcx; lea eax, [rbp-0x01]; int3
+/// static CODE: &[u8] = &[0x51, 0x8D, 0x45, 0xFF, 0xCC];
+/// let decoder = Decoder::new64();
+///
+/// let insns = decoder
+///     .decode_all::<NoOperands>(CODE, 0)
+///     .map(|r| r.map(|(_, _, insn)| insn.to_compact()))
+///     .collect::<Result<Vec<_>>>()
+///     .unwrap();
+///
+/// assert_eq!(insns[1].mnemonic, Mnemonic::LEA);
+/// assert_eq!(insns[1].length, 3);
+/// let (compact, full) = (
+///     core::mem::size_of::<CompactInstruction>(),
+///     core::mem::size_of::<Instruction<NoOperands>>(),
+/// );
+/// assert!(compact * 6 < full);
+/// ```
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompactInstruction {
+    /// The instruction-mnemonic.
+    pub mnemonic: Mnemonic,
+    /// The length of the decoded instruction.
+    pub length: u8,
+    /// The instruction-encoding.
+    pub encoding: InstructionEncoding,
+    /// The opcode map.
+    pub opcode_map: OpcodeMap,
+    /// The instruction opcode.
+    pub opcode: u8,
+    /// The effective operand width.
+    pub operand_width: u8,
+    /// The effective address width.
+    pub address_width: u8,
+    /// The number of instruction operands.
+    pub operand_count: u8,
+    /// The number of explicit (visible) instruction operands.
+    pub operand_count_visible: u8,
+    /// Instruction attributes.
+    pub attributes: InstructionAttributes,
+    /// The category this instruction belongs to.
+    pub category: InstructionCategory,
+    /// The instruction set this instruction belongs to.
+    pub isa_set: ISASet,
+    /// The instruction set extension this instruction belongs to.
+    pub isa_ext: ISAExt,
+    /// The branch type.
+    pub branch_type: BranchType,
+}
+
+impl From<&ffi::DecodedInstruction> for CompactInstruction {
+    fn from(info: &ffi::DecodedInstruction) -> Self {
+        Self {
+            mnemonic: info.mnemonic,
+            length: info.length,
+            encoding: info.encoding,
+            opcode_map: info.opcode_map,
+            opcode: info.opcode,
+            operand_width: info.operand_width,
+            address_width: info.address_width,
+            operand_count: info.operand_count,
+            operand_count_visible: info.operand_count_visible,
+            attributes: info.attributes,
+            category: info.meta.category,
+            isa_set: info.meta.isa_set,
+            isa_ext: info.meta.isa_ext,
+            branch_type: info.meta.branch_type,
+        }
     }
 }
 
 /// Defines storage and decoding behavior for operands.
-pub trait Operands {
+pub trait Operands: Sized {
+    /// Decodes the operands of `insn`, failing if Zydis does.
     fn decode(
         decoder: &ffi::Decoder,
         ctx: &ffi::DecoderContext,
         insn: &ffi::DecodedInstruction,
-    ) -> Self;
+    ) -> Result<Self>;
 
     fn operands(&self) -> &[ffi::DecodedOperand];
 }
@@ -265,8 +1130,12 @@ pub trait Operands {
 pub struct NoOperands;
 
 impl Operands for NoOperands {
-    fn decode(_: &ffi::Decoder, _: &ffi::DecoderContext, _: &ffi::DecodedInstruction) -> Self {
-        Self
+    fn decode(
+        _: &ffi::Decoder,
+        _: &ffi::DecoderContext,
+        _: &ffi::DecodedInstruction,
+    ) -> Result<Self> {
+        Ok(Self)
     }
 
     fn operands(&self) -> &[ffi::DecodedOperand] {
@@ -283,47 +1152,149 @@ pub type VisibleOperands = OperandArrayVec<MAX_OPERAND_COUNT_VISIBLE>;
 pub type AllOperands = OperandArrayVec<MAX_OPERAND_COUNT>;
 
 /// Decode and store operands in a static array buffer.
+///
+/// `MAX_OPERANDS` may be any value up to [`MAX_OPERAND_COUNT`]; larger values
+/// are rejected at compile time. When decoding, capacities up to
+/// [`MAX_OPERAND_COUNT_VISIBLE`] store the first `MAX_OPERANDS` visible
+/// operands, while larger capacities store visible operands followed by hidden
+/// ones. Operands exceeding the capacity are silently dropped.
+///
+/// Note that instructions with truncated visible operands can't be formatted.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// // imul eax, ecx, 0x05
+/// static CODE: &[u8] = &[0x6B, 0xC1, 0x05];
+/// let decoder = Decoder::new64();
+///
+/// let insn = decoder
+///     .decode_first::<OperandArrayVec<2>>(CODE)
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(insn.operand_count_visible, 3);
+/// assert_eq!(insn.visible_operands().len(), 2);
+/// ```
 #[cfg(feature = "full-decoder")]
 pub struct OperandArrayVec<const MAX_OPERANDS: usize> {
     operands: [MaybeUninit<ffi::DecodedOperand>; MAX_OPERANDS],
     num_initialized: usize,
 }
 
+#[cfg(feature = "full-decoder")]
+impl<const MAX_OPERANDS: usize> OperandArrayVec<MAX_OPERANDS> {
+    const UNINIT: MaybeUninit<ffi::DecodedOperand> = MaybeUninit::uninit();
+    const VALID_CAPACITY: () = assert!(
+        MAX_OPERANDS <= MAX_OPERAND_COUNT,
+        "OperandArrayVec capacity exceeds MAX_OPERAND_COUNT"
+    );
+
+    /// Creates an empty operand array.
+    #[inline]
+    pub fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_CAPACITY;
+
+        Self {
+            operands: [Self::UNINIT; MAX_OPERANDS],
+            num_initialized: 0,
+        }
+    }
+
+    /// Creates an operand array holding the first `MAX_OPERANDS` operands of
+    /// `operands`, dropping the rest.
+    #[inline]
+    pub fn from_slice_truncated(operands: &[ffi::DecodedOperand]) -> Self {
+        let mut ops = Self::new();
+        for (slot, op) in ops.operands.iter_mut().zip(operands) {
+            slot.write(op.clone());
+            ops.num_initialized += 1;
+        }
+        ops
+    }
+
+    /// Creates an operand array holding all of `operands`.
+    ///
+    /// Fails with [`Status::InsufficientBufferSize`] if there are more than
+    /// `MAX_OPERANDS` operands.
+    #[inline]
+    pub fn try_from_slice(operands: &[ffi::DecodedOperand]) -> Result<Self> {
+        if operands.len() > MAX_OPERANDS {
+            return Err(Status::InsufficientBufferSize);
+        }
+        Ok(Self::from_slice_truncated(operands))
+    }
+
+    /// The maximum number of operands that can be stored.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        MAX_OPERANDS
+    }
+}
+
+#[cfg(feature = "full-decoder")]
+impl<const N: usize> Instruction<OperandArrayVec<N>> {
+    /// Get mutable access to the stored operands.
+    ///
+    /// This allows making small changes to a decoded instruction (e.g.
+    /// swapping a register or adjusting a displacement) before re-encoding
+    /// it. The basic instruction info is left untouched and may thus become
+    /// inconsistent with the operands.
+    #[inline]
+    pub fn operands_mut(&mut self) -> &mut [ffi::DecodedOperand] {
+        let ops = &mut self.operands;
+        // SAFETY: the first `num_initialized` operands are initialized.
+        unsafe { slice::from_raw_parts_mut(ops.operands.as_mut_ptr().cast(), ops.num_initialized) }
+    }
+}
+
+#[cfg(feature = "full-decoder")]
+impl<const MAX_OPERANDS: usize> Default for OperandArrayVec<MAX_OPERANDS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(feature = "full-decoder")]
 impl<const MAX_OPERANDS: usize> Operands for OperandArrayVec<MAX_OPERANDS> {
     fn decode(
         decoder: &ffi::Decoder,
         ctx: &ffi::DecoderContext,
         insn: &ffi::DecodedInstruction,
-    ) -> Self {
-        let num_operands = match MAX_OPERANDS {
-            MAX_OPERAND_COUNT => usize::from(insn.operand_count),
-            MAX_OPERAND_COUNT_VISIBLE => usize::from(insn.operand_count_visible),
-            _ => unreachable!(),
+    ) -> Result<Self> {
+        let available = if MAX_OPERANDS > MAX_OPERAND_COUNT_VISIBLE {
+            insn.operand_count
+        } else {
+            insn.operand_count_visible
         };
+        let num_operands = usize::from(available).min(MAX_OPERANDS);
 
-        unsafe {
-            let mut ops = OperandArrayVec {
-                num_initialized: num_operands,
-                operands: MaybeUninit::uninit().assume_init(),
-            };
+        let mut ops = Self::new();
 
+        // SAFETY: `operands` has room for `MAX_OPERANDS >= num_operands`
+        // operands, and Zydis initializes exactly `num_operands` of them
+        // on success.
+        unsafe {
             ffi::ZydisDecoderDecodeOperands(
                 decoder,
                 ctx,
                 insn,
-                ops.operands.as_mut_ptr() as _,
-                MAX_OPERANDS as u8,
+                ops.operands.as_mut_ptr().cast(),
+                num_operands as u8,
             )
-            .as_result()
-            .expect("operand decoding should be infallible for valid arguments");
-
-            ops
+            .as_result()?;
+            for operand in &ops.operands[..num_operands] {
+                ffi::DecodedOperand::check_variants(operand.as_ptr())?;
+            }
         }
+        ops.num_initialized = num_operands;
+
+        Ok(ops)
     }
 
     fn operands(&self) -> &[ffi::DecodedOperand] {
-        unsafe { mem::transmute(&self.operands[..self.num_initialized]) }
+        // SAFETY: the first `num_initialized` operands are initialized.
+        unsafe { slice::from_raw_parts(self.operands.as_ptr().cast(), self.num_initialized) }
     }
 }
 
@@ -347,21 +1318,7 @@ impl<const MAX_OPERANDS: usize> hash::Hash for OperandArrayVec<MAX_OPERANDS> {
 #[cfg(feature = "full-decoder")]
 impl<const MAX_OPERANDS: usize> Clone for OperandArrayVec<MAX_OPERANDS> {
     fn clone(&self) -> Self {
-        unsafe {
-            let mut operands: [MaybeUninit<ffi::DecodedOperand>; MAX_OPERANDS] =
-                MaybeUninit::uninit().assume_init();
-
-            ptr::copy_nonoverlapping(
-                self.operands.as_ptr(),
-                operands.as_mut_ptr(),
-                self.num_initialized,
-            );
-
-            Self {
-                num_initialized: self.num_initialized,
-                operands,
-            }
-        }
+        Self::from_slice_truncated(self.operands())
     }
 }
 
@@ -373,3 +1330,34 @@ impl<const MAX_OPERANDS: usize> fmt::Debug for OperandArrayVec<MAX_OPERANDS> {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn const_init_matches_ffi() {
+        let modes = [
+            MachineMode::LONG_64,
+            MachineMode::LONG_COMPAT_32,
+            MachineMode::LONG_COMPAT_16,
+            MachineMode::LEGACY_32,
+            MachineMode::LEGACY_16,
+            MachineMode::REAL_16,
+        ];
+        let widths = [StackWidth::_16, StackWidth::_32, StackWidth::_64];
+
+        for machine_mode in modes {
+            for stack_width in widths {
+                let ffi = unsafe {
+                    let mut decoder = MaybeUninit::uninit();
+                    ffi::ZydisDecoderInit(decoder.as_mut_ptr(), machine_mode, stack_width)
+                        .as_result()
+                        .map(|_| decoder.assume_init())
+                };
+                let ours = Decoder::new(machine_mode, stack_width).map(|d| d.raw);
+                assert_eq!(ours, ffi, "{machine_mode:?}, {stack_width:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Counters of the work done by a [`Decoder`].
+
+use crate::*;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Errors counted separately by [`DecoderStats`]. Other errors are counted
+/// as [`Status::Failed`].
+const COUNTED_ERRORS: [Status; 13] = [
+    Status::Failed,
+    Status::InvalidArgument,
+    Status::DecodingError,
+    Status::InstructionTooLong,
+    Status::BadRegister,
+    Status::IllegalLock,
+    Status::IllegalLegacyPfx,
+    Status::IllegalRex,
+    Status::InvalidMap,
+    Status::MalformedEvex,
+    Status::MalformedMvex,
+    Status::InvalidMask,
+    Status::ImpossibleInstruction,
+];
+
+fn error_index(status: Status) -> usize {
+    COUNTED_ERRORS
+        .iter()
+        .position(|&counted| counted == status)
+        .unwrap_or(0)
+}
+
+/// Snapshot of the counters of a [`Decoder`], see [`Decoder::stats`].
+///
+/// Reaching the end of the buffer isn't counted as an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DecoderStats {
+    /// Number of successfully decoded instructions.
+    pub instructions: u64,
+    /// Sum of the lengths of all successfully decoded instructions.
+    pub bytes: u64,
+    errors: [u64; COUNTED_ERRORS.len()],
+}
+
+impl DecoderStats {
+    /// Returns the number of decoding attempts that failed with `status`.
+    ///
+    /// Errors that aren't decoding errors are all counted as
+    /// [`Status::Failed`].
+    #[inline]
+    pub fn errors(&self, status: Status) -> u64 {
+        match status {
+            status if COUNTED_ERRORS.contains(&status) => self.errors[error_index(status)],
+            _ => 0,
+        }
+    }
+
+    /// Returns the number of failed decoding attempts.
+    #[inline]
+    pub fn total_errors(&self) -> u64 {
+        self.errors.iter().sum()
+    }
+
+    /// Returns an iterator over all errors that occurred, along with their
+    /// counts.
+    pub fn error_counts(&self) -> impl Iterator<Item = (Status, u64)> + '_ {
+        COUNTED_ERRORS
+            .iter()
+            .zip(self.errors)
+            .filter(|&(_, count)| count > 0)
+            .map(|(&status, count)| (status, count))
+    }
+
+    /// Returns the share of failed decoding attempts, or `0.0` if nothing was
+    /// decoded yet.
+    pub fn error_rate(&self) -> f64 {
+        let errors = self.total_errors();
+        match self.instructions + errors {
+            0 => 0.0,
+            total => errors as f64 / total as f64,
+        }
+    }
+}
+
+/// The live counters embedded into a [`Decoder`].
+///
+/// Relaxed atomics, so shared decoders (e.g. in a `static`) can count as
+/// well. Pointer-sized, as not all targets have 64-bit atomics, so counts
+/// wrap around after 4 GiB on 32-bit targets. Clones start with the counts
+/// of the original.
+#[derive(Debug, Default)]
+pub(crate) struct DecoderCounters {
+    instructions: AtomicUsize,
+    bytes: AtomicUsize,
+    errors: [AtomicUsize; COUNTED_ERRORS.len()],
+}
+
+impl DecoderCounters {
+    pub(crate) const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const ZERO: AtomicUsize = AtomicUsize::new(0);
+        Self {
+            instructions: ZERO,
+            bytes: ZERO,
+            errors: [ZERO; COUNTED_ERRORS.len()],
+        }
+    }
+
+    #[inline]
+    pub(crate) fn record_instruction(&self, length: u8) {
+        self.instructions.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(length.into(), Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn record_error(&self, status: Status) {
+        self.errors[error_index(status)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> DecoderStats {
+        DecoderStats {
+            instructions: self.instructions.load(Ordering::Relaxed) as u64,
+            bytes: self.bytes.load(Ordering::Relaxed) as u64,
+            errors: core::array::from_fn(|idx| self.errors[idx].load(Ordering::Relaxed) as u64),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.instructions.store(0, Ordering::Relaxed);
+        self.bytes.store(0, Ordering::Relaxed);
+        for count in &self.errors {
+            count.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Clone for DecoderCounters {
+    fn clone(&self) -> Self {
+        let load = |count: &AtomicUsize| AtomicUsize::new(count.load(Ordering::Relaxed));
+        Self {
+            instructions: load(&self.instructions),
+            bytes: load(&self.bytes),
+            errors: core::array::from_fn(|idx| load(&self.errors[idx])),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Association of AVX decorators with the operands they apply to.
+
+use crate::*;
+
+/// AVX decorators applying to a single operand.
+///
+/// Created via [`Instruction::operand_decorators`]. The association follows
+/// the one used by the Zydis formatters, e.g. `{k1}{z}` belongs to the
+/// destination operand and `{1to16}` to the memory operand.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct OperandDecorators {
+    /// Embedded mask register and masking mode (`{k1}`, `{k1}{z}`).
+    pub mask: Option<(Register, MaskMode)>,
+    /// Broadcast mode (`{1to16}`), including static broadcasts.
+    pub broadcast: Option<BroadcastMode>,
+    /// Rounding mode (`{rn-sae}`).
+    pub rounding: Option<RoundingMode>,
+    /// Suppress-all-exceptions without rounding control (`{sae}`).
+    pub sae: bool,
+    /// Register swizzle (`KNC` only).
+    pub swizzle: Option<SwizzleMode>,
+    /// Data conversion (`KNC` only).
+    pub conversion: Option<ConversionMode>,
+    /// Memory eviction hint (`KNC` only).
+    pub eviction_hint: bool,
+}
+
+impl OperandDecorators {
+    /// Whether no decorator applies to the operand.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl ffi::DecodedOperand {
+    /// Whether this is an embedded mask register operand.
+    ///
+    /// Such operands are printed as a `{k}` decorator of the destination
+    /// operand rather than as a regular operand.
+    #[inline]
+    pub fn is_embedded_mask(&self) -> bool {
+        self.encoding == OperandEncoding::MASK
+            && matches!(self.kind, ffi::DecodedOperandKind::Reg(_))
+    }
+}
+
+impl<O: Operands> Instruction<O> {
+    /// Returns the AVX decorators applying to the visible operand at
+    /// `operand_index`.
+    ///
+    /// Returns no decorators for hidden or out of bounds operands and for
+    /// instructions that aren't EVEX or MVEX encoded.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // vaddps zmm1{k1}{z}, zmm2, dword ptr [rax]{1to16}
+    /// static CODE: &[u8] = &[0x62, 0xF1, 0x6C, 0xD9, 0x58, 0x08];
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(CODE)
+    ///     .unwrap()
+    ///     .unwrap();
+    ///
+    /// let dst = insn.operand_decorators(0);
+    /// assert_eq!(dst.mask, Some((Register::K1, MaskMode::ZEROING)));
+    /// assert_eq!(dst.broadcast, None);
+    ///
+    /// assert!(insn.operands()[1].is_embedded_mask());
+    ///
+    /// let src = insn.operand_decorators(3);
+    /// assert_eq!(src.broadcast, Some(BroadcastMode::_1_TO_16));
+    /// assert_eq!(src.mask, None);
+    /// ```
+    pub fn operand_decorators(&self, operand_index: usize) -> OperandDecorators {
+        let mut decorators = OperandDecorators::default();
+
+        let operands = self.visible_operands();
+        let avx = &self.avx;
+        let is_mvex = self.encoding == InstructionEncoding::MVEX;
+        if !is_mvex && self.encoding != InstructionEncoding::EVEX {
+            return decorators;
+        }
+        let Some(operand) = operands.get(operand_index) else {
+            return decorators;
+        };
+        if operand.is_embedded_mask() && operand_index == 1 {
+            return decorators;
+        }
+
+        if operand_index == 0
+            && operands.get(1).is_some_and(|x| x.is_embedded_mask())
+            && !matches!(avx.mask_mode, MaskMode::INVALID | MaskMode::DISABLED)
+        {
+            decorators.mask = Some((avx.mask_reg, avx.mask_mode));
+        }
+
+        if let ffi::DecodedOperandKind::Mem(_) = operand.kind {
+            if avx.broadcast_mode != BroadcastMode::INVALID {
+                decorators.broadcast = Some(avx.broadcast_mode);
+            }
+            if is_mvex {
+                if avx.conversion_mode != ConversionMode::INVALID {
+                    decorators.conversion = Some(avx.conversion_mode);
+                }
+                decorators.eviction_hint = avx.has_eviction_hint;
+            }
+            return decorators;
+        }
+
+        // Rounding, SAE and swizzle decorate the last non-immediate operand.
+        let is_imm = |op: &ffi::DecodedOperand| matches!(op.kind, ffi::DecodedOperandKind::Imm(_));
+        let decorate = match operands.get(operand_index + 1) {
+            None => !is_imm(operand),
+            Some(next) => is_imm(next),
+        };
+
+        if decorate {
+            if is_mvex && avx.swizzle_mode != SwizzleMode::INVALID {
+                decorators.swizzle = Some(avx.swizzle_mode);
+            }
+            if avx.rounding_mode != RoundingMode::INVALID {
+                decorators.rounding = Some(avx.rounding_mode);
+            } else {
+                decorators.sae = avx.has_sae;
+            }
+        }
+
+        decorators
+    }
+}
@@ -0,0 +1,284 @@
+//! Instruction-level diffing of two code regions.
+
+use crate::{formatter::with_scratch_buffer, *};
+use std::io;
+
+/// A decoded instruction taking part in a diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    /// Address of the instruction.
+    pub ip: u64,
+    /// Raw instruction bytes.
+    pub bytes: Vec<u8>,
+    /// Instruction text, formatted with absolute addresses.
+    ///
+    /// Bytes that don't decode are represented as `(bad)`.
+    pub text: String,
+    /// Instruction text with relative addresses, used for alignment so that
+    /// moved code still compares equal.
+    key: String,
+}
+
+/// A single entry of the structured change list produced by [`diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// The instruction is present in both regions.
+    Equal {
+        /// Instruction in the old region.
+        old: DiffLine,
+        /// Instruction in the new region.
+        new: DiffLine,
+    },
+    /// The instruction was only present in the old region.
+    Removed(DiffLine),
+    /// The instruction is only present in the new region.
+    Added(DiffLine),
+}
+
+impl Change {
+    /// The instruction in the old region, if any.
+    pub fn old_line(&self) -> Option<&DiffLine> {
+        match self {
+            Change::Equal { old, .. } | Change::Removed(old) => Some(old),
+            Change::Added(_) => None,
+        }
+    }
+
+    /// The instruction in the new region, if any.
+    pub fn new_line(&self) -> Option<&DiffLine> {
+        match self {
+            Change::Equal { new, .. } | Change::Added(new) => Some(new),
+            Change::Removed(_) => None,
+        }
+    }
+}
+
+/// Decodes both regions and aligns their instructions.
+///
+/// Instructions are compared by their text formatted with relative
+/// addresses, so code that merely moved compares equal. The alignment is a
+/// longest common subsequence after stripping common prefixes and suffixes,
+/// which is quadratic in the size of the differing middle part.
+pub fn diff<UserData>(
+    decoder: &Decoder,
+    formatter: &Formatter<UserData>,
+    old: &[u8],
+    old_base: u64,
+    new: &[u8],
+    new_base: u64,
+) -> Result<Vec<Change>> {
+    let old = disassemble(decoder, formatter, old, old_base)?;
+    let new = disassemble(decoder, formatter, new, new_base)?;
+
+    let prefix = old
+        .iter()
+        .zip(&new)
+        .take_while(|(a, b)| a.key == b.key)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a.key == b.key)
+        .count();
+
+    let mid_old = &old[prefix..old.len() - suffix];
+    let mid_new = &new[prefix..new.len() - suffix];
+
+    // lcs[i][j]: length of the LCS of `mid_old[i..]` and `mid_new[j..]`.
+    let width = mid_new.len() + 1;
+    let mut lcs = vec![0u32; (mid_old.len() + 1) * width];
+    for i in (0..mid_old.len()).rev() {
+        for j in (0..mid_new.len()).rev() {
+            lcs[i * width + j] = if mid_old[i].key == mid_new[j].key {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(old.len().max(new.len()));
+    ops.extend(core::iter::repeat_n(Op::Equal, prefix));
+    let (mut i, mut j) = (0, 0);
+    while i < mid_old.len() || j < mid_new.len() {
+        if i < mid_old.len() && j < mid_new.len() && mid_old[i].key == mid_new[j].key {
+            ops.push(Op::Equal);
+            i += 1;
+            j += 1;
+        } else if j == mid_new.len()
+            || (i < mid_old.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            ops.push(Op::Removed);
+            i += 1;
+        } else {
+            ops.push(Op::Added);
+            j += 1;
+        }
+    }
+    ops.extend(core::iter::repeat_n(Op::Equal, suffix));
+
+    let mut old = old.into_iter();
+    let mut new = new.into_iter();
+    let changes = ops
+        .into_iter()
+        // There's exactly one line per op.
+        .filter_map(|op| {
+            Some(match op {
+                Op::Equal => Change::Equal {
+                    old: old.next()?,
+                    new: new.next()?,
+                },
+                Op::Removed => Change::Removed(old.next()?),
+                Op::Added => Change::Added(new.next()?),
+            })
+        })
+        .collect();
+
+    Ok(changes)
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Equal,
+    Removed,
+    Added,
+}
+
+fn disassemble<UserData>(
+    decoder: &Decoder,
+    formatter: &Formatter<UserData>,
+    mut bytes: &[u8],
+    mut ip: u64,
+) -> Result<Vec<DiffLine>> {
+    let mut lines = Vec::new();
+
+    while !bytes.is_empty() {
+        let insn = decoder
+            .decode_first::<VisibleOperands>(bytes)
+            .ok()
+            .flatten();
+        let length = insn.as_ref().map_or(1, |insn| usize::from(insn.length));
+        let (insn_bytes, rest) = bytes.split_at(length);
+
+        let (text, key) = match &insn {
+            Some(insn) => with_scratch_buffer(|buffer| {
+                formatter.format_ex(Some(ip), insn, buffer, None)?;
+                let text = buffer.as_str()?.to_owned();
+                formatter.format_ex(None, insn, buffer, None)?;
+                Ok::<_, Status>((text, buffer.as_str()?.to_owned()))
+            })?,
+            None => ("(bad)".to_owned(), format!("(bad) {:02X}", bytes[0])),
+        };
+
+        lines.push(DiffLine {
+            ip,
+            bytes: insn_bytes.to_vec(),
+            text,
+            key,
+        });
+
+        bytes = rest;
+        ip += length as u64;
+    }
+
+    Ok(lines)
+}
+
+/// Renders a change list as a unified diff.
+///
+/// Unchanged instructions within `context` instructions of a change are
+/// shown as context. Hunk headers give the start address (in hex) and the
+/// number of instructions of each side.
+///
+/// # Examples
+/// ```
+/// # use zydis::{*, diff::*};
+/// // push rcx; lea eax, [rbp-0x01]; int3
+/// static OLD: &[u8] = &[0x51, 0x8D, 0x45, 0xFF, 0xCC];
+/// // push rcx; lea eax, [rbp-0x02]; int3
+/// static NEW: &[u8] = &[0x51, 0x8D, 0x45, 0xFE, 0xCC];
+///
+/// let changes = diff(
+///     &Decoder::new64(),
+///     &Formatter::intel(),
+///     OLD,
+///     0x1000,
+///     NEW,
+///     0x1000,
+/// )
+/// .unwrap();
+///
+/// let mut out = Vec::new();
+/// write_unified(&changes, 1, &mut out).unwrap();
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     concat!(
+///         "@@ -1000,3 +1000,3 @@\n",
+///         " 0000000000001000  push rcx\n",
+///         "-0000000000001001  lea eax, [rbp-0x01]\n",
+///         "+0000000000001001  lea eax, [rbp-0x02]\n",
+///         " 0000000000001004  int3\n",
+///     )
+/// );
+/// ```
+pub fn write_unified<W: io::Write>(
+    changes: &[Change],
+    context: usize,
+    mut out: W,
+) -> io::Result<()> {
+    let changed: Vec<usize> = changes
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| !matches!(c, Change::Equal { .. }))
+        .map(|(idx, _)| idx)
+        .collect();
+
+    let mut line = String::new();
+    let mut idx = 0;
+    while idx < changed.len() {
+        let start = changed[idx].saturating_sub(context);
+        let mut end = changed[idx] + 1;
+        while idx + 1 < changed.len() && changed[idx + 1] <= end + 2 * context {
+            idx += 1;
+            end = changed[idx] + 1;
+        }
+        let end = (end + context).min(changes.len());
+        idx += 1;
+
+        let hunk = &changes[start..end];
+        let side_start = |side: fn(&Change) -> Option<&DiffLine>| {
+            hunk.iter().find_map(side).map_or(0, |line| line.ip)
+        };
+        let side_count = |side: fn(&Change) -> Option<&DiffLine>| {
+            hunk.iter().filter(|c| side(c).is_some()).count()
+        };
+
+        writeln!(
+            out,
+            "@@ -{:X},{} +{:X},{} @@",
+            side_start(Change::old_line),
+            side_count(Change::old_line),
+            side_start(Change::new_line),
+            side_count(Change::new_line),
+        )?;
+
+        for change in hunk {
+            let (marker, insn) = match change {
+                Change::Equal { old, .. } => (' ', old),
+                Change::Removed(old) => ('-', old),
+                Change::Added(new) => ('+', new),
+            };
+
+            line.clear();
+            line.push(marker);
+            write_hex_u64_padded(&mut line, insn.ip, 16).map_err(fmt_to_io)?;
+            line.push_str("  ");
+            line.push_str(&insn.text);
+            line.push('\n');
+            out.write_all(line.as_bytes())?;
+        }
+    }
+
+    Ok(())
+}
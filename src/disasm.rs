@@ -0,0 +1,111 @@
+//! One-stop decoding and formatting.
+
+use crate::*;
+use alloc::string::String;
+use core::fmt;
+
+/// A [`Decoder`] and [`Formatter`] bundled for the common case of turning
+/// machine code into text.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// static CODE: &[u8] = &[0x51, 0x8D, 0x45, 0xFF];
+///
+/// let lines: Vec<_> = Disassembler::x64()
+///     .disasm(CODE, 0x1000)
+///     .map(|line| line.unwrap().to_string())
+///     .collect();
+///
+/// assert_eq!(
+///     lines,
+///     [
+///         "0x0000000000001000 push rcx",
+///         "0x0000000000001001 lea eax, [rbp-0x01]",
+///     ]
+/// );
+/// ```
+pub struct Disassembler {
+    decoder: Decoder,
+    formatter: Formatter,
+}
+
+impl Disassembler {
+    /// Creates a disassembler from a custom decoder and formatter.
+    pub fn new(decoder: Decoder, formatter: Formatter) -> Self {
+        Self { decoder, formatter }
+    }
+
+    /// Creates a disassembler for 64 bit code, using Intel syntax.
+    pub fn x64() -> Self {
+        Self::new(Decoder::new64(), Formatter::intel())
+    }
+
+    /// Creates a disassembler for 32 bit code, using Intel syntax.
+    pub fn x86() -> Self {
+        Self::new(Decoder::new32(), Formatter::intel())
+    }
+
+    /// Returns the decoder.
+    pub fn decoder(&self) -> &Decoder {
+        &self.decoder
+    }
+
+    /// Returns the formatter.
+    pub fn formatter(&self) -> &Formatter {
+        &self.formatter
+    }
+
+    /// Returns the formatter for changing its properties or hooks.
+    pub fn formatter_mut(&mut self) -> &mut Formatter {
+        &mut self.formatter
+    }
+
+    /// Decodes and formats all instructions in `bytes`, with the first one
+    /// located at `ip`.
+    ///
+    /// Like [`Decoder::decode_all`], iteration stops at the end of `bytes`
+    /// and decoding errors are reported but not skipped.
+    pub fn disasm<'this, 'bytes>(
+        &'this self,
+        bytes: &'bytes [u8],
+        ip: u64,
+    ) -> impl Iterator<Item = Result<DisasmLine<'bytes>>> + 'this
+    where
+        'bytes: 'this,
+    {
+        self.decoder
+            .decode_all::<VisibleOperands>(bytes, ip)
+            .items()
+            .map(|item| {
+                let item = item?;
+                Ok(DisasmLine {
+                    text: item.text(&self.formatter)?,
+                    ip: item.ip,
+                    bytes: item.bytes,
+                    instruction: item.insn,
+                })
+            })
+    }
+}
+
+/// A single disassembled instruction, see [`Disassembler::disasm`].
+///
+/// Displays as the address followed by the text.
+#[derive(Debug, Clone)]
+pub struct DisasmLine<'bytes> {
+    /// Address of the instruction.
+    pub ip: u64,
+    /// The raw instruction bytes.
+    pub bytes: &'bytes [u8],
+    /// The formatted instruction.
+    pub text: String,
+    /// The decoded instruction.
+    pub instruction: Instruction<VisibleOperands>,
+}
+
+impl fmt::Display for DisasmLine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:016X} {}", self.ip, self.text)
+    }
+}
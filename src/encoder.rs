@@ -152,6 +152,20 @@ impl EncoderRequest {
         Self::new(MachineMode::LONG_64, mnemonic)
     }
 
+    /// Create a new [`MachineMode::REAL_16`] request.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let mov = EncoderRequest::new_real16(Mnemonic::MOV)
+    ///     .add_operand(Register::SP)
+    ///     .add_operand(0x7C00);
+    /// assert_eq!(mov.encode().unwrap(), [0xBC, 0x00, 0x7C]);
+    /// ```
+    pub const fn new_real16(mnemonic: Mnemonic) -> Self {
+        Self::new(MachineMode::REAL_16, mnemonic)
+    }
+
     /// Create a new encoder request from scratch.
     pub const fn new(machine_mode: MachineMode, mnemonic: Mnemonic) -> Self {
         let mut request = unsafe { zeroed!(ffi::EncoderRequest) };
@@ -246,15 +260,36 @@ impl EncoderRequest {
     ///
     /// # Panics
     ///
-    /// If the operand count exceeds [`ENCODER_MAX_OPERANDS`].
-    pub fn add_operand(mut self, op: impl Into<EncoderOperand>) -> Self {
-        assert!(
-            self.0.operand_count < ENCODER_MAX_OPERANDS as _,
-            "too many operands"
-        );
-        self.0.operands[self.0.operand_count as usize] = op.into().0;
+    /// If the operand count exceeds [`ENCODER_MAX_OPERANDS`]. See
+    /// [`EncoderRequest::try_add_operand`] for a non-panicking version.
+    #[allow(clippy::expect_used)]
+    pub fn add_operand(self, op: impl Into<EncoderOperand>) -> Self {
+        self.try_add_operand(op).expect("too many operands")
+    }
+
+    /// Adds an operand to the request.
+    ///
+    /// Fails with [`Status::OutOfRange`] if the request already has
+    /// [`ENCODER_MAX_OPERANDS`] operands.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let mut req = EncoderRequest::new64(Mnemonic::NOP);
+    /// for _ in 0..ENCODER_MAX_OPERANDS {
+    ///     req = req.try_add_operand(Register::RAX).unwrap();
+    /// }
+    /// assert_eq!(req.try_add_operand(Register::RAX).err(), Some(Status::OutOfRange));
+    /// ```
+    pub fn try_add_operand(mut self, op: impl Into<EncoderOperand>) -> Result<Self> {
+        let slot = self
+            .0
+            .operands
+            .get_mut(usize::from(self.0.operand_count))
+            .ok_or(Status::OutOfRange)?;
+        *slot = op.into().0;
         self.0.operand_count += 1;
-        self
+        Ok(self)
     }
 
     /// Clears the operand list.
@@ -267,13 +302,74 @@ impl EncoderRequest {
     ///
     /// # Panics
     ///
-    /// If the index was not previously populated.
-    pub fn replace_operand(mut self, idx: usize, new: impl Into<EncoderOperand>) -> Self {
-        assert!(
-            idx < self.0.operand_count as _,
-            "operand index out of bounds"
-        );
+    /// If the index was not previously populated. See
+    /// [`EncoderRequest::try_replace_operand`] for a non-panicking version.
+    #[allow(clippy::expect_used)]
+    pub fn replace_operand(self, idx: usize, new: impl Into<EncoderOperand>) -> Self {
+        self.try_replace_operand(idx, new)
+            .expect("operand index out of bounds")
+    }
+
+    /// Replaces the operand at the given index.
+    ///
+    /// Fails with [`Status::OutOfRange`] if the index was not previously
+    /// populated.
+    pub fn try_replace_operand(
+        mut self,
+        idx: usize,
+        new: impl Into<EncoderOperand>,
+    ) -> Result<Self> {
+        if idx >= usize::from(self.0.operand_count) {
+            return Err(Status::OutOfRange);
+        }
         self.0.operands[idx] = new.into().0;
+        Ok(self)
+    }
+
+    /// Replaces every operand with the result of `f`, which receives the
+    /// operand index and the current operand.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let swap = |reg| if reg == Register::RAX { Register::R10 } else { reg };
+    /// let mov = insn64!(MOV RAX, qword ptr [RAX + 8]).map_operands(|_, op| {
+    ///     let mut op = op.clone();
+    ///     op.reg.value = swap(op.reg.value);
+    ///     op.mem.base = swap(op.mem.base);
+    ///     op.mem.index = swap(op.mem.index);
+    ///     op
+    /// });
+    /// assert_eq!(mov.encode().unwrap(), [0x4D, 0x8B, 0x52, 0x08]); // mov r10, [r10+8]
+    /// ```
+    pub fn map_operands(
+        mut self,
+        mut f: impl FnMut(usize, &EncoderOperand) -> EncoderOperand,
+    ) -> Self {
+        for (idx, op) in self.operands_mut().iter_mut().enumerate() {
+            *op = f(idx, op);
+        }
+        self
+    }
+
+    /// Removes all operands for which `pred` returns `false`, keeping the
+    /// order of the others.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let imul = insn64!(IMUL RAX, RCX, 3).retain_operands(|op| op.ty != OperandType::IMMEDIATE);
+    /// assert_eq!(imul.encode().unwrap(), [0x48, 0x0F, 0xAF, 0xC1]); // imul rax, rcx
+    /// ```
+    pub fn retain_operands(mut self, mut pred: impl FnMut(&EncoderOperand) -> bool) -> Self {
+        let mut kept = 0;
+        for idx in 0..usize::from(self.0.operand_count) {
+            if pred(&self.operands()[idx]) {
+                self.0.operands.swap(kept, idx);
+                kept += 1;
+            }
+        }
+        self.0.operand_count = kept as u8;
         self
     }
 
@@ -304,28 +400,387 @@ impl EncoderRequest {
         out.resize(length, 0);
         Ok(out)
     }
-}
 
-/// Converts a decoded instruction into an encoder request.
-impl<const N: usize> From<Instruction<OperandArrayVec<N>>> for EncoderRequest {
-    fn from(instr: Instruction<OperandArrayVec<N>>) -> Self {
+    /// Encodes the instruction into the given buffer, treating relative
+    /// operands (e.g. branch targets) as absolute addresses.
+    ///
+    /// `runtime_address` is the address the instruction will be placed at.
+    /// The encoder picks the smallest branch width able to reach the target.
+    pub fn encode_absolute_into(&self, buf: &mut [u8], runtime_address: u64) -> Result<usize> {
+        unsafe {
+            let mut length = buf.len();
+            ffi::ZydisEncoderEncodeInstructionAbsolute(
+                &self.0,
+                buf.as_ptr() as _,
+                &mut length,
+                runtime_address,
+            )
+            .as_result()?;
+            Ok(length)
+        }
+    }
+
+    /// Encodes the instruction into a new buffer, treating relative operands
+    /// as absolute addresses.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let jmp = insn64!(JMP 0x1010).encode_absolute(0x1000).unwrap();
+    /// assert_eq!(jmp, [0xEB, 0x0E]);
+    /// ```
+    pub fn encode_absolute(&self, runtime_address: u64) -> Result<Vec<u8>> {
+        let mut out = vec![0; MAX_INSTRUCTION_LENGTH];
+        let length = self.encode_absolute_into(&mut out[..], runtime_address)?;
+        out.resize(length, 0);
+        Ok(out)
+    }
+
+    /// Encodes the instruction with an explicit sequence of legacy prefixes.
+    ///
+    /// The legacy prefixes picked by the encoder are replaced by `prefixes`,
+    /// emitted verbatim and in the given order. This allows redundant and
+    /// reordered prefixes, e.g. for testing other decoders. `prefixes` must
+    /// only contain legacy prefix bytes and include every prefix the encoder
+    /// emits, so the instruction keeps its meaning (ordering sensitive
+    /// mandatory prefixes aside). Returns [`Status::InvalidArgument`]
+    /// otherwise and [`Status::InstructionTooLong`] if the result exceeds
+    /// [`MAX_INSTRUCTION_LENGTH`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // notrack jmp rax
+    /// let jmp = insn64!(JMP RAX).encode_with_prefixes(&[0x3E]).unwrap();
+    /// assert_eq!(jmp, [0x3E, 0xFF, 0xE0]);
+    ///
+    /// // mov ax, cx with the operand size prefix after a redundant cs
+    /// let mov = insn64!(MOV AX, CX);
+    /// assert_eq!(mov.encode().unwrap(), [0x66, 0x89, 0xC8]);
+    /// assert_eq!(
+    ///     mov.encode_with_prefixes(&[0x2E, 0x66]).unwrap(),
+    ///     [0x2E, 0x66, 0x89, 0xC8]
+    /// );
+    /// assert_eq!(mov.encode_with_prefixes(&[0x2E]), Err(Status::InvalidArgument));
+    /// ```
+    pub fn encode_with_prefixes(&self, prefixes: &[u8]) -> Result<Vec<u8>> {
+        const LEGACY_PREFIXES: [u8; 11] = [
+            0xF0, 0xF2, 0xF3, 0x2E, 0x36, 0x3E, 0x26, 0x64, 0x65, 0x66, 0x67,
+        ];
+
+        let mut buf = [0; MAX_INSTRUCTION_LENGTH];
+        let length = self.encode_into(&mut buf)?;
+        let encoded = &buf[..length];
+        let emitted = encoded
+            .iter()
+            .take_while(|b| LEGACY_PREFIXES.contains(b))
+            .count();
+
+        let mut remaining = prefixes.to_vec();
+        for prefix in &encoded[..emitted] {
+            let pos = remaining
+                .iter()
+                .position(|b| b == prefix)
+                .ok_or(Status::InvalidArgument)?;
+            remaining.swap_remove(pos);
+        }
+        if !remaining.iter().all(|b| LEGACY_PREFIXES.contains(b)) {
+            return Err(Status::InvalidArgument);
+        }
+
+        let out: Vec<u8> = prefixes
+            .iter()
+            .chain(&encoded[emitted..])
+            .copied()
+            .collect();
+        if out.len() > MAX_INSTRUCTION_LENGTH {
+            return Err(Status::InstructionTooLong);
+        }
+        Ok(out)
+    }
+
+    /// Encodes the instruction into at most `max_len` bytes.
+    ///
+    /// If the request doesn't fit as is, alternative encodings with the same
+    /// meaning are tried: relative branches with the branch type and width
+    /// left to the encoder and, for restricted [`EncodableEncoding`]s, any
+    /// encoding. Returns [`Status::InstructionTooLong`] if none fits.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let jmp = insn64!(JMP 0x10).set_branch_width(BranchWidth::_32);
+    /// assert_eq!(jmp.encode().unwrap(), [0xE9, 0x10, 0x00, 0x00, 0x00]);
+    /// assert_eq!(jmp.encode_with_max_len(2).unwrap(), [0xEB, 0x10]);
+    ///
+    /// let mov = insn64!(MOV RAX, 0x1122334455667788u64);
+    /// assert_eq!(mov.encode_with_max_len(5), Err(Status::InstructionTooLong));
+    /// ```
+    pub fn encode_with_max_len(&self, max_len: usize) -> Result<Vec<u8>> {
+        let mut candidates = [Some(self.clone()), None, None];
+
+        let relative = self.branch_type != BranchType::FAR
+            && self
+                .operands()
+                .iter()
+                .any(|op| op.ty == OperandType::IMMEDIATE);
+        if relative
+            && (self.branch_type != BranchType::NONE || self.branch_width != BranchWidth::NONE)
+        {
+            candidates[1] = Some(
+                self.clone()
+                    .set_branch_type(BranchType::NONE)
+                    .set_branch_width(BranchWidth::NONE),
+            );
+        }
+        if self.allowed_encodings != EncodableEncoding::DEFAULT {
+            let mut any = candidates[1].clone().unwrap_or_else(|| self.clone());
+            any.allowed_encodings = EncodableEncoding::DEFAULT;
+            candidates[2] = Some(any);
+        }
+
+        let mut buf = [0; MAX_INSTRUCTION_LENGTH];
+        for request in candidates.iter().flatten() {
+            if let Ok(length) = request.encode_into(&mut buf) {
+                if length <= max_len {
+                    return Ok(buf[..length].to_vec());
+                }
+            }
+        }
+
+        // Report why the request itself can't be encoded, if it can't.
+        self.encode_into(&mut buf)?;
+        Err(Status::InstructionTooLong)
+    }
+
+    /// Converts a decoded, possibly modified instruction into an encoder
+    /// request.
+    pub fn from_instruction<const N: usize>(
+        instr: &Instruction<OperandArrayVec<N>>,
+    ) -> Result<Self> {
         unsafe {
             let ops = instr.visible_operands();
             let mut request = MaybeUninit::uninit();
             ffi::ZydisEncoderDecodedInstructionToEncoderRequest(
-                &*instr,
+                &**instr,
                 ops.as_ptr(),
                 ops.len() as _,
                 request.as_mut_ptr(),
             )
-            .as_result()
-            .expect(
-                "our rust wrapper for instructions is immutable and unchanged decoded \
-                 instructions should always be convertible",
-            );
-            Self(request.assume_init())
+            .as_result()?;
+            ffi::EncoderRequest::check_variants(request.as_ptr())?;
+            Ok(Self(request.assume_init()))
         }
     }
+
+    /// Checks whether `instruction` is equivalent to this request.
+    ///
+    /// Compares the machine mode, mnemonic, encodable prefixes and visible
+    /// operands, ignoring how the instruction was encoded. Immediates are
+    /// compared truncated to the decoded operand size, so e.g. `-1` matches
+    /// a 32-bit `0xFFFFFFFF`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // mov rax, rcx using the alternative `8B /r` form
+    /// let decoded: Instruction<VisibleOperands> = Decoder::new64()
+    ///     .decode_first(&[0x48, 0x8B, 0xC1])
+    ///     .unwrap()
+    ///     .unwrap();
+    ///
+    /// assert!(insn64!(MOV RAX, RCX).matches(&decoded));
+    /// assert!(!insn64!(MOV RAX, RDX).matches(&decoded));
+    /// ```
+    pub fn matches<const N: usize>(&self, instruction: &Instruction<OperandArrayVec<N>>) -> bool {
+        let Ok(decoded) = Self::from_instruction(instruction) else {
+            return false;
+        };
+
+        self.machine_mode == decoded.machine_mode
+            && self.mnemonic == decoded.mnemonic
+            && self.prefixes.bits() & ENCODABLE_PREFIXES
+                == decoded.prefixes.bits() & ENCODABLE_PREFIXES
+            && self.operands().len() == decoded.operands().len()
+            && self
+                .operands()
+                .iter()
+                .zip(decoded.operands())
+                .zip(instruction.visible_operands())
+                .all(|((a, b), op)| {
+                    if a.ty != b.ty {
+                        return false;
+                    }
+                    match a.ty {
+                        OperandType::REGISTER => a.reg.value == b.reg.value,
+                        OperandType::MEMORY => {
+                            a.mem.base == b.mem.base
+                                && a.mem.index == b.mem.index
+                                && (a.mem.index == Register::NONE || a.mem.scale == b.mem.scale)
+                                && a.mem.displacement == b.mem.displacement
+                                && a.mem.size == b.mem.size
+                        }
+                        OperandType::POINTER => a.ptr == b.ptr,
+                        OperandType::IMMEDIATE => {
+                            let mask = match op.size {
+                                0 | 64.. => u64::MAX,
+                                bits => (1 << bits) - 1,
+                            };
+                            a.imm & mask == b.imm & mask
+                        }
+                        _ => true,
+                    }
+                })
+    }
+}
+
+/// Converts a decoded instruction into an encoder request.
+///
+/// # Panics
+///
+/// If the instruction isn't convertible, e.g. because its operands were
+/// truncated or modified via [`Instruction::operands_mut`]. Use
+/// [`EncoderRequest::from_instruction`] to handle this case.
+impl<const N: usize> From<Instruction<OperandArrayVec<N>>> for EncoderRequest {
+    fn from(instr: Instruction<OperandArrayVec<N>>) -> Self {
+        instr.encoder_request()
+    }
+}
+
+impl<const N: usize> Instruction<OperandArrayVec<N>> {
+    /// Converts the instruction into an encoder request without consuming it.
+    ///
+    /// # Panics
+    ///
+    /// If the instruction isn't convertible, e.g. because its operands were
+    /// truncated or modified via [`Instruction::operands_mut`]. Use
+    /// [`EncoderRequest::from_instruction`] to handle this case.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // mov rax, rcx
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(&[0x48, 0x89, 0xC8])
+    ///     .unwrap()
+    ///     .unwrap();
+    ///
+    /// let request = insn.encoder_request().set_mnemonic(Mnemonic::SUB);
+    /// assert_eq!(request.encode().unwrap(), [0x48, 0x29, 0xC8]);
+    ///
+    /// // The original instruction is still available.
+    /// assert_eq!(insn.mnemonic, Mnemonic::MOV);
+    /// ```
+    #[allow(clippy::expect_used)]
+    pub fn encoder_request(&self) -> EncoderRequest {
+        EncoderRequest::from_instruction(self)
+            .expect("unchanged decoded instructions should always be convertible")
+    }
+
+    /// Re-encodes the instruction, taking changes made via
+    /// [`Instruction::operands_mut`] into account.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // mov rax, rcx
+    /// let mut insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(&[0x48, 0x89, 0xC8])
+    ///     .unwrap()
+    ///     .unwrap();
+    ///
+    /// insn.operands_mut()[1].kind = ffi::DecodedOperandKind::Reg(Register::RDX);
+    /// assert_eq!(insn.reencode().unwrap(), [0x48, 0x89, 0xD0]);
+    /// ```
+    pub fn reencode(&self) -> Result<Vec<u8>> {
+        EncoderRequest::from_instruction(self)?.encode()
+    }
+
+    /// Re-encodes the instruction into the given buffer.
+    ///
+    /// See [`Instruction::reencode`].
+    pub fn reencode_into(&self, buf: &mut [u8]) -> Result<usize> {
+        EncoderRequest::from_instruction(self)?.encode_into(buf)
+    }
+
+    /// Re-encodes the instruction and returns the bytes.
+    ///
+    /// Same as [`Instruction::reencode`], for use together with
+    /// [`Instruction::verify_roundtrip`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // mov rax, rcx using the alternative `8B /r` form
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(&[0x48, 0x8B, 0xC1])
+    ///     .unwrap()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(insn.reencode_bytes().unwrap(), [0x48, 0x89, 0xC8]);
+    /// ```
+    #[inline]
+    pub fn reencode_bytes(&self) -> Result<Vec<u8>> {
+        self.reencode()
+    }
+
+    /// Checks whether re-encoding the instruction reproduces the bytes it was
+    /// decoded from.
+    ///
+    /// `original` is the buffer the instruction was decoded from; bytes past
+    /// the instruction length are ignored. Returns `Ok(false)` if the encoder
+    /// picks a different encoding, e.g. because the original used an
+    /// alternative form or contained redundant prefixes. Use
+    /// [`Instruction::reencode_bytes`] to obtain the re-encoded bytes.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let decoder = Decoder::new64();
+    /// let roundtrips = |code: &[u8]| {
+    ///     decoder
+    ///         .decode_first::<VisibleOperands>(code)
+    ///         .unwrap()
+    ///         .unwrap()
+    ///         .verify_roundtrip(code)
+    ///         .unwrap()
+    /// };
+    ///
+    /// // mov rax, rcx
+    /// assert!(roundtrips(&[0x48, 0x89, 0xC8]));
+    /// // mov rax, rcx using the alternative `8B /r` form
+    /// assert!(!roundtrips(&[0x48, 0x8B, 0xC1]));
+    /// ```
+    pub fn verify_roundtrip(&self, original: &[u8]) -> Result<bool> {
+        let mut buf = [0; MAX_INSTRUCTION_LENGTH];
+        let length = self.reencode_into(&mut buf)?;
+        Ok(original.get(..usize::from(self.length)) == Some(&buf[..length]))
+    }
+}
+
+/// Encodes a sequence of instructions into one contiguous buffer.
+///
+/// Returns the code along with the offset of each instruction within it.
+/// Stops at the first instruction that fails to encode.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// let (code, offsets) =
+///     encode_all([insn64!(PUSH RBP), insn64!(MOV RBP, RSP), insn64!(RET)]).unwrap();
+/// assert_eq!(code, [0x55, 0x48, 0x89, 0xE5, 0xC3]);
+/// assert_eq!(offsets, [0, 1, 4]);
+/// ```
+pub fn encode_all(
+    requests: impl IntoIterator<Item = EncoderRequest>,
+) -> Result<(Vec<u8>, Vec<usize>)> {
+    let mut code = Vec::new();
+    let mut offsets = Vec::new();
+    for request in requests {
+        offsets.push(code.len());
+        request.encode_extend(&mut code)?;
+    }
+    Ok((code, offsets))
 }
 
 /// Describes an operand in an [`EncoderRequest`].
@@ -516,9 +971,21 @@ macro_rules! mem_impl {
     (@size zmmword) => { 512/8 };
     (@size $x:tt) => { compile_error!(concat!("bad operand size: ", stringify!($x))) };
 
+    (@scale 1) => { 1 };
+    (@scale 2) => { 2 };
+    (@scale 4) => { 4 };
+    (@scale 8) => { 8 };
+    (@scale $x:tt) => { compile_error!(concat!("scale must be 1, 2, 4 or 8, found ", stringify!($x))) };
+
     (@base_or_disp $x:ident $disp:literal) => {
         $x.displacement = $disp;
     };
+    (@base_or_disp $x:ident $disp:literal $($tail:tt)+) => {
+        compile_error!(concat!(
+            "the displacement must come last in a memory operand, found `",
+            stringify!($($tail)+), "` after it"
+        ))
+    };
     (@base_or_disp $x:ident $base:ident $($tail:tt)*) => {
         $x.base = $crate::Register::$base;
         $crate::mem_impl!(@index_or_disp_or_scale $x $($tail)*);
@@ -539,6 +1006,12 @@ macro_rules! mem_impl {
 
         $crate::mem_impl!(@index_or_disp_or_scale $x $($tail)*);
     };
+    (@base_or_disp $x:ident $($tail:tt)*) => {
+        compile_error!(concat!(
+            "expected a base register, displacement or `(expr)` in memory operand, found `",
+            stringify!($($tail)*), "`"
+        ))
+    };
 
     (@index_or_disp_or_scale $x:ident) => {};
     (@index_or_disp_or_scale $x:ident + $disp:literal) => {
@@ -557,18 +1030,24 @@ macro_rules! mem_impl {
         $x.scale = 1;
         $crate::mem_impl!(@scale_or_disp $x $($tail)*);
     };
-    (@index_or_disp_or_scale $x:ident * $scale:literal $($tail:tt)*) => {
+    (@index_or_disp_or_scale $x:ident * ($scale:expr) $($tail:tt)*) => {
         $x.index = $x.base;
         $x.base = $crate::Register::NONE;
         $x.scale = $scale;
         $crate::mem_impl!(@scale_or_disp $x $($tail)*);
     };
-    (@index_or_disp_or_scale $x:ident * ($scale:expr) $($tail:tt)*) => {
+    (@index_or_disp_or_scale $x:ident * $scale:tt $($tail:tt)*) => {
         $x.index = $x.base;
         $x.base = $crate::Register::NONE;
-        $x.scale = $scale;
+        $x.scale = $crate::mem_impl!(@scale $scale);
         $crate::mem_impl!(@scale_or_disp $x $($tail)*);
     };
+    (@index_or_disp_or_scale $x:ident $($tail:tt)*) => {
+        compile_error!(concat!(
+            "expected `+ index`, `* scale` or `+ disp` in memory operand, found `",
+            stringify!($($tail)*), "`"
+        ))
+    };
 
     (@scale_or_disp $x:ident) => {};
     (@scale_or_disp $x:ident + $disp:literal) => {
@@ -577,14 +1056,20 @@ macro_rules! mem_impl {
     (@scale_or_disp $x:ident + ($disp:expr)) => {
         $x.displacement = $disp;
     };
-    (@scale_or_disp $x:ident * $scale:literal $($tail:tt)*) => {
+    (@scale_or_disp $x:ident * ($scale:expr) $($tail:tt)*) => {
         $x.scale = $scale;
         $crate::mem_impl!(@disp $x $($tail)*);
     };
-    (@scale_or_disp $x:ident * ($scale:expr) $($tail:tt)*) => {
-        $x.scale = $scale;
+    (@scale_or_disp $x:ident * $scale:tt $($tail:tt)*) => {
+        $x.scale = $crate::mem_impl!(@scale $scale);
         $crate::mem_impl!(@disp $x $($tail)*);
     };
+    (@scale_or_disp $x:ident $($tail:tt)*) => {
+        compile_error!(concat!(
+            "expected `* scale` or `+ disp` in memory operand, found `",
+            stringify!($($tail)*), "`"
+        ))
+    };
 
     (@disp $x:ident) => {};
     (@disp $x:ident + $disp:literal) => {
@@ -593,6 +1078,12 @@ macro_rules! mem_impl {
     (@disp $x:ident + ($disp:expr)) => {
         $x.displacement = $disp;
     };
+    (@disp $x:ident $($tail:tt)*) => {
+        compile_error!(concat!(
+            "expected `+ disp` at the end of memory operand, found `",
+            stringify!($($tail)*), "`"
+        ))
+    };
 }
 
 /// Macro for creating memory operands.
@@ -627,6 +1118,14 @@ macro_rules! mem_impl {
 /// mem!(qword ptr [(my_dyn_reg) * 4 + (my_dyn_disp)]);
 /// mem!(qword ptr [RAX * (4 * 2) + 0x1234]);
 /// ```
+///
+/// Malformed operands are rejected at compile time with a description of
+/// what was expected, e.g. for invalid scales:
+///
+/// ```compile_fail
+/// # use zydis::*;
+/// mem!(dword ptr [RDX + RSI * 3]); // error: scale must be 1, 2, 4 or 8, found 3
+/// ```
 #[macro_export]
 macro_rules! mem {
     ($size:tt ptr [ $($base_index_scale_disp:tt)* ]) => {{
@@ -635,6 +1134,12 @@ macro_rules! mem {
         $crate::mem_impl!(@base_or_disp x $($base_index_scale_disp)*);
         $crate::EncoderOperand::mem_custom(x)
     }};
+    ($($tail:tt)*) => {
+        compile_error!(concat!(
+            "expected a memory operand like `dword ptr [base + index * scale + disp]`, found `",
+            stringify!($($tail)*), "`"
+        ))
+    };
 }
 
 #[doc(hidden)]
@@ -660,13 +1165,25 @@ macro_rules! insn_munch_operands {
         $crate::insn_munch_operands!($r $($($tail)*)*);
     };
 
-    // TODO: pointer operands for far jumps etc
+    // Pointer operands for far jumps and calls.
+    ($r:ident far $seg:tt : $off:tt $(, $($tail:tt)*)?) => {
+        $r = $r.add_operand($crate::EncoderOperand::ptr($seg, $off));
+        $crate::insn_munch_operands!($r $($($tail)*)*);
+    };
 
     // Arbitrary expressions that eval to something `impl Into<EncoderOperand>`.
     ($r:ident ($e:expr) $(, $($tail:tt)*)?) => {
         $r = $r.add_operand($e);
         $crate::insn_munch_operands!($r $($($tail)*)*);
     };
+
+    ($r:ident $($tail:tt)*) => {
+        compile_error!(concat!(
+            "expected a literal, register, `size ptr [...]`, `far seg:off` or `(expr)` operand, \
+             followed by `,` or the end, found `",
+            stringify!($($tail)*), "`"
+        ))
+    };
 }
 
 /// Macro for conveniently creating encoder requests (64-bit variant).
@@ -677,7 +1194,10 @@ macro_rules! insn_munch_operands {
 ///
 /// If you wish to insert variables or expressions, you need to wrap them
 /// into parenthesis. The parenthesized expression must eval to something
-/// that conforms to `impl Into<EncoderOperand>`.
+/// that conforms to `impl Into<EncoderOperand>`. This includes
+/// [`asm::Label`]s, which can be used as branch targets when pushing the
+/// request into the [`asm::CodeBuffer`] that created them:
+/// `insn64!(JNZ (label))`.
 ///
 /// Produces an [`EncoderRequest`] instance.
 ///
@@ -693,14 +1213,40 @@ macro_rules! insn_munch_operands {
 /// insn64!(PUSH (some_reg)).encode().unwrap();
 /// insn64!(PUSH (some_imm + 123)).encode().unwrap();
 /// insn64!(MOV RSI, (Register::RDI)).encode().unwrap();
+///
+/// // A trailing comma is accepted.
+/// insn64!(MOV RAX, 1234,).encode().unwrap();
+///
+/// // Far pointers are written as `far segment:offset`.
+/// let jmp = insn32!(JMP far 0x08:0x401000).encode().unwrap();
+/// assert_eq!(jmp, [0xEA, 0x00, 0x10, 0x40, 0x00, 0x08, 0x00]);
+/// let (seg, off) = (0x08, 0x401000);
+/// insn32!(CALL far (seg):(off)).encode().unwrap();
+/// ```
+///
+/// Unknown register names are reported at the offending identifier, and
+/// malformed operands with a description of what was expected:
+///
+/// ```compile_fail
+/// # use zydis::*;
+/// insn64!(MOV RXA, 1234); // error: no variant or associated item named `RXA`
+/// ```
+///
+/// ```compile_fail
+/// # use zydis::*;
+/// insn64!(MOV RAX RCX); // error: expected a literal, register, ... found `RAX RCX`
 /// ```
 #[macro_export]
 macro_rules! insn64 {
     ($mnemonic:ident $($operands:tt)*) => {{
+        #[allow(unused_mut)]
         let mut r = EncoderRequest::new64($crate::Mnemonic::$mnemonic);
         $crate::insn_munch_operands!(r $($operands)*);
         r
-    }}
+    }};
+    ($($tail:tt)*) => {
+        compile_error!(concat!("expected a mnemonic followed by operands, found `", stringify!($($tail)*), "`"))
+    };
 }
 
 /// Macro for conveniently creating encoder requests (32-bit variant).
@@ -709,10 +1255,93 @@ macro_rules! insn64 {
 #[macro_export]
 macro_rules! insn32 {
     ($mnemonic:ident $($operands:tt)*) => {{
+        #[allow(unused_mut)]
         let mut r = EncoderRequest::new32($crate::Mnemonic::$mnemonic);
         $crate::insn_munch_operands!(r $($operands)*);
         r
-    }}
+    }};
+    ($($tail:tt)*) => {
+        compile_error!(concat!("expected a mnemonic followed by operands, found `", stringify!($($tail)*), "`"))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! asm_munch_insns {
+    (@emit $new:ident $buf:ident $len:ident) => {};
+    (@emit $new:ident $buf:ident $len:ident $mnemonic:ident $($operands:tt)*) => {
+        #[allow(unused_mut)]
+        let mut r = $crate::EncoderRequest::$new($crate::Mnemonic::$mnemonic);
+        $crate::insn_munch_operands!(r $($operands)*);
+        $len += r.encode_extend($buf)?;
+    };
+
+    ($new:ident $buf:ident $len:ident [$($insn:tt)*]) => {
+        $crate::asm_munch_insns!(@emit $new $buf $len $($insn)*);
+    };
+    ($new:ident $buf:ident $len:ident [$($insn:tt)*] ; $($tail:tt)*) => {
+        $crate::asm_munch_insns!(@emit $new $buf $len $($insn)*);
+        $crate::asm_munch_insns!($new $buf $len [] $($tail)*);
+    };
+    ($new:ident $buf:ident $len:ident [$($insn:tt)*] $next:tt $($tail:tt)*) => {
+        $crate::asm_munch_insns!($new $buf $len [$($insn)* $next] $($tail)*);
+    };
+}
+
+/// Macro for encoding a block of instructions into a buffer (64-bit
+/// variant).
+///
+/// Takes the buffer (anything implementing `Extend<u8>`), followed by the
+/// instructions, each separated by `;`. Instructions use the same syntax as
+/// [`insn64`]. Evaluates to a [`Result`] holding the total number of bytes
+/// appended, stopping at the first instruction that fails to encode.
+///
+/// The macro processes its input one token at a time, so very long blocks
+/// may require raising the `recursion_limit`.
+///
+/// ```rust
+/// # use zydis::*;
+/// let offset = 0x10;
+/// let mut code = Vec::new();
+/// let len = asm64!(code;
+///     PUSH RBP;
+///     MOV RBP, RSP;
+///     LEA RAX, qword ptr [RDI + RSI];
+///     MOV RAX, qword ptr [RAX + (offset)];
+///     POP RBP;
+///     RET;
+/// )
+/// .unwrap();
+///
+/// assert_eq!(len, code.len());
+/// assert_eq!(code[..4], [0x55, 0x48, 0x89, 0xE5]);
+/// ```
+#[macro_export]
+macro_rules! asm64 {
+    ($buf:expr; $($insns:tt)*) => {
+        (|| -> $crate::Result<usize> {
+            let buf = &mut $buf;
+            let mut len = 0;
+            $crate::asm_munch_insns!(new64 buf len [] $($insns)*);
+            Ok(len)
+        })()
+    };
+}
+
+/// Macro for encoding a block of instructions into a buffer (32-bit
+/// variant).
+///
+/// See [`asm64`] for more details: this macro works exactly the same.
+#[macro_export]
+macro_rules! asm32 {
+    ($buf:expr; $($insns:tt)*) => {
+        (|| -> $crate::Result<usize> {
+            let buf = &mut $buf;
+            let mut len = 0;
+            $crate::asm_munch_insns!(new32 buf len [] $($insns)*);
+            Ok(len)
+        })()
+    };
 }
 
 #[cfg(all(test, feature = "formatter"))]
@@ -4,9 +4,9 @@
 mod generated;
 
 pub use self::generated::*;
-use super::ffi;
+use super::{ffi, Status};
 use bitflags::bitflags;
-use core::fmt;
+use core::{fmt, mem, ops, slice, str};
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
 
@@ -43,9 +43,215 @@ pub const ENCODABLE_PREFIXES: u64 = InstructionAttributes::HAS_LOCK.bits()
     | InstructionAttributes::HAS_SEGMENT_FS.bits()
     | InstructionAttributes::HAS_SEGMENT_GS.bits();
 
+/// A static string provided by the Zydis C library.
+///
+/// Wraps an [`ffi::ShortString`], whose length is known upfront, so no
+/// `strlen` is needed to access it.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShortStr(&'static str);
+
+impl ShortStr {
+    /// Wraps a short string returned by the C library, e.g. by
+    /// [`ffi::ZydisRegisterGetStringWrapped`].
+    ///
+    /// Returns `None` if `ptr` is null.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must either be null or point to a static, ASCII encoded
+    /// [`ffi::ShortString`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let name = unsafe { ShortStr::from_ptr(ffi::ZydisMnemonicGetShortString(Mnemonic::JMP)) };
+    /// assert_eq!(name.unwrap().as_str(), "jmp");
+    /// ```
+    pub unsafe fn from_ptr(ptr: *const ffi::ShortString) -> Option<Self> {
+        let short = ptr.as_ref()?;
+        let bytes = slice::from_raw_parts(short.data as *const u8, usize::from(short.size));
+        Some(Self(str::from_utf8_unchecked(bytes)))
+    }
+
+    /// Returns the string.
+    #[inline]
+    pub const fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl ops::Deref for ShortStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl AsRef<str> for ShortStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+impl fmt::Debug for ShortStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.0, f)
+    }
+}
+
+impl fmt::Display for ShortStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// Error returned when converting an integer that doesn't correspond to any
+/// variant of a C-mirroring enum.
+///
+/// Decoding checks the enum fields written by Zydis this way and fails with
+/// [`Status::OutOfRange`] on unknown variants, e.g. when linking against a
+/// newer C library.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// assert_eq!(Register::try_from(0u32), Ok(Register::NONE));
+/// assert_eq!(Register::try_from(0x10000u32), Err(UnknownVariant(0x10000)));
+/// assert_eq!(Padding::try_from(-1i32), Ok(Padding::AUTO));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnknownVariant(pub i64);
+
+impl fmt::Display for UnknownVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown enum variant {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownVariant {}
+
+impl From<UnknownVariant> for Status {
+    #[inline]
+    fn from(_: UnknownVariant) -> Self {
+        Status::OutOfRange
+    }
+}
+
+/// Implements `TryFrom<u32>` and `TryFrom<i32>` for enums with
+/// discriminants `0..=$max`.
+macro_rules! impl_try_from_contiguous {
+    ($($ty:ident => $max:ident),* $(,)?) => {$(
+        impl TryFrom<u32> for $ty {
+            type Error = UnknownVariant;
+
+            #[inline]
+            fn try_from(value: u32) -> Result<Self, UnknownVariant> {
+                if value as usize > $max {
+                    return Err(UnknownVariant(value.into()));
+                }
+                // SAFETY: the enum is `repr(C)` with contiguous discriminants
+                // from zero to `$max`.
+                Ok(unsafe { mem::transmute::<u32, $ty>(value) })
+            }
+        }
+
+        impl TryFrom<i32> for $ty {
+            type Error = UnknownVariant;
+
+            #[inline]
+            fn try_from(value: i32) -> Result<Self, UnknownVariant> {
+                u32::try_from(value)
+                    .map_err(|_| UnknownVariant(value.into()))
+                    .and_then(Self::try_from)
+            }
+        }
+    )*};
+}
+
+/// Implements `TryFrom<i32>` and `TryFrom<u32>` for enums with arbitrary
+/// discriminants by listing all variants.
+macro_rules! impl_try_from_variants {
+    ($($ty:ident { $($variant:ident),* $(,)? })*) => {$(
+        impl TryFrom<i32> for $ty {
+            type Error = UnknownVariant;
+
+            fn try_from(value: i32) -> Result<Self, UnknownVariant> {
+                $(
+                    if value == $ty::$variant as i32 {
+                        return Ok($ty::$variant);
+                    }
+                )*
+                Err(UnknownVariant(value.into()))
+            }
+        }
+
+        impl TryFrom<u32> for $ty {
+            type Error = UnknownVariant;
+
+            #[inline]
+            fn try_from(value: u32) -> Result<Self, UnknownVariant> {
+                i32::try_from(value)
+                    .map_err(|_| UnknownVariant(value.into()))
+                    .and_then(Self::try_from)
+            }
+        }
+    )*};
+}
+
+impl_try_from_contiguous! {
+    InstructionCategory => CATEGORY_MAX_VALUE,
+    ISASet => ISA_SET_MAX_VALUE,
+    ISAExt => ISA_EXT_MAX_VALUE,
+    Mnemonic => MNEMONIC_MAX_VALUE,
+    MachineMode => MACHINE_MODE_MAX_VALUE,
+    StackWidth => STACK_WIDTH_MAX_VALUE,
+    ElementType => ELEMENT_TYPE_MAX_VALUE,
+    OperandType => OPERAND_TYPE_MAX_VALUE,
+    OperandEncoding => OPERAND_ENCODING_MAX_VALUE,
+    OperandVisibility => OPERAND_VISIBILITY_MAX_VALUE,
+    InstructionEncoding => INSTRUCTION_ENCODING_MAX_VALUE,
+    OpcodeMap => OPCODE_MAP_MAX_VALUE,
+    Register => REGISTER_MAX_VALUE,
+    RegisterKind => REGKIND_MAX_VALUE,
+    RegisterClass => REGCLASS_MAX_VALUE,
+    MemoryOperandType => MEMOP_TYPE_MAX_VALUE,
+    BranchType => BRANCH_TYPE_MAX_VALUE,
+    ExceptionClass => EXCEPTION_CLASS_MAX_VALUE,
+    MaskMode => MASK_MODE_MAX_VALUE,
+    BroadcastMode => BROADCAST_MODE_MAX_VALUE,
+    RoundingMode => ROUNDING_MODE_MAX_VALUE,
+    SwizzleMode => SWIZZLE_MODE_MAX_VALUE,
+    ConversionMode => CONVERSION_MODE_MAX_VALUE,
+    PrefixType => PREFIX_TYPE_MAX_VALUE,
+    DecoderMode => DECODER_MODE_MAX_VALUE,
+    BranchWidth => BRANCH_WIDTH_MAX_VALUE,
+    AddressSizeHint => ADDRESS_SIZE_HINT_MAX_VALUE,
+    OperandSizeHint => OPERAND_SIZE_HINT_MAX_VALUE,
+    FormatterStyle => FORMATTER_STYLE_MAX_VALUE,
+    ZydisFormatterProperty => FORMATTER_PROP_MAX_VALUE,
+    NumericBase => NUMERIC_BASE_MAX_VALUE,
+    Signedness => SIGNEDNESS_MAX_VALUE,
+    FormatterFunction => FORMATTER_FUNC_MAX_VALUE,
+    Decorator => DECORATOR_MAX_VALUE,
+    InstructionSegment => INSTR_SEGMENT_MAX_VALUE,
+    Feature => FEATURE_MAX_VALUE,
+}
+
+impl_try_from_variants! {
+    EncodableEncoding { DEFAULT, LEGACY, _3DNOW, XOP, VEX, EVEX, MVEX }
+    Padding { DISABLED, AUTO }
+}
+
 impl Mnemonic {
     /// Returns the static string corresponding to this mnemonic.
     ///
+    /// This measures the length of the string on every call, prefer
+    /// [`Mnemonic::short_string`] in hot paths.
+    ///
     /// # Examples
     /// ```
     /// use zydis::Mnemonic;
@@ -56,6 +262,19 @@ impl Mnemonic {
         unsafe { check_string!(ffi::ZydisMnemonicGetString(self)) }
     }
 
+    /// Returns the static string corresponding to this mnemonic, without
+    /// measuring its length.
+    ///
+    /// # Examples
+    /// ```
+    /// use zydis::Mnemonic;
+    /// let str = Mnemonic::CMOVP.short_string().unwrap();
+    /// assert_eq!("cmovp", str.as_str());
+    /// ```
+    pub fn short_string(self) -> Option<ShortStr> {
+        unsafe { ShortStr::from_ptr(ffi::ZydisMnemonicGetShortString(self)) }
+    }
+
     #[doc(hidden)]
     #[deprecated(since = "4.0.0", note = "use `static_string()` instead")]
     pub fn get_string(self) -> Option<&'static str> {
@@ -77,7 +296,7 @@ impl Mnemonic {
 
 impl fmt::Display for Mnemonic {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(self.static_string().ok_or(fmt::Error)?)
+        f.write_str(&self.short_string().ok_or(fmt::Error)?)
     }
 }
 
@@ -122,6 +341,9 @@ impl Register {
 
     /// Returns the textual representation of this register.
     ///
+    /// This measures the length of the string on every call, prefer
+    /// [`Register::short_string`] in hot paths.
+    ///
     /// # Examples
     /// ```
     /// use zydis::Register;
@@ -133,6 +355,20 @@ impl Register {
         unsafe { check_string!(ffi::ZydisRegisterGetString(self)) }
     }
 
+    /// Returns the textual representation of this register, without
+    /// measuring its length.
+    ///
+    /// # Examples
+    /// ```
+    /// use zydis::Register;
+    ///
+    /// let str = Register::EAX.short_string().unwrap();
+    /// assert_eq!("eax", str.as_str());
+    /// ```
+    pub fn short_string(self) -> Option<ShortStr> {
+        unsafe { ShortStr::from_ptr(ffi::ZydisRegisterGetStringWrapped(self)) }
+    }
+
     #[doc(hidden)]
     #[deprecated(since = "4.0.0", note = "use `static_string()` instead")]
     pub fn get_string(self) -> Option<&'static str> {
@@ -176,11 +412,39 @@ impl Register {
     pub fn get_largest_enclosing(self, mode: MachineMode) -> Register {
         self.largest_enclosing(mode)
     }
+
+    /// Checks whether this is an `AMX` tile register (`tmm0`-`tmm7`).
+    ///
+    /// # Examples
+    /// ```
+    /// use zydis::{MachineMode, Register};
+    ///
+    /// assert!(Register::TMM3.is_tmm());
+    /// assert!(!Register::ZMM3.is_tmm());
+    /// assert_eq!(8192, Register::TMM3.width(MachineMode::LONG_64));
+    /// ```
+    #[inline]
+    pub fn is_tmm(self) -> bool {
+        self.class() == RegisterClass::TMM
+    }
+
+    /// Returns an iterator over all registers, excluding [`Register::NONE`].
+    ///
+    /// # Examples
+    /// ```
+    /// use zydis::{Register, RegisterClass};
+    ///
+    /// assert_eq!(8, Register::all().filter(|reg| reg.is_tmm()).count());
+    /// assert!(Register::all().any(|reg| reg.class() == RegisterClass::GPR64));
+    /// ```
+    pub fn all() -> impl Iterator<Item = Register> {
+        (1..=REGISTER_MAX_VALUE as u32).filter_map(|value| Register::try_from(value).ok())
+    }
 }
 
 impl fmt::Display for Register {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(self.static_string().ok_or(fmt::Error)?)
+        f.write_str(&self.short_string().ok_or(fmt::Error)?)
     }
 }
 
@@ -207,6 +471,42 @@ impl RegisterClass {
     pub fn get_width(self, mode: MachineMode) -> RegisterWidth {
         self.width(mode)
     }
+
+    /// Returns an iterator over all registers of this register-class.
+    ///
+    /// # Examples
+    /// ```
+    /// use zydis::{Register, RegisterClass};
+    ///
+    /// let tiles: Vec<_> = RegisterClass::TMM.registers().collect();
+    /// assert_eq!(tiles.first(), Some(&Register::TMM0));
+    /// assert_eq!(tiles.last(), Some(&Register::TMM7));
+    /// ```
+    pub fn registers(self) -> impl Iterator<Item = Register> {
+        Register::all().filter(move |reg| reg.class() == self)
+    }
+}
+
+impl ISASet {
+    /// Checks whether this is one of the `AMX` ISA sets.
+    #[inline]
+    pub fn is_amx(self) -> bool {
+        matches!(
+            self,
+            ISASet::AMX_BF16 | ISASet::AMX_FP16 | ISASet::AMX_INT8 | ISASet::AMX_TILE
+        )
+    }
+}
+
+impl ISAExt {
+    /// Checks whether this is one of the `AMX` ISA extensions.
+    #[inline]
+    pub fn is_amx(self) -> bool {
+        matches!(
+            self,
+            ISAExt::AMX_BF16 | ISAExt::AMX_FP16 | ISAExt::AMX_INT8 | ISAExt::AMX_TILE
+        )
+    }
 }
 
 /// The type of a formatter token.
@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum InstructionCategory {
     INVALID = 0,
     ADOX_ADCX = 1,
@@ -131,6 +132,7 @@ pub const CATEGORY_MAX_VALUE: usize = 114;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum ISASet {
     INVALID = 0,
     ADOX_ADCX = 1,
@@ -338,6 +340,7 @@ pub const ISA_SET_MAX_VALUE: usize = 197;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum ISAExt {
     INVALID = 0,
     ADOX_ADCX = 1,
@@ -450,6 +453,7 @@ pub const ISA_EXT_MAX_VALUE: usize = 102;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum Mnemonic {
     INVALID = 0,
     AAA = 1,
@@ -2252,6 +2256,7 @@ pub const MNEMONIC_MAX_VALUE: usize = 1792;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum MachineMode {
     /// 64 bit mode.
     LONG_64 = 0,
@@ -2273,6 +2278,7 @@ pub const MACHINE_MODE_MAX_VALUE: usize = 5;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum StackWidth {
     _16 = 0,
     _32 = 1,
@@ -2285,6 +2291,7 @@ pub const STACK_WIDTH_MAX_VALUE: usize = 2;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum ElementType {
     INVALID = 0,
     /// A struct type.
@@ -2315,6 +2322,7 @@ pub const ELEMENT_TYPE_MAX_VALUE: usize = 10;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum OperandType {
     /// The operand is not used.
     UNUSED = 0,
@@ -2334,6 +2342,7 @@ pub const OPERAND_TYPE_MAX_VALUE: usize = 4;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum OperandEncoding {
     NONE = 0,
     MODRM_REG = 1,
@@ -2378,6 +2387,7 @@ pub const OPERAND_ENCODING_MAX_VALUE: usize = 34;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum OperandVisibility {
     INVALID = 0,
     /// The operand is explicitly encoded in the instruction.
@@ -2394,6 +2404,7 @@ pub const OPERAND_VISIBILITY_MAX_VALUE: usize = 3;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum InstructionEncoding {
     /// The instruction uses the legacy encoding.
     LEGACY = 0,
@@ -2415,6 +2426,7 @@ pub const INSTRUCTION_ENCODING_MAX_VALUE: usize = 5;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum OpcodeMap {
     DEFAULT = 0,
     _0F = 1,
@@ -2436,6 +2448,7 @@ pub const OPCODE_MAP_MAX_VALUE: usize = 11;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum Register {
     NONE = 0,
     AL = 1,
@@ -2711,6 +2724,7 @@ pub const REGISTER_MAX_VALUE: usize = 265;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum RegisterKind {
     INVALID = 0,
     GPR = 1,
@@ -2732,6 +2746,7 @@ pub const REGKIND_MAX_VALUE: usize = 11;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum RegisterClass {
     INVALID = 0,
     /// 8-bit general-purpose registers.
@@ -2780,6 +2795,7 @@ pub const REGCLASS_MAX_VALUE: usize = 19;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum MemoryOperandType {
     INVALID = 0,
     /// Normal memory operand.
@@ -2798,6 +2814,7 @@ pub const MEMOP_TYPE_MAX_VALUE: usize = 4;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum BranchType {
     /// The instruction is not a branch instruction.
     NONE = 0,
@@ -2815,6 +2832,7 @@ pub const BRANCH_TYPE_MAX_VALUE: usize = 3;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum ExceptionClass {
     NONE = 0,
     SSE1 = 1,
@@ -2870,6 +2888,7 @@ pub const EXCEPTION_CLASS_MAX_VALUE: usize = 45;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum MaskMode {
     INVALID = 0,
     /// Masking is disabled for the current instruction (`K0` register is used).
@@ -2890,6 +2909,7 @@ pub const MASK_MODE_MAX_VALUE: usize = 5;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum BroadcastMode {
     INVALID = 0,
     _1_TO_2 = 1,
@@ -2912,6 +2932,7 @@ pub const BROADCAST_MODE_MAX_VALUE: usize = 12;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum RoundingMode {
     INVALID = 0,
     /// Round to nearest.
@@ -2930,6 +2951,7 @@ pub const ROUNDING_MODE_MAX_VALUE: usize = 4;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum SwizzleMode {
     INVALID = 0,
     DCBA = 1,
@@ -2948,6 +2970,7 @@ pub const SWIZZLE_MODE_MAX_VALUE: usize = 8;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum ConversionMode {
     INVALID = 0,
     FLOAT16 = 1,
@@ -2963,6 +2986,7 @@ pub const CONVERSION_MODE_MAX_VALUE: usize = 5;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum PrefixType {
     /// The prefix is ignored by the instruction.
     IGNORED = 0,
@@ -2978,6 +3002,7 @@ pub const PREFIX_TYPE_MAX_VALUE: usize = 2;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum DecoderMode {
     /// Enables minimal instruction decoding without semantic analysis.
     MINIMAL = 0,
@@ -3009,6 +3034,7 @@ pub const DECODER_MODE_MAX_VALUE: usize = 10;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum EncodableEncoding {
     DEFAULT = 0,
     LEGACY = 1,
@@ -3025,6 +3051,7 @@ pub const ENCODABLE_ENCODING_MAX_VALUE: usize = 63;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum BranchWidth {
     NONE = 0,
     _8 = 1,
@@ -3039,6 +3066,7 @@ pub const BRANCH_WIDTH_MAX_VALUE: usize = 4;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum AddressSizeHint {
     NONE = 0,
     _16 = 1,
@@ -3052,6 +3080,7 @@ pub const ADDRESS_SIZE_HINT_MAX_VALUE: usize = 3;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum OperandSizeHint {
     NONE = 0,
     _8 = 1,
@@ -3066,6 +3095,7 @@ pub const OPERAND_SIZE_HINT_MAX_VALUE: usize = 4;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum FormatterStyle {
     /// Generates `AT&T`-style disassembly.
     ATT = 0,
@@ -3082,6 +3112,7 @@ pub const FORMATTER_STYLE_MAX_VALUE: usize = 2;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum ZydisFormatterProperty {
     /// Controls the printing of effective operand-size suffixes (`AT&T`) or operand-sizes of memory operands (`INTEL`).
     FORCE_SIZE = 0,
@@ -3147,6 +3178,7 @@ pub const FORMATTER_PROP_MAX_VALUE: usize = 27;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum NumericBase {
     /// Decimal system.
     DEC = 0,
@@ -3160,6 +3192,7 @@ pub const NUMERIC_BASE_MAX_VALUE: usize = 1;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum Signedness {
     /// Automatically choose the most suitable mode based on the operands ZydisDecodedOperand.imm.is_signed` attribute.
     AUTO = 0,
@@ -3175,6 +3208,7 @@ pub const SIGNEDNESS_MAX_VALUE: usize = 2;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum Padding {
     /// Disables padding.
     DISABLED = 0,
@@ -3186,6 +3220,7 @@ pub enum Padding {
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum FormatterFunction {
     /// This function is invoked before the formatter formats an instruction.
     PRE_INSTRUCTION = 0,
@@ -3233,6 +3268,7 @@ pub const FORMATTER_FUNC_MAX_VALUE: usize = 18;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum Decorator {
     INVALID = 0,
     /// The embedded-mask decorator.
@@ -3257,6 +3293,7 @@ pub const DECORATOR_MAX_VALUE: usize = 7;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum InstructionSegment {
     NONE = 0,
     /// The legacy prefixes (including ignored `REX` prefixes).
@@ -3289,6 +3326,7 @@ pub const INSTR_SEGMENT_MAX_VALUE: usize = 11;
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
+#[non_exhaustive]
 pub enum Feature {
     DECODER = 0,
     ENCODER = 1,
@@ -0,0 +1,96 @@
+//! CPU exceptions instructions may raise, derived from their exception class.
+
+use crate::*;
+use CpuException::*;
+
+/// A CPU exception (fault) an instruction may raise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CpuException {
+    /// `#UD`, invalid opcode.
+    UD,
+    /// `#NM`, device not available.
+    NM,
+    /// `#SS`, stack-segment fault.
+    SS,
+    /// `#GP`, general protection fault.
+    GP,
+    /// `#PF`, page fault.
+    PF,
+    /// `#AC`, alignment check.
+    AC,
+    /// `#XM`, SIMD floating-point exception.
+    XM,
+}
+
+const UD_NM: &[CpuException] = &[UD, NM];
+const MEM: &[CpuException] = &[UD, NM, SS, GP, PF];
+const MEM_XM: &[CpuException] = &[UD, NM, SS, GP, PF, XM];
+const MEM_AC: &[CpuException] = &[UD, NM, SS, GP, PF, AC];
+const MEM_AC_XM: &[CpuException] = &[UD, NM, SS, GP, PF, AC, XM];
+
+impl ExceptionClass {
+    /// Returns the exceptions instructions of this class may raise, following
+    /// the exception type tables in the Intel SDM.
+    ///
+    /// Memory related exceptions are listed even if the specific instruction
+    /// has no memory operand. [`ExceptionClass::NONE`] carries no information
+    /// and returns an empty slice.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// assert!(ExceptionClass::SSE2.possible_faults().contains(&CpuException::XM));
+    /// assert!(!ExceptionClass::E2NF.possible_faults().contains(&CpuException::XM));
+    /// ```
+    pub fn possible_faults(self) -> &'static [CpuException] {
+        use ExceptionClass as C;
+
+        match self {
+            C::NONE => &[],
+            C::SSE1 | C::SSE4 | C::AVX1 | C::AVX4 | C::AVX12 => MEM,
+            C::SSE2 | C::AVX2 => MEM_XM,
+            C::SSE3 | C::AVX3 | C::AVX11 => MEM_AC_XM,
+            C::SSE5 | C::AVX5 | C::AVX6 => MEM_AC,
+            C::SSE7 | C::AVX7 | C::AVX8 => UD_NM,
+            C::E1 | C::E1NF | C::E2NF | C::E4NF | C::E12 => MEM,
+            C::E2 | C::E4 => MEM_XM,
+            C::E3 | C::E5 | C::E10 | C::E11 => MEM_AC_XM,
+            C::E3NF | C::E5NF | C::E6 | C::E6NF | C::E9NF | C::E10NF | C::E11NF => MEM_AC,
+            C::E7NM | C::E7NM128 => UD_NM,
+            C::E12NP => &[UD, NM, SS, GP],
+            C::K20 => &[UD],
+            C::K21 => &[UD, SS, GP, PF, AC],
+            C::AMXE1 | C::AMXE2 | C::AMXE3 => MEM,
+            C::AMXE4 | C::AMXE5 | C::AMXE6 => UD_NM,
+        }
+    }
+}
+
+impl<O: Operands> Instruction<O> {
+    /// Checks whether the instruction may raise `exception`, according to its
+    /// exception class.
+    ///
+    /// Instructions without exception class (most general purpose ones)
+    /// always return `false`, see [`ExceptionClass::possible_faults`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // addps xmm0, xmmword ptr [rax]
+    /// static CODE: &[u8] = &[0x0F, 0x58, 0x00];
+    ///
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<NoOperands>(CODE)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert!(insn.may_fault_with(CpuException::XM));
+    /// assert!(insn.may_fault_with(CpuException::PF));
+    /// ```
+    #[inline]
+    pub fn may_fault_with(&self, exception: CpuException) -> bool {
+        self.meta
+            .exception_class
+            .possible_faults()
+            .contains(&exception)
+    }
+}
@@ -1,16 +1,134 @@
 use super::*;
+use core::{mem, ptr};
 
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
 
+/// The raw state of a [`crate::Decoder`].
+///
+/// Serializing captures the full decoder configuration, which can be
+/// restored via `crate::Decoder::try_from`.
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[repr(C)]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Decoder {
     machine_mode: MachineMode,
     stack_width: StackWidth,
     decoder_mode: u32,
 }
 
+/// All decoder modes, in order of their values.
+pub(crate) const DECODER_MODES: [DecoderMode; DECODER_MODE_MAX_VALUE + 1] = [
+    DecoderMode::MINIMAL,
+    DecoderMode::AMD_BRANCHES,
+    DecoderMode::KNC,
+    DecoderMode::MPX,
+    DecoderMode::CET,
+    DecoderMode::LZCNT,
+    DecoderMode::TZCNT,
+    DecoderMode::WBNOINVD,
+    DecoderMode::CLDEMOTE,
+    DecoderMode::IPREFETCH,
+    DecoderMode::UD0_COMPAT,
+];
+
+/// Decoder modes enabled by `ZydisDecoderInit`.
+const DEFAULT_DECODER_MODES: u32 = {
+    let modes = (1 << DecoderMode::MPX as u32)
+        | (1 << DecoderMode::CET as u32)
+        | (1 << DecoderMode::LZCNT as u32)
+        | (1 << DecoderMode::TZCNT as u32)
+        | (1 << DecoderMode::CLDEMOTE as u32)
+        | (1 << DecoderMode::IPREFETCH as u32);
+
+    // The C library is built in minimal mode without `full-decoder`.
+    if cfg!(feature = "full-decoder") {
+        modes
+    } else {
+        modes | (1 << DecoderMode::MINIMAL as u32)
+    }
+};
+
+impl Decoder {
+    /// Initializes a decoder the way `ZydisDecoderInit` does, but in Rust so
+    /// that it can be used in `const` contexts.
+    ///
+    /// Returns `None` for invalid machine mode and stack width combinations.
+    pub(crate) const fn init(machine_mode: MachineMode, stack_width: StackWidth) -> Option<Self> {
+        let valid = match machine_mode {
+            MachineMode::LONG_64 => matches!(stack_width, StackWidth::_64),
+            _ => matches!(stack_width, StackWidth::_16 | StackWidth::_32),
+        };
+
+        if !valid {
+            return None;
+        }
+
+        Some(Self::init_unchecked(machine_mode, stack_width))
+    }
+
+    /// Like [`Decoder::init`], for combinations known to be valid.
+    pub(crate) const fn init_unchecked(machine_mode: MachineMode, stack_width: StackWidth) -> Self {
+        Self {
+            machine_mode,
+            stack_width,
+            decoder_mode: DEFAULT_DECODER_MODES,
+        }
+    }
+
+    /// Returns a copy with the given decoder mode enabled or disabled, like
+    /// `ZydisDecoderEnableMode`.
+    pub(crate) const fn with_mode(mut self, mode: DecoderMode, value: bool) -> Self {
+        if value {
+            self.decoder_mode |= 1 << mode as u32;
+        } else {
+            self.decoder_mode &= !(1 << mode as u32);
+        }
+        self
+    }
+
+    /// Returns the machine mode.
+    #[inline]
+    pub fn machine_mode(&self) -> MachineMode {
+        self.machine_mode
+    }
+
+    /// Returns the stack width.
+    #[inline]
+    pub fn stack_width(&self) -> StackWidth {
+        self.stack_width
+    }
+
+    /// Checks whether the given decoder mode is enabled.
+    #[inline]
+    pub fn is_mode_enabled(&self, mode: DecoderMode) -> bool {
+        self.decoder_mode & (1 << mode as u32) != 0
+    }
+
+    /// Returns an iterator over all enabled decoder modes.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let mut decoder = Decoder::new64();
+    /// decoder.enable_mode(DecoderMode::KNC, true).unwrap();
+    ///
+    /// assert!(decoder.raw().enabled_modes().any(|mode| mode == DecoderMode::KNC));
+    /// assert!(decoder.raw().enabled_modes().all(|mode| mode != DecoderMode::MINIMAL));
+    /// ```
+    pub fn enabled_modes(&self) -> impl Iterator<Item = DecoderMode> + '_ {
+        DECODER_MODES
+            .into_iter()
+            .filter(|&mode| self.is_mode_enabled(mode))
+    }
+
+    /// Checks whether any of the mode bits set is no known decoder mode.
+    #[inline]
+    pub(crate) fn has_unknown_modes(&self) -> bool {
+        self.decoder_mode >> (DECODER_MODE_MAX_VALUE + 1) != 0
+    }
+}
+
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
@@ -49,6 +167,101 @@ pub struct DecodedOperand {
     pub kind: DecodedOperandKind,
 }
 
+/// Checks that the C enum at `field` holds a variant known to these bindings,
+/// without reading it as `T`.
+///
+/// # Safety
+///
+/// `field` must be valid for reading a `u32`.
+pub(crate) unsafe fn check_variant<T>(field: *const T) -> core::result::Result<(), UnknownVariant>
+where
+    T: TryFrom<u32, Error = UnknownVariant>,
+{
+    debug_assert_eq!(mem::size_of::<T>(), mem::size_of::<u32>());
+    T::try_from(field.cast::<u32>().read()).map(drop)
+}
+
+impl DecodedOperand {
+    /// Checks that the enum fields Zydis wrote to `operand` hold variants
+    /// known to these bindings, before it's treated as a `DecodedOperand`.
+    ///
+    /// # Safety
+    ///
+    /// `operand` must point to an operand written by Zydis.
+    #[cfg(feature = "full-decoder")]
+    pub(crate) unsafe fn check_variants(
+        operand: *const Self,
+    ) -> core::result::Result<(), UnknownVariant> {
+        check_variant(ptr::addr_of!((*operand).visibility))?;
+        check_variant(ptr::addr_of!((*operand).encoding))?;
+        check_variant(ptr::addr_of!((*operand).element_type))?;
+
+        // `DecodedOperandKind` is laid out as the C operand type followed by
+        // the union of the variants, which starts at the enum's alignment.
+        let kind = ptr::addr_of!((*operand).kind);
+        let payload = kind.cast::<u8>().add(mem::align_of::<DecodedOperandKind>());
+        match OperandType::try_from(kind.cast::<u32>().read())? {
+            OperandType::REGISTER => check_variant(payload.cast::<Register>()),
+            OperandType::MEMORY => {
+                let mem = payload.cast::<MemoryInfo>();
+                check_variant(ptr::addr_of!((*mem).ty))?;
+                check_variant(ptr::addr_of!((*mem).segment))?;
+                check_variant(ptr::addr_of!((*mem).base))?;
+                check_variant(ptr::addr_of!((*mem).index))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Intel-style operand formatting without instruction context.
+///
+/// The output resembles the one of the Zydis Intel formatter, e.g.
+/// `dword ptr [rax+0x10]`. Without access to the instruction, only segments
+/// other than `ds` and `ss` are printed. Use `DecodedOperand::format_with` for
+/// exact output.
+impl fmt::Display for DecodedOperand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            DecodedOperandKind::Unused => Ok(()),
+            DecodedOperandKind::Reg(reg) => reg.fmt(f),
+            DecodedOperandKind::Ptr(ptr) => write!(f, "0x{:X}:0x{:X}", ptr.segment, ptr.offset),
+            DecodedOperandKind::Imm(imm) if imm.is_negative() => {
+                write!(f, "-0x{:X}", imm.value_i64().unsigned_abs())
+            }
+            DecodedOperandKind::Imm(imm) => write!(f, "0x{:X}", imm.value_u64()),
+            DecodedOperandKind::Mem(mem) => {
+                if matches!(mem.ty, MemoryOperandType::MEM | MemoryOperandType::VSIB) {
+                    let size = match self.size {
+                        8 => "byte",
+                        16 => "word",
+                        32 => "dword",
+                        48 => "fword",
+                        64 => "qword",
+                        80 => "tbyte",
+                        128 => "xmmword",
+                        256 => "ymmword",
+                        512 => "zmmword",
+                        _ => "",
+                    };
+                    if !size.is_empty() {
+                        write!(f, "{size} ptr ")?;
+                    }
+                }
+
+                let mut expr = mem.effective_address_expr();
+                if !matches!(
+                    mem.segment,
+                    Register::ES | Register::CS | Register::FS | Register::GS
+                ) {
+                    expr.segment = None;
+                }
+                expr.fmt(f)
+            }
+        }
+    }
+}
+
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
@@ -87,13 +300,48 @@ pub struct ImmediateInfo {
     pub is_signed: bool,
     /// Signals, if the immediate is relative.
     pub is_relative: bool,
-    /// This is actually an i64 if `is_signed` is true.
-    // TODO: Should we use an union?
+    /// This is actually an i64 if `is_signed` is true, prefer
+    /// [`ImmediateInfo::value_i64`] and [`ImmediateInfo::value_u64`].
     // C definition:
     //   union ZydisDecodedOperandImmValue_{ ZyanU64 u; ZyanI64 s; } value;
     pub value: u64,
 }
 
+/// Implements the typed accessors for the immediate value union.
+macro_rules! impl_imm_value {
+    ($ty:ident) => {
+        impl $ty {
+            /// Returns the value as an unsigned integer.
+            ///
+            /// Signed values are sign extended to 64 bits, so negative values
+            /// are returned in two's complement.
+            #[inline]
+            pub fn value_u64(&self) -> u64 {
+                self.value
+            }
+
+            /// Returns the value as a signed integer.
+            ///
+            /// Unsigned values are reinterpreted, so values above
+            /// [`i64::MAX`] turn negative. Check `is_signed` where that
+            /// matters.
+            #[inline]
+            pub fn value_i64(&self) -> i64 {
+                self.value as i64
+            }
+
+            /// Checks whether this is a signed immediate with a negative value.
+            #[inline]
+            pub fn is_negative(&self) -> bool {
+                self.is_signed && self.value_i64() < 0
+            }
+        }
+    };
+}
+
+impl_imm_value!(ImmediateInfo);
+impl_imm_value!(RawImmediateInfo);
+
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
@@ -110,6 +358,119 @@ pub struct AccessedFlags<FlagType> {
     pub undefined: FlagType,
 }
 
+/// How an instruction accesses a single flag, see
+/// [`AccessedFlags::action_of`].
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[allow(non_camel_case_types)]
+pub enum FlagAction {
+    /// The flag isn't accessed.
+    NONE,
+    /// The flag is read.
+    TESTED,
+    /// The flag is read and written.
+    TESTED_MODIFIED,
+    /// The flag is written.
+    MODIFIED,
+    /// The flag is set to 0.
+    SET_0,
+    /// The flag is set to 1.
+    SET_1,
+    /// The flag is left in an undefined state.
+    UNDEFINED,
+}
+
+impl<F: bitflags::Flags + Copy> AccessedFlags<F> {
+    /// Returns the flags written in any way, including undefined ones.
+    #[inline]
+    pub fn all_written(&self) -> F {
+        self.modified
+            .union(self.set_0)
+            .union(self.set_1)
+            .union(self.undefined)
+    }
+
+    /// Returns the flags read or written in any way.
+    #[inline]
+    pub fn all_accessed(&self) -> F {
+        self.tested.union(self.all_written())
+    }
+
+    /// Checks whether any flag is written.
+    #[inline]
+    pub fn any_written(&self) -> bool {
+        !self.all_written().is_empty()
+    }
+
+    /// Combines the accesses of both, e.g. to summarize a sequence of
+    /// instructions.
+    #[inline]
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            tested: self.tested.union(other.tested),
+            modified: self.modified.union(other.modified),
+            set_0: self.set_0.union(other.set_0),
+            set_1: self.set_1.union(other.set_1),
+            undefined: self.undefined.union(other.undefined),
+        }
+    }
+
+    /// Keeps only the accesses common to both.
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            tested: self.tested.intersection(other.tested),
+            modified: self.modified.intersection(other.modified),
+            set_0: self.set_0.intersection(other.set_0),
+            set_1: self.set_1.intersection(other.set_1),
+            undefined: self.undefined.intersection(other.undefined),
+        }
+    }
+
+    /// Returns how `flag` is accessed.
+    ///
+    /// Flags that are both read and written are reported as
+    /// [`FlagAction::TESTED_MODIFIED`], regardless of the kind of write.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // adc eax, ecx
+    /// static CODE: &[u8] = &[0x11, 0xC8];
+    ///
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<NoOperands>(CODE)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let flags = insn.cpu_flags;
+    /// assert_eq!(flags.action_of(CpuFlag::CF), ffi::FlagAction::TESTED_MODIFIED);
+    /// assert_eq!(flags.action_of(CpuFlag::ZF), ffi::FlagAction::MODIFIED);
+    /// assert_eq!(flags.action_of(CpuFlag::DF), ffi::FlagAction::NONE);
+    /// ```
+    pub fn action_of(&self, flag: F) -> FlagAction {
+        let written = self.all_written().contains(flag);
+        if self.tested.contains(flag) {
+            return if written {
+                FlagAction::TESTED_MODIFIED
+            } else {
+                FlagAction::TESTED
+            };
+        }
+
+        if self.set_0.contains(flag) {
+            FlagAction::SET_0
+        } else if self.set_1.contains(flag) {
+            FlagAction::SET_1
+        } else if self.undefined.contains(flag) {
+            FlagAction::UNDEFINED
+        } else if written {
+            FlagAction::MODIFIED
+        } else {
+            FlagAction::NONE
+        }
+    }
+}
+
 // NOTE: can't implement `deserialize` due to the static refs (no easy way to
 // recover)
 #[cfg_attr(feature = "serialization", derive(Serialize))]
@@ -157,6 +518,43 @@ pub struct DecodedInstruction {
 }
 
 impl DecodedInstruction {
+    /// Checks that the enum fields Zydis wrote to `insn` hold variants known
+    /// to these bindings, before it's treated as a `DecodedInstruction`.
+    ///
+    /// # Safety
+    ///
+    /// `insn` must point to an instruction written by Zydis.
+    pub(crate) unsafe fn check_variants(
+        insn: *const Self,
+    ) -> core::result::Result<(), UnknownVariant> {
+        check_variant(ptr::addr_of!((*insn).machine_mode))?;
+        check_variant(ptr::addr_of!((*insn).mnemonic))?;
+        check_variant(ptr::addr_of!((*insn).encoding))?;
+        check_variant(ptr::addr_of!((*insn).opcode_map))?;
+
+        let avx = ptr::addr_of!((*insn).avx);
+        check_variant(ptr::addr_of!((*avx).mask_mode))?;
+        check_variant(ptr::addr_of!((*avx).mask_reg))?;
+        check_variant(ptr::addr_of!((*avx).broadcast_mode))?;
+        check_variant(ptr::addr_of!((*avx).rounding_mode))?;
+        check_variant(ptr::addr_of!((*avx).swizzle_mode))?;
+        check_variant(ptr::addr_of!((*avx).conversion_mode))?;
+
+        let meta = ptr::addr_of!((*insn).meta);
+        check_variant(ptr::addr_of!((*meta).category))?;
+        check_variant(ptr::addr_of!((*meta).isa_set))?;
+        check_variant(ptr::addr_of!((*meta).isa_ext))?;
+        check_variant(ptr::addr_of!((*meta).branch_type))?;
+        check_variant(ptr::addr_of!((*meta).exception_class))?;
+
+        let raw = ptr::addr_of!((*insn).raw);
+        for index in 0..MAX_INSTRUCTION_LENGTH {
+            check_variant(ptr::addr_of!((*raw).prefixes[index].ty))?;
+        }
+        // The variants of `RawInfoKindSpecific` follow the encodings.
+        check_variant(ptr::addr_of!((*raw).kind_specific).cast::<InstructionEncoding>())
+    }
+
     /// Calculates the absolute address for the given instruction operand,
     /// using the given `address` as the address for this instruction.
     #[inline]
@@ -170,6 +568,11 @@ impl DecodedInstruction {
 
     /// Behaves like `calc_absolute_address`, but takes runtime-known values of
     /// registers passed in the `context` into account.
+    ///
+    /// The context is consulted for the base and index registers of memory
+    /// operands and for register operands of branches (e.g. `jmp rax`).
+    /// Relative immediates and `rip`-relative memory operands only depend on
+    /// `address`. Segment bases are never added.
     #[inline]
     pub fn calc_absolute_address_ex(
         &self,
@@ -183,6 +586,78 @@ impl DecodedInstruction {
             Ok(addr)
         }
     }
+
+    /// Checks whether this is an `AMX` instruction, e.g. `tileloadd` or
+    /// `tdpbssd`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // tdpbssd tmm1, tmm2, tmm3
+    /// static TDPBSSD: &[u8] = &[0xC4, 0xE2, 0x63, 0x5E, 0xCA];
+    /// // vaddps xmm1, xmm2, xmm3
+    /// static VADDPS: &[u8] = &[0xC5, 0xE8, 0x58, 0xCB];
+    ///
+    /// let decoder = Decoder::new64();
+    /// let insn = decoder.decode_first::<NoOperands>(TDPBSSD).unwrap().unwrap();
+    /// assert!(insn.is_amx());
+    /// let insn = decoder.decode_first::<NoOperands>(VADDPS).unwrap().unwrap();
+    /// assert!(!insn.is_amx());
+    /// ```
+    #[inline]
+    pub fn is_amx(&self) -> bool {
+        self.meta.category == InstructionCategory::AMX_TILE
+            || self.meta.isa_set.is_amx()
+            || self.meta.isa_ext.is_amx()
+    }
+
+    /// Returns the `REX` prefix info, if the instruction has one.
+    #[inline]
+    pub fn rex_info(&self) -> Option<&RawInfoRex> {
+        if self.attributes.contains(InstructionAttributes::HAS_REX) {
+            self.raw.rex()
+        } else {
+            None
+        }
+    }
+
+    /// Shorthand for `self.raw.xop()`.
+    #[inline]
+    pub fn xop_info(&self) -> Option<&RawInfoXop> {
+        self.raw.xop()
+    }
+
+    /// Shorthand for `self.raw.vex()`.
+    #[inline]
+    pub fn vex_info(&self) -> Option<&RawInfoVex> {
+        self.raw.vex()
+    }
+
+    /// Shorthand for `self.raw.evex()`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // vaddps zmm0, zmm1, zmm2
+    /// static CODE: &[u8] = &[0x62, 0xF1, 0x74, 0x48, 0x58, 0xC2];
+    ///
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<NoOperands>(CODE)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert!(insn.vex_info().is_none());
+    /// assert_eq!(insn.evex_info().unwrap().W, 0);
+    /// ```
+    #[inline]
+    pub fn evex_info(&self) -> Option<&RawInfoEvex> {
+        self.raw.evex()
+    }
+
+    /// Shorthand for `self.raw.mvex()`.
+    #[inline]
+    pub fn mvex_info(&self) -> Option<&RawInfoMvex> {
+        self.raw.mvex()
+    }
 }
 
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
@@ -481,6 +956,56 @@ pub struct RawInfo {
     pub imm: [RawImmediateInfo; 2],
 }
 
+impl RawInfo {
+    /// Returns the `REX` info of legacy encoded instructions.
+    ///
+    /// This is also returned if no `REX` prefix is present, see
+    /// [`DecodedInstruction::rex_info`] for a checked variant.
+    #[inline]
+    pub fn rex(&self) -> Option<&RawInfoRex> {
+        match &self.kind_specific {
+            RawInfoKindSpecific::Legacy(rex) => Some(rex),
+            _ => None,
+        }
+    }
+
+    /// Returns the `XOP` info of `XOP` encoded instructions.
+    #[inline]
+    pub fn xop(&self) -> Option<&RawInfoXop> {
+        match &self.kind_specific {
+            RawInfoKindSpecific::Xop(xop) => Some(xop),
+            _ => None,
+        }
+    }
+
+    /// Returns the `VEX` info of `VEX` encoded instructions.
+    #[inline]
+    pub fn vex(&self) -> Option<&RawInfoVex> {
+        match &self.kind_specific {
+            RawInfoKindSpecific::Vex(vex) => Some(vex),
+            _ => None,
+        }
+    }
+
+    /// Returns the `EVEX` info of `EVEX` encoded instructions.
+    #[inline]
+    pub fn evex(&self) -> Option<&RawInfoEvex> {
+        match &self.kind_specific {
+            RawInfoKindSpecific::Evex(evex) => Some(evex),
+            _ => None,
+        }
+    }
+
+    /// Returns the `MVEX` info of `MVEX` encoded instructions.
+    #[inline]
+    pub fn mvex(&self) -> Option<&RawInfoMvex> {
+        match &self.kind_specific {
+            RawInfoKindSpecific::Mvex(mvex) => Some(mvex),
+            _ => None,
+        }
+    }
+}
+
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 #[repr(C)]
@@ -492,8 +1017,8 @@ pub struct RawImmediateInfo {
     pub is_relative: bool,
     /// The immediate value.
     ///
-    /// This is an `i64` if `is_signed` is true.
-    // TODO: union?
+    /// This is an `i64` if `is_signed` is true, prefer
+    /// [`RawImmediateInfo::value_i64`] and [`RawImmediateInfo::value_u64`].
     // C definition:
     //   union ZydisDecodedInstructionRawImmValue_ { ZyanU64 u; ZyanI64 s; } value;
     pub value: u64,
@@ -579,7 +1104,7 @@ pub struct ContextDefinition(u8 /* dummy */);
 #[repr(C)]
 pub struct DecoderContext {
     /// A pointer to the internal instruction definition.
-    definition: *const ContextDefinition,
+    definition: DefinitionPtr,
     /// Contains the effective operand-size index.
     /// 0 = 16 bit, 1 = 32 bit, 2 = 64 bit
     eosz_index: u8,
@@ -599,6 +1124,17 @@ pub struct DecoderContext {
     cd8_scal: u8,
 }
 
+/// Pointer to an internal instruction definition.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[repr(transparent)]
+struct DefinitionPtr(*const ContextDefinition);
+
+// SAFETY: the pointer is either null or points into the static decoder
+// tables, which are never written.
+unsafe impl Send for DefinitionPtr {}
+// SAFETY: see above.
+unsafe impl Sync for DefinitionPtr {}
+
 extern "C" {
     pub fn ZydisDecoderInit(
         decoder: *mut Decoder,
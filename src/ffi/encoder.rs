@@ -1,5 +1,5 @@
 use crate::{ffi::*, status::Status};
-use core::ffi::c_void;
+use core::{ffi::c_void, ptr};
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
 
@@ -83,6 +83,43 @@ pub struct EncoderRequest {
     pub mvex: MvexFeatures,
 }
 
+impl EncoderRequest {
+    /// Checks that the enum fields Zydis wrote to `request` hold variants
+    /// known to these bindings, before it's treated as an `EncoderRequest`.
+    ///
+    /// # Safety
+    ///
+    /// `request` must point to a request written by Zydis.
+    pub(crate) unsafe fn check_variants(
+        request: *const Self,
+    ) -> core::result::Result<(), UnknownVariant> {
+        check_variant(ptr::addr_of!((*request).machine_mode))?;
+        check_variant(ptr::addr_of!((*request).allowed_encodings))?;
+        check_variant(ptr::addr_of!((*request).mnemonic))?;
+        check_variant(ptr::addr_of!((*request).branch_type))?;
+        check_variant(ptr::addr_of!((*request).branch_width))?;
+        check_variant(ptr::addr_of!((*request).address_size_hint))?;
+        check_variant(ptr::addr_of!((*request).operand_size_hint))?;
+
+        for index in 0..ENCODER_MAX_OPERANDS {
+            let operand = ptr::addr_of!((*request).operands[index]);
+            check_variant(ptr::addr_of!((*operand).ty))?;
+            check_variant(ptr::addr_of!((*operand).reg.value))?;
+            check_variant(ptr::addr_of!((*operand).mem.base))?;
+            check_variant(ptr::addr_of!((*operand).mem.index))?;
+        }
+
+        let evex = ptr::addr_of!((*request).evex);
+        check_variant(ptr::addr_of!((*evex).broadcast))?;
+        check_variant(ptr::addr_of!((*evex).rounding))?;
+        let mvex = ptr::addr_of!((*request).mvex);
+        check_variant(ptr::addr_of!((*mvex).broadcast))?;
+        check_variant(ptr::addr_of!((*mvex).conversion))?;
+        check_variant(ptr::addr_of!((*mvex).rounding))?;
+        check_variant(ptr::addr_of!((*mvex).swizzle))
+    }
+}
+
 extern "C" {
     pub fn ZydisEncoderEncodeInstructionAbsolute(
         request: *const EncoderRequest,
@@ -131,6 +131,159 @@ impl FormatterBuffer {
         unsafe { ZydisFormatterBufferAppend(self, token).into() }
     }
 
+    /// Appends a new token to this buffer and writes `args` as its value.
+    ///
+    /// This combines [`FormatterBuffer::append`],
+    /// [`FormatterBuffer::get_string`] and `write!`. Errors of the
+    /// underlying string, e.g. [`Status::InsufficientBufferSize`], are
+    /// returned as is. Errors of `Display` impls are reported as
+    /// [`Status::User`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// fn print_mnemonic(
+    ///     _formatter: &Formatter<()>,
+    ///     buffer: &mut ffi::FormatterBuffer,
+    ///     ctx: &mut ffi::FormatterContext,
+    ///     _user_data: Option<&mut ()>,
+    /// ) -> Result<()> {
+    ///     let mnemonic = unsafe { (*ctx.instruction).mnemonic };
+    ///     buffer.write_token(TOKEN_MNEMONIC, format_args!("{mnemonic}!"))
+    /// }
+    ///
+    /// let mut formatter = Formatter::intel();
+    /// formatter
+    ///     .set_print_mnemonic(Box::new(print_mnemonic))
+    ///     .unwrap();
+    ///
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(&[0xC3])
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let mut buffer = [0u8; 32];
+    /// let mut buffer = OutputBuffer::new(&mut buffer[..]);
+    /// formatter
+    ///     .format_ex(Some(0), &insn, &mut buffer, None)
+    ///     .unwrap();
+    /// assert_eq!(buffer.as_str().unwrap(), "ret!");
+    /// ```
+    #[inline]
+    pub fn write_token(&mut self, token: Token, args: fmt::Arguments<'_>) -> Result<()> {
+        self.write_token_with(token, args, OverflowPolicy::Error)
+    }
+
+    /// Like [`FormatterBuffer::write_token`], but with `policy` deciding
+    /// what happens if the text doesn't fit into the buffer.
+    ///
+    /// If there's no room for the token itself,
+    /// [`Status::InsufficientBufferSize`] is returned regardless of `policy`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// fn print_mnemonic(
+    ///     _formatter: &Formatter<()>,
+    ///     buffer: &mut ffi::FormatterBuffer,
+    ///     _ctx: &mut ffi::FormatterContext,
+    ///     _user_data: Option<&mut ()>,
+    /// ) -> Result<()> {
+    ///     buffer.write_token_with(
+    ///         TOKEN_MNEMONIC,
+    ///         format_args!("{}", "a".repeat(20)),
+    ///         ffi::OverflowPolicy::Truncate,
+    ///     )
+    /// }
+    ///
+    /// let mut formatter = Formatter::intel();
+    /// formatter
+    ///     .set_print_mnemonic(Box::new(print_mnemonic))
+    ///     .unwrap();
+    ///
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(&[0xC3])
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let mut buffer = [0u8; 8];
+    /// let mut buffer = OutputBuffer::new(&mut buffer[..]);
+    /// formatter
+    ///     .format_ex(Some(0), &insn, &mut buffer, None)
+    ///     .unwrap();
+    /// assert_eq!(buffer.as_str().unwrap(), "aaaa...");
+    /// ```
+    pub fn write_token_with(
+        &mut self,
+        token: Token,
+        args: fmt::Arguments<'_>,
+        policy: OverflowPolicy,
+    ) -> Result<()> {
+        self.append(token)?;
+        let string = self.get_string()?;
+
+        let remaining = string.remaining_capacity();
+        let limit = match policy {
+            OverflowPolicy::Truncate if formatted_len(args) > remaining => {
+                Some(remaining.saturating_sub(ELLIPSIS.len()))
+            }
+            _ => None,
+        };
+
+        let mut writer = TokenWriter {
+            string,
+            limit,
+            status: Ok(()),
+        };
+        if fmt::write(&mut writer, args).is_err() {
+            return writer.status.and(Err(Status::User));
+        }
+
+        if limit.is_some() {
+            let remaining = writer.string.remaining_capacity();
+            writer
+                .string
+                .append(&ELLIPSIS[..ELLIPSIS.len().min(remaining)])?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of bytes that can still be written to the text of
+    /// the most recently added token.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// fn print_mnemonic(
+    ///     _formatter: &Formatter<()>,
+    ///     buffer: &mut ffi::FormatterBuffer,
+    ///     _ctx: &mut ffi::FormatterContext,
+    ///     _user_data: Option<&mut ()>,
+    /// ) -> Result<()> {
+    ///     buffer.append(TOKEN_MNEMONIC)?;
+    ///     let remaining = buffer.remaining_capacity()?;
+    ///     buffer.get_string()?.append(&"x".repeat(remaining))
+    /// }
+    ///
+    /// let mut formatter = Formatter::intel();
+    /// formatter
+    ///     .set_print_mnemonic(Box::new(print_mnemonic))
+    ///     .unwrap();
+    ///
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(&[0xC3])
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let mut buffer = [0u8; 8];
+    /// let mut buffer = OutputBuffer::new(&mut buffer[..]);
+    /// formatter
+    ///     .format_ex(Some(0), &insn, &mut buffer, None)
+    ///     .unwrap();
+    /// assert_eq!(buffer.as_str().unwrap(), "xxxxxxx");
+    /// ```
+    #[inline]
+    pub fn remaining_capacity(&mut self) -> Result<usize> {
+        Ok(self.get_string()?.remaining_capacity())
+    }
+
     /// Returns a snapshot of the buffer-state.
     #[inline]
     pub fn remember(&self) -> Result<FormatterBufferState> {
@@ -148,6 +301,65 @@ impl FormatterBuffer {
     }
 }
 
+/// What [`FormatterBuffer::write_token_with`] does if the text doesn't fit.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum OverflowPolicy {
+    /// Fail with [`Status::InsufficientBufferSize`].
+    #[default]
+    Error,
+    /// Cut the text to fit and end it with `...`.
+    Truncate,
+}
+
+const ELLIPSIS: &str = "...";
+
+/// Returns the length of the formatted `args`, in bytes.
+fn formatted_len(args: fmt::Arguments<'_>) -> usize {
+    struct Counter(usize);
+
+    impl fmt::Write for Counter {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 += s.len();
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    let _ = fmt::write(&mut counter, args);
+    counter.0
+}
+
+/// Writes to a [`ZyanString`], remembering the status of failed appends.
+///
+/// With a `limit`, output exceeding it is dropped instead.
+struct TokenWriter<'a> {
+    string: &'a mut ZyanString,
+    limit: Option<usize>,
+    status: Result<()>,
+}
+
+impl fmt::Write for TokenWriter<'_> {
+    fn write_str(&mut self, mut s: &str) -> fmt::Result {
+        if let Some(limit) = &mut self.limit {
+            if s.len() > *limit {
+                let mut end = *limit;
+                while !s.is_char_boundary(end) {
+                    end -= 1;
+                }
+                s = &s[..end];
+                *limit = 0;
+            } else {
+                *limit -= s.len();
+            }
+        }
+
+        if !s.is_empty() {
+            self.status = self.string.append(s);
+        }
+        self.status.map_err(|_| fmt::Error)
+    }
+}
+
 /// Opaque type representing a `FormatterBuffer` state.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(transparent)]
@@ -204,6 +416,14 @@ pub struct Formatter {
     func_print_decorator: FormatterDecoratorFunc,
 }
 
+impl Formatter {
+    /// Returns the style the formatter was initialized with.
+    #[inline]
+    pub fn style(&self) -> FormatterStyle {
+        self.style
+    }
+}
+
 #[derive(Debug)]
 #[repr(C)]
 struct ZydisFormatterStringData {
@@ -212,6 +432,13 @@ struct ZydisFormatterStringData {
     buffer: [c_char; 11],
 }
 
+// SAFETY: Zydis only ever points `string` at `string_data`, which in turn
+// only references `buffer`. The data is owned by the struct and only written
+// through `&mut Formatter`.
+unsafe impl Send for ZydisFormatterStringData {}
+// SAFETY: see above, formatting only reads the string data.
+unsafe impl Sync for ZydisFormatterStringData {}
+
 #[derive(Debug)]
 #[repr(C)]
 pub struct FormatterContext {
@@ -39,3 +39,11 @@ extern "C" {
     pub fn ZydisMnemonicGetString(mnemonic: Mnemonic) -> *const c_char;
     pub fn ZydisMnemonicGetShortString(mnemonic: Mnemonic) -> *const ShortString;
 }
+
+// abi/abi.c, built alongside the C library to report its struct layouts.
+extern "C" {
+    pub fn ZydisRsLayoutOfDecodedInstruction(size: *mut usize, align: *mut usize);
+    pub fn ZydisRsLayoutOfDecodedOperand(size: *mut usize, align: *mut usize);
+    pub fn ZydisRsLayoutOfEncoderRequest(size: *mut usize, align: *mut usize);
+    pub fn ZydisRsLayoutOfFormatter(size: *mut usize, align: *mut usize);
+}
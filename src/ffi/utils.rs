@@ -3,11 +3,69 @@ use super::*;
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
 
+/// Runtime register values used by
+/// [`DecodedInstruction::calc_absolute_address_ex`].
+///
+/// Values are stored per [`Register`] variant, so `EAX` and `RAX` are
+/// distinct entries: set exactly the registers the operands refer to.
+/// Unset registers read as `0`.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// // mov eax, dword ptr [rbx+rcx*4+0x10]
+/// static CODE: &[u8] = &[0x8B, 0x44, 0x8B, 0x10];
+/// let insn = Decoder::new64()
+///     .decode_first::<VisibleOperands>(CODE)
+///     .unwrap()
+///     .unwrap();
+///
+/// let context = ffi::RegisterContext::new()
+///     .set(Register::RBX, 0x1000)
+///     .set(Register::RCX, 2);
+/// assert_eq!(context.get(Register::RBX), 0x1000);
+///
+/// let addr = insn
+///     .calc_absolute_address_ex(0, &insn.operands()[1], &context)
+///     .unwrap();
+/// assert_eq!(addr, 0x1018);
+/// ```
+#[derive(Clone)]
 #[repr(C)]
 pub struct RegisterContext {
     pub values: [u64; REGISTER_MAX_VALUE + 1],
 }
 
+impl RegisterContext {
+    /// Creates a context with all registers set to `0`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            values: [0; REGISTER_MAX_VALUE + 1],
+        }
+    }
+
+    /// Sets the value of `reg`.
+    #[inline]
+    pub const fn set(mut self, reg: Register, value: u64) -> Self {
+        self.values[reg as usize] = value;
+        self
+    }
+
+    /// Returns the value of `reg`.
+    #[inline]
+    pub const fn get(&self, reg: Register) -> u64 {
+        self.values[reg as usize]
+    }
+}
+
+impl Default for RegisterContext {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[repr(C)]
@@ -42,6 +42,12 @@ impl ZyanString {
             ZyanStringAppend(self, &view).into()
         }
     }
+
+    /// Returns the number of bytes that can still be appended.
+    #[inline]
+    pub fn remaining_capacity(&self) -> usize {
+        self.vector.capacity.saturating_sub(self.vector.size)
+    }
 }
 
 impl fmt::Write for ZyanString {
@@ -0,0 +1,251 @@
+//! Control flow successors of single instructions.
+
+use crate::*;
+use core::{array, iter, ops, slice};
+
+/// A possible successor of an instruction in the control flow graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Successor {
+    /// Statically known target of a jump, branch or call.
+    Branch(u64),
+    /// The instruction directly following this one.
+    Fallthrough(u64),
+    /// Target of a jump or call only known at runtime, e.g. `jmp rax`.
+    Indirect,
+    /// Control leaves the function, e.g. via `ret` or `sysret`.
+    Return,
+}
+
+/// Static branch prediction hint of a conditional branch, see
+/// [`Instruction::branch_hint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BranchHint {
+    /// The branch is likely taken (`3E` prefix).
+    Taken,
+    /// The branch is likely not taken (`2E` prefix).
+    NotTaken,
+}
+
+/// The successors of an instruction, as returned by
+/// [`Instruction::successors`].
+///
+/// Stores up to two successors inline and dereferences to a slice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Successors {
+    items: [Successor; 2],
+    len: u8,
+}
+
+impl Successors {
+    const fn new() -> Self {
+        Self {
+            items: [Successor::Indirect; 2],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, successor: Successor) {
+        self.items[usize::from(self.len)] = successor;
+        self.len += 1;
+    }
+}
+
+impl ops::Deref for Successors {
+    type Target = [Successor];
+
+    #[inline]
+    fn deref(&self) -> &[Successor] {
+        &self.items[..usize::from(self.len)]
+    }
+}
+
+impl IntoIterator for Successors {
+    type IntoIter = iter::Take<array::IntoIter<Successor, 2>>;
+    type Item = Successor;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter().take(usize::from(self.len))
+    }
+}
+
+impl<'a> IntoIterator for &'a Successors {
+    type IntoIter = slice::Iter<'a, Successor>;
+    type Item = &'a Successor;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<O: Operands> Instruction<O> {
+    /// Returns the address of the instruction directly following this one,
+    /// using `ip` as its address.
+    #[inline]
+    pub fn next_ip(&self, ip: u64) -> u64 {
+        ip.wrapping_add(u64::from(self.length))
+    }
+
+    /// Whether execution never continues with the next instruction.
+    ///
+    /// This is the case for returns, unconditional jumps and instructions
+    /// that stop execution (`hlt`, `ud2`, `int3`).
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let decoder = Decoder::new64();
+    /// let halts = |code: &[u8]| {
+    ///     decoder
+    ///         .decode_first::<NoOperands>(code)
+    ///         .unwrap()
+    ///         .unwrap()
+    ///         .halts_fallthrough()
+    /// };
+    ///
+    /// assert!(halts(&[0xC3])); // ret
+    /// assert!(halts(&[0xEB, 0x00])); // jmp
+    /// assert!(halts(&[0xCC])); // int3
+    /// assert!(!halts(&[0x74, 0x00])); // jz
+    /// assert!(!halts(&[0xE8, 0x00, 0x00, 0x00, 0x00])); // call
+    /// ```
+    pub fn halts_fallthrough(&self) -> bool {
+        matches!(
+            self.meta.category,
+            InstructionCategory::RET | InstructionCategory::SYSRET | InstructionCategory::UNCOND_BR
+        ) || matches!(
+            self.mnemonic,
+            Mnemonic::HLT | Mnemonic::UD0 | Mnemonic::UD1 | Mnemonic::UD2 | Mnemonic::INT3
+        )
+    }
+
+    /// Returns the control flow successors of this instruction, using `ip` as
+    /// its address.
+    ///
+    /// Calls are assumed to return, so they yield both their target and the
+    /// fallthrough. Conditional branches yield the taken target first.
+    /// Instructions that stop execution (`hlt`, `ud2`, `int3`) have no
+    /// successors. Branch targets are taken from the raw instruction info,
+    /// so this works with any `O`, including [`NoOperands`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let decoder = Decoder::new64();
+    /// let successors = |code: &[u8]| {
+    ///     decoder
+    ///         .decode_first::<NoOperands>(code)
+    ///         .unwrap()
+    ///         .unwrap()
+    ///         .successors(0x1000)
+    /// };
+    ///
+    /// // jz 0x1010
+    /// assert_eq!(
+    ///     *successors(&[0x74, 0x0E]),
+    ///     [Successor::Branch(0x1010), Successor::Fallthrough(0x1002)]
+    /// );
+    /// // jmp rax
+    /// assert_eq!(*successors(&[0xFF, 0xE0]), [Successor::Indirect]);
+    /// // ret
+    /// assert_eq!(*successors(&[0xC3]), [Successor::Return]);
+    /// // nop
+    /// assert_eq!(*successors(&[0x90]), [Successor::Fallthrough(0x1001)]);
+    /// ```
+    pub fn successors(&self, ip: u64) -> Successors {
+        let mut successors = Successors::new();
+        let next = self.next_ip(ip);
+
+        let target = || {
+            self.raw
+                .imm
+                .iter()
+                .find(|imm| imm.is_relative)
+                .map(|imm| {
+                    let target = next.wrapping_add(imm.value_u64());
+                    match self.operand_width {
+                        16 => Successor::Branch(target & 0xFFFF),
+                        32 => Successor::Branch(target & 0xFFFF_FFFF),
+                        _ => Successor::Branch(target),
+                    }
+                })
+                .unwrap_or(Successor::Indirect)
+        };
+
+        match self.meta.category {
+            InstructionCategory::RET | InstructionCategory::SYSRET => {
+                successors.push(Successor::Return)
+            }
+            InstructionCategory::UNCOND_BR
+            | InstructionCategory::COND_BR
+            | InstructionCategory::CALL => successors.push(target()),
+            _ => (),
+        }
+        if !self.halts_fallthrough() {
+            successors.push(Successor::Fallthrough(next));
+        }
+
+        successors
+    }
+
+    /// Returns the static branch prediction hint of a conditional branch.
+    ///
+    /// This surfaces [`InstructionAttributes::HAS_BRANCH_TAKEN`] and
+    /// [`InstructionAttributes::HAS_BRANCH_NOT_TAKEN`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let decoder = Decoder::new64();
+    /// let hint = |code: &[u8]| {
+    ///     decoder
+    ///         .decode_first::<NoOperands>(code)
+    ///         .unwrap()
+    ///         .unwrap()
+    ///         .branch_hint()
+    /// };
+    ///
+    /// assert_eq!(hint(&[0x3E, 0x74, 0x00]), Some(BranchHint::Taken)); // jz
+    /// assert_eq!(hint(&[0x2E, 0x74, 0x00]), Some(BranchHint::NotTaken)); // jz
+    /// assert_eq!(hint(&[0x74, 0x00]), None); // jz
+    /// ```
+    pub fn branch_hint(&self) -> Option<BranchHint> {
+        if self
+            .attributes
+            .contains(InstructionAttributes::HAS_BRANCH_TAKEN)
+        {
+            Some(BranchHint::Taken)
+        } else if self
+            .attributes
+            .contains(InstructionAttributes::HAS_BRANCH_NOT_TAKEN)
+        {
+            Some(BranchHint::NotTaken)
+        } else {
+            None
+        }
+    }
+
+    /// Whether an indirect jump or call is exempt from CET indirect branch
+    /// tracking (`notrack` prefix).
+    ///
+    /// This surfaces [`InstructionAttributes::HAS_NOTRACK`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let decoder = Decoder::new64();
+    /// let notrack = |code: &[u8]| {
+    ///     decoder
+    ///         .decode_first::<NoOperands>(code)
+    ///         .unwrap()
+    ///         .unwrap()
+    ///         .has_notrack()
+    /// };
+    ///
+    /// assert!(notrack(&[0x3E, 0xFF, 0xE0])); // notrack jmp rax
+    /// assert!(!notrack(&[0xFF, 0xE0])); // jmp rax
+    /// ```
+    #[inline]
+    pub fn has_notrack(&self) -> bool {
+        self.attributes.contains(InstructionAttributes::HAS_NOTRACK)
+    }
+}
@@ -1,14 +1,20 @@
 //! Textual instruction formatting routines.
+//!
+//! Formatting into caller provided buffers (e.g. [`Formatter::format_ex`])
+//! and raw hooks work without `alloc`. Wrapped hooks and the methods
+//! returning owned strings require the `alloc` feature.
 
-use alloc::{borrow::ToOwned, boxed::Box, string::String};
+#[cfg(feature = "alloc")]
+use alloc::{borrow::ToOwned, boxed::Box, string::String, vec, vec::Vec};
 use core::{
     ffi::{c_void, CStr},
     fmt,
+    marker::PhantomData,
     mem::{self, MaybeUninit},
     ptr,
 };
 
-use crate::decoder::{Instruction, OperandArrayVec};
+use crate::decoder::{Instruction, Operands};
 
 use super::{
     enums::*,
@@ -136,14 +142,16 @@ macro_rules! wrapped_hook_setter {
         ///
         /// This function accepts a wrapped version of the raw hook.
         /// It returns the previous set *raw* hook.
+        #[cfg(feature = "alloc")]
         #[inline]
         pub fn $func_name(&mut self, new_func: Box<$field_type>) -> Result<Hook> {
-            self.$field_name = Some(new_func);
+            self.hooks.$field_name = Some(new_func);
             unsafe { self.set_raw_hook($constructor($dispatch_func)) }
         }
     };
 }
 
+#[cfg(feature = "alloc")]
 unsafe fn get_user_data<'a, UserData>(user_data: *mut c_void) -> Option<&'a mut UserData> {
     if user_data.is_null() {
         None
@@ -154,13 +162,17 @@ unsafe fn get_user_data<'a, UserData>(user_data: *mut c_void) -> Option<&'a mut
 
 macro_rules! wrap_func {
     (general $field_name:ident, $func_name:ident) => {
+        #[cfg(feature = "alloc")]
         unsafe extern "C" fn $func_name<UserData>(
             formatter: *const ffi::Formatter,
             buffer: *mut ffi::FormatterBuffer,
             ctx: *mut ffi::FormatterContext,
         ) -> Status {
             let formatter = &*(formatter as *const Formatter<UserData>);
-            match formatter.$field_name.as_ref().unwrap()(
+            let Some(hook) = formatter.hooks.$field_name.as_ref() else {
+                return Status::InvalidOperation;
+            };
+            match hook(
                 formatter,
                 &mut *buffer,
                 &mut *ctx,
@@ -172,6 +184,7 @@ macro_rules! wrap_func {
         }
     };
     (register $field_name:ident, $func_name:ident) => {
+        #[cfg(feature = "alloc")]
         unsafe extern "C" fn $func_name<UserData>(
             formatter: *const ffi::Formatter,
             buffer: *mut ffi::FormatterBuffer,
@@ -179,7 +192,10 @@ macro_rules! wrap_func {
             reg: Register,
         ) -> Status {
             let formatter = &*(formatter as *const Formatter<UserData>);
-            match formatter.$field_name.as_ref().unwrap()(
+            let Some(hook) = formatter.hooks.$field_name.as_ref() else {
+                return Status::InvalidOperation;
+            };
+            match hook(
                 formatter,
                 &mut *buffer,
                 &mut *ctx,
@@ -192,6 +208,7 @@ macro_rules! wrap_func {
         }
     };
     (decorator $field_name:ident, $func_name:ident) => {
+        #[cfg(feature = "alloc")]
         unsafe extern "C" fn $func_name<UserData>(
             formatter: *const ffi::Formatter,
             buffer: *mut ffi::FormatterBuffer,
@@ -199,7 +216,10 @@ macro_rules! wrap_func {
             decorator: Decorator,
         ) -> Status {
             let formatter = &*(formatter as *const Formatter<UserData>);
-            match formatter.$field_name.as_ref().unwrap()(
+            let Some(hook) = formatter.hooks.$field_name.as_ref() else {
+                return Status::InvalidOperation;
+            };
+            match hook(
                 formatter,
                 &mut *buffer,
                 &mut *ctx,
@@ -265,6 +285,89 @@ pub enum FormatterProperty<'a> {
     HexSuffix(Option<&'a CStr>),
 }
 
+/// Size of the scratch buffers used by the allocating / [`fmt`] formatting
+/// paths.
+const SCRATCH_BUFFER_SIZE: usize = 256;
+
+#[cfg(all(feature = "std", not(feature = "nolibc")))]
+std::thread_local! {
+    static SCRATCH_BUFFER: core::cell::RefCell<[u8; SCRATCH_BUFFER_SIZE]> =
+        const { core::cell::RefCell::new([0; SCRATCH_BUFFER_SIZE]) };
+}
+
+/// Runs `f` with a scratch [`OutputBuffer`].
+///
+/// With `std` enabled (and `nolibc` disabled), this reuses a
+/// per-thread buffer instead of zeroing a fresh stack array on every call.
+/// Re-entrant use (e.g. formatting from within a formatter hook) and use
+/// during thread teardown fall back to a stack buffer.
+pub(crate) fn with_scratch_buffer<R>(mut f: impl FnMut(&mut OutputBuffer) -> R) -> R {
+    #[cfg(all(feature = "std", not(feature = "nolibc")))]
+    {
+        let pooled = SCRATCH_BUFFER.try_with(|cell| {
+            let mut buffer = cell.try_borrow_mut().ok()?;
+            Some(f(&mut OutputBuffer::new(&mut buffer[..])))
+        });
+        if let Ok(Some(result)) = pooled {
+            return result;
+        }
+    }
+
+    let mut buffer = [0u8; SCRATCH_BUFFER_SIZE];
+    f(&mut OutputBuffer::new(&mut buffer))
+}
+
+impl ffi::DecodedOperand {
+    /// Formats this operand of `insn` as a [`String`].
+    ///
+    /// Unlike [`Formatter::format_operand`], this takes the operand directly
+    /// rather than an index into the operands of an [`Instruction`].
+    ///
+    /// The `ip` may be `None`, in which case relative address formatting is
+    /// used. Otherwise absolute addresses are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zydis::*;
+    /// // mov dword ptr [rax+0x10], ecx
+    /// static CODE: &[u8] = &[0x89, 0x48, 0x10];
+    ///
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(CODE)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let op = &insn.operands()[0];
+    ///
+    /// let text = op.format_with(&Formatter::intel(), &insn, None).unwrap();
+    /// assert_eq!(text, "dword ptr [rax+0x10]");
+    /// assert_eq!(op.to_string(), text);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn format_with<UserData>(
+        &self,
+        formatter: &Formatter<UserData>,
+        insn: &ffi::DecodedInstruction,
+        ip: Option<u64>,
+    ) -> Result<String> {
+        with_scratch_buffer(|buffer| {
+            unsafe {
+                ffi::ZydisFormatterFormatOperand(
+                    &formatter.formatter,
+                    insn,
+                    self,
+                    buffer.buffer.as_mut_ptr() as *mut _,
+                    buffer.buffer.len(),
+                    ip_to_runtime_addr(ip),
+                    ptr::null_mut(),
+                )
+                .as_result()?;
+            }
+            Ok(buffer.as_str()?.to_owned())
+        })
+    }
+}
+
 fn ip_to_runtime_addr(ip: Option<u64>) -> u64 {
     match ip {
         None => (-1i64) as u64,
@@ -301,13 +404,104 @@ impl fmt::Display for OutputBuffer<'_> {
     }
 }
 
+/// A growable alternative to [`OutputBuffer`].
+///
+/// Used with [`Formatter::format_growable`] and
+/// [`Formatter::tokenize_growable`], which retry with a larger buffer
+/// whenever the current one is too small.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct GrowableBuffer {
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl GrowableBuffer {
+    /// Size the buffer is never grown beyond.
+    const MAX_SIZE: usize = 1 << 20;
+
+    /// Creates a new buffer of the recommended default size.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(SCRATCH_BUFFER_SIZE)
+    }
+
+    /// Creates a new buffer of `capacity` bytes.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: vec![0; capacity],
+        }
+    }
+
+    /// Returns the current size of the buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Gets a string from this buffer.
+    #[inline]
+    pub fn as_str(&self) -> Result<&str> {
+        CStr::from_bytes_until_nul(&self.buffer)
+            .map_err(|_| Status::InvalidArgument)?
+            .to_str()
+            .map_err(|_| Status::NotUTF8)
+    }
+
+    /// Doubles the size of the buffer, starting at the default size.
+    fn grow(&mut self) -> Result<()> {
+        if self.buffer.len() >= Self::MAX_SIZE {
+            return Err(Status::InsufficientBufferSize);
+        }
+        let len = (self.buffer.len() * 2).clamp(SCRATCH_BUFFER_SIZE, Self::MAX_SIZE);
+        self.buffer.resize(len, 0);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Default for GrowableBuffer {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for GrowableBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str().map_err(|_| fmt::Error)?)
+    }
+}
+
 /// Formats decoded instructions to human-readable text.
+///
+/// `UserData` is only borrowed while formatting and doesn't affect whether
+/// formatters are `Send` and `Sync`. Wrapped hooks may capture anything, so
+/// formatters are only `Send` and `Sync` without the `alloc` feature.
+///
+/// # Determinism
+///
+/// The output only depends on the instruction, the `ip`, the style, the
+/// properties and the hooks. Numbers are printed by Zydis itself, so neither
+/// the platform, the locale nor the environment influence it, and all
+/// formatting methods produce byte-identical text. This makes the output
+/// suitable for golden tests and reproducible builds.
 #[repr(C)]
 // needed, since we cast a *const ZydisFormatter to a *const Formatter and the
 // rust compiler could reorder the fields if this wasn't #[repr(C)].
 pub struct Formatter<UserData = ()> {
     formatter: ffi::Formatter,
+    #[cfg(feature = "alloc")]
+    hooks: WrappedHooks<UserData>,
+    // `UserData` is only passed to the hooks, never stored.
+    _user_data: PhantomData<fn(&mut UserData)>,
+}
 
+/// The wrapped hooks called by the `dispatch_*` functions.
+#[cfg(feature = "alloc")]
+struct WrappedHooks<UserData> {
     pre_instruction: Option<Box<WrappedGeneralFunc<UserData>>>,
     post_instruction: Option<Box<WrappedGeneralFunc<UserData>>>,
     pre_operand: Option<Box<WrappedGeneralFunc<UserData>>>,
@@ -328,6 +522,32 @@ pub struct Formatter<UserData = ()> {
     print_decorator: Option<Box<WrappedDecoratorFunc<UserData>>>,
 }
 
+#[cfg(feature = "alloc")]
+impl<UserData> Default for WrappedHooks<UserData> {
+    fn default() -> Self {
+        Self {
+            pre_instruction: None,
+            post_instruction: None,
+            pre_operand: None,
+            post_operand: None,
+            format_instruction: None,
+            format_operand_reg: None,
+            format_operand_mem: None,
+            format_operand_ptr: None,
+            format_operand_imm: None,
+            print_mnemonic: None,
+            print_register: None,
+            print_address_abs: None,
+            print_address_rel: None,
+            print_disp: None,
+            print_imm: None,
+            print_typecast: None,
+            print_prefixes: None,
+            print_decorator: None,
+        }
+    }
+}
+
 impl Formatter<()> {
     /// Creates a new formatter instance (no user-data).
     pub fn new(style: FormatterStyle) -> Self {
@@ -499,6 +719,12 @@ impl<UserData> Formatter<UserData> {
     }
 
     /// Creates a new formatter instance.
+    ///
+    /// # Panics
+    ///
+    /// If Zydis rejects `style`, which doesn't happen for the styles it
+    /// defines.
+    #[allow(clippy::expect_used)]
     pub fn new_custom_userdata(style: FormatterStyle) -> Self {
         unsafe {
             let mut formatter = MaybeUninit::uninit();
@@ -509,24 +735,9 @@ impl<UserData> Formatter<UserData> {
 
             Formatter {
                 formatter: formatter.assume_init(),
-                pre_instruction: None,
-                post_instruction: None,
-                pre_operand: None,
-                post_operand: None,
-                format_instruction: None,
-                format_operand_reg: None,
-                format_operand_mem: None,
-                format_operand_ptr: None,
-                format_operand_imm: None,
-                print_mnemonic: None,
-                print_register: None,
-                print_address_abs: None,
-                print_address_rel: None,
-                print_disp: None,
-                print_imm: None,
-                print_typecast: None,
-                print_prefixes: None,
-                print_decorator: None,
+                #[cfg(feature = "alloc")]
+                hooks: WrappedHooks::default(),
+                _user_data: PhantomData,
             }
         }
     }
@@ -587,32 +798,33 @@ impl<UserData> Formatter<UserData> {
     ///  
     /// The `ip` may be `None`, in which case relative address formatting is
     /// used. Otherwise absolute addresses are used.
-    pub fn format<const N: usize>(
-        &self,
-        ip: Option<u64>,
-        insn: &Instruction<OperandArrayVec<N>>,
-    ) -> Result<String> {
-        let mut buffer = [0u8; 256];
-        let mut buffer = OutputBuffer::new(&mut buffer);
-        self.format_ex(ip, insn, &mut buffer, None)?;
-        Ok(buffer.as_str()?.to_owned())
+    ///
+    /// Works with any operand storage that holds at least the visible
+    /// operands. Instructions decoded with e.g.
+    /// [`NoOperands`](crate::NoOperands) fail with [`Status::InvalidArgument`].
+    #[cfg(feature = "alloc")]
+    pub fn format<O: Operands>(&self, ip: Option<u64>, insn: &Instruction<O>) -> Result<String> {
+        with_scratch_buffer(|buffer| {
+            self.format_ex(ip, insn, buffer, None)?;
+            Ok(buffer.as_str()?.to_owned())
+        })
     }
 
     /// Format an instruction and append it to a [`fmt::Formatter`].
     ///
     /// The `ip` may be `None`, in which case relative address formatting is
     /// used. Otherwise absolute addresses are used.
-    pub fn format_into<const N: usize>(
+    pub fn format_into<O: Operands>(
         &self,
         ip: Option<u64>,
-        insn: &Instruction<OperandArrayVec<N>>,
+        insn: &Instruction<O>,
         f: &mut fmt::Formatter<'_>,
     ) -> Result {
-        let mut buffer = [0u8; 256];
-        let mut buffer = OutputBuffer::new(&mut buffer);
-        self.format_ex(ip, insn, &mut buffer, None)?;
-        f.write_str(buffer.as_str()?)
-            .map_err(|_| Status::FormatterError)
+        with_scratch_buffer(|buffer| {
+            self.format_ex(ip, insn, buffer, None)?;
+            f.write_str(buffer.as_str()?)
+                .map_err(|_| Status::FormatterError)
+        })
     }
 
     /// Format an instruction into an [`OutputBuffer`].
@@ -645,19 +857,56 @@ impl<UserData> Formatter<UserData> {
     /// assert_eq!(buffer.as_str().unwrap(), "int3");
     /// ```
     #[inline]
-    pub fn format_ex<const N: usize>(
+    pub fn format_ex<O: Operands>(
         &self,
         ip: Option<u64>,
-        insn: &Instruction<OperandArrayVec<N>>,
+        insn: &Instruction<O>,
+        buffer: &mut OutputBuffer,
+        user_data: Option<&mut UserData>,
+    ) -> Result<()> {
+        self.format_raw(ip, insn, insn.operands(), buffer, user_data)
+    }
+
+    /// Format an instruction given as raw FFI structs into an
+    /// [`OutputBuffer`].
+    ///
+    /// Like [`Formatter::format_ex`], but allows formatting instructions
+    /// whose operands are stored elsewhere, e.g. in a custom [`Operands`]
+    /// implementation or a borrowed slice. `operands` must contain at least
+    /// the visible operands of `insn`, otherwise
+    /// [`Status::InvalidArgument`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zydis::*;
+    /// let insn: FullInstruction = Decoder::new64()
+    ///     .decode_first(&[0x51]) // push rcx
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let (raw, operands) = (&*insn, insn.operands());
+    ///
+    /// let mut buffer = [0u8; 256];
+    /// let mut buffer = OutputBuffer::new(&mut buffer[..]);
+    /// Formatter::intel()
+    ///     .format_raw(None, raw, operands, &mut buffer, None)
+    ///     .unwrap();
+    /// assert_eq!(buffer.as_str().unwrap(), "push rcx");
+    /// ```
+    pub fn format_raw(
+        &self,
+        ip: Option<u64>,
+        insn: &ffi::DecodedInstruction,
+        operands: &[ffi::DecodedOperand],
         buffer: &mut OutputBuffer,
         user_data: Option<&mut UserData>,
     ) -> Result<()> {
         unsafe {
             ffi::ZydisFormatterFormatInstruction(
                 &self.formatter,
-                &**insn,
-                insn.operands().as_ptr(),
-                insn.operands().len() as u8,
+                insn,
+                operands.as_ptr(),
+                operands.len().min(usize::from(u8::MAX)) as u8,
                 buffer.buffer.as_mut_ptr() as *mut _,
                 buffer.buffer.len(),
                 ip_to_runtime_addr(ip),
@@ -669,6 +918,47 @@ impl<UserData> Formatter<UserData> {
         }
     }
 
+    /// Format an instruction into a [`GrowableBuffer`].
+    ///
+    /// Like [`Formatter::format_ex`], but grows `buffer` as needed instead of
+    /// failing with [`Status::InsufficientBufferSize`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zydis::*;
+    /// static INT3: &'static [u8] = &[0xCC];
+    ///
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(INT3)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let mut buffer = GrowableBuffer::with_capacity(0);
+    /// Formatter::intel()
+    ///     .format_growable(Some(0), &insn, &mut buffer, None)
+    ///     .unwrap();
+    /// assert_eq!(buffer.as_str().unwrap(), "int3");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn format_growable<O: Operands>(
+        &self,
+        ip: Option<u64>,
+        insn: &Instruction<O>,
+        buffer: &mut GrowableBuffer,
+        mut user_data: Option<&mut UserData>,
+    ) -> Result<()> {
+        if buffer.buffer.is_empty() {
+            buffer.grow()?;
+        }
+        loop {
+            let mut output = OutputBuffer::new(&mut buffer.buffer);
+            match self.format_ex(ip, insn, &mut output, user_data.as_deref_mut()) {
+                Err(Status::InsufficientBufferSize) => buffer.grow()?,
+                res => return res,
+            }
+        }
+    }
+
     /// Formats just the given operand at `operand_index` from the given
     /// `instruction`, using `buffer` for storage.
     ///
@@ -676,15 +966,12 @@ impl<UserData> Formatter<UserData> {
     /// used. Otherwise absolute addresses are used.
     ///
     /// `user_data` may contain any data to pass on to the formatter hooks.
-    ///
-    /// # Panics
-    ///
-    /// If `operand_index` is out of bounds.
+    /// Fails with [`Status::OutOfRange`] if `operand_index` is out of bounds.
     #[inline]
-    pub fn format_operand<const N: usize>(
+    pub fn format_operand<O: Operands>(
         &self,
         ip: Option<u64>,
-        insn: &Instruction<OperandArrayVec<N>>,
+        insn: &Instruction<O>,
         buffer: &mut OutputBuffer,
         operand_index: usize,
         user_data: Option<&mut UserData>,
@@ -693,7 +980,9 @@ impl<UserData> Formatter<UserData> {
             ffi::ZydisFormatterFormatOperand(
                 &self.formatter,
                 &**insn,
-                &insn.operands()[operand_index],
+                insn.operands()
+                    .get(operand_index)
+                    .ok_or(Status::OutOfRange)?,
                 buffer.buffer.as_mut_ptr() as *mut _,
                 buffer.buffer.len(),
                 ip_to_runtime_addr(ip),
@@ -709,10 +998,24 @@ impl<UserData> Formatter<UserData> {
     ///
     /// The recommended amount of memory to allocate is 256 bytes.
     #[inline]
-    pub fn tokenize<'buffer, const N: usize>(
+    pub fn tokenize<'buffer, O: Operands>(
         &self,
         ip: Option<u64>,
-        insn: &Instruction<OperandArrayVec<N>>,
+        insn: &Instruction<O>,
+        buffer: &'buffer mut [u8],
+        user_data: Option<&mut UserData>,
+    ) -> Result<&'buffer ffi::FormatterToken<'buffer>> {
+        self.tokenize_raw(ip, insn, insn.operands(), buffer, user_data)
+    }
+
+    /// Tokenize an instruction given as raw FFI structs.
+    ///
+    /// See [`Formatter::format_raw`] for the requirements on `operands`.
+    pub fn tokenize_raw<'buffer>(
+        &self,
+        ip: Option<u64>,
+        insn: &ffi::DecodedInstruction,
+        operands: &[ffi::DecodedOperand],
         buffer: &'buffer mut [u8],
         user_data: Option<&mut UserData>,
     ) -> Result<&'buffer ffi::FormatterToken<'buffer>> {
@@ -720,9 +1023,9 @@ impl<UserData> Formatter<UserData> {
             let mut token = MaybeUninit::uninit();
             ffi::ZydisFormatterTokenizeInstruction(
                 &self.formatter,
-                &**insn,
-                insn.operands().as_ptr(),
-                insn.operands().len() as u8,
+                insn,
+                operands.as_ptr(),
+                operands.len().min(usize::from(u8::MAX)) as u8,
                 buffer.as_mut_ptr() as *mut _,
                 buffer.len(),
                 ip_to_runtime_addr(ip),
@@ -736,8 +1039,37 @@ impl<UserData> Formatter<UserData> {
         }
     }
 
+    /// Tokenize the given instruction into a [`GrowableBuffer`].
+    ///
+    /// Like [`Formatter::tokenize`], but grows `buffer` as needed instead of
+    /// failing with [`Status::InsufficientBufferSize`]. Once the buffer is
+    /// large enough, the instruction is tokenized one more time for the
+    /// returned tokens, so hooks may be invoked twice.
+    #[cfg(feature = "alloc")]
+    pub fn tokenize_growable<'buffer, O: Operands>(
+        &self,
+        ip: Option<u64>,
+        insn: &Instruction<O>,
+        buffer: &'buffer mut GrowableBuffer,
+        mut user_data: Option<&mut UserData>,
+    ) -> Result<&'buffer ffi::FormatterToken<'buffer>> {
+        if buffer.buffer.is_empty() {
+            buffer.grow()?;
+        }
+        loop {
+            match self.tokenize(ip, insn, &mut buffer.buffer, user_data.as_deref_mut()) {
+                Err(Status::InsufficientBufferSize) => buffer.grow()?,
+                Err(err) => return Err(err),
+                Ok(_) => break,
+            }
+        }
+        self.tokenize(ip, insn, &mut buffer.buffer, user_data)
+    }
+
     /// Tokenizes the given operand at `operand_index`.
     ///
+    /// Fails with [`Status::OutOfRange`] if `operand_index` is out of bounds.
+    ///
     /// # Examples
     ///
     /// ```
@@ -758,15 +1090,11 @@ impl<UserData> Formatter<UserData> {
     /// assert_eq!(ty, TOKEN_REGISTER);
     /// assert_eq!(val, "rcx");
     /// ```
-    ///
-    /// # Panics
-    ///
-    /// If `operand_index` is out of bounds.
     #[inline]
-    pub fn tokenize_operand<'buffer, const N: usize>(
+    pub fn tokenize_operand<'buffer, O: Operands>(
         &self,
         ip: Option<u64>,
-        insn: &Instruction<OperandArrayVec<N>>,
+        insn: &Instruction<O>,
         buffer: &'buffer mut [u8],
         operand_index: usize,
         user_data: Option<&mut UserData>,
@@ -776,7 +1104,9 @@ impl<UserData> Formatter<UserData> {
             ffi::ZydisFormatterTokenizeOperand(
                 &self.formatter,
                 &**insn,
-                &insn.operands()[operand_index],
+                insn.operands()
+                    .get(operand_index)
+                    .ok_or(Status::OutOfRange)?,
                 buffer.as_mut_ptr() as *mut _,
                 buffer.len(),
                 ip_to_runtime_addr(ip),
@@ -804,3 +1134,81 @@ impl<UserData> Formatter<UserData> {
         Ok(Hook::from_raw(hook_id, cb))
     }
 }
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[rustfmt::skip]
+    static CODE: &[u8] = &[
+        0x51, 0x8D, 0x45, 0xFF, 0x50, 0xFF, 0x75, 0x0C, 0xFF, 0x75, 0x08,
+        0xFF, 0x15, 0xA0, 0xA5, 0x48, 0x76, 0x85, 0xC0, 0x0F, 0x88, 0xFC,
+        0xDA, 0x02, 0x00,
+    ];
+
+    static GOLDEN_INTEL: &[&str] = &[
+        "push rcx",
+        "lea eax, [rbp-0x01]",
+        "push rax",
+        "push [rbp+0x0C]",
+        "push [rbp+0x08]",
+        "call [0x000000007648A5B1]",
+        "test eax, eax",
+        "js 0x000000000002DB15",
+    ];
+
+    fn decode_all() -> Vec<(u64, FullInstruction)> {
+        Decoder::new64()
+            .decode_all::<AllOperands>(CODE, 0)
+            .map(|item| item.map(|(ip, _, insn)| (ip, insn)))
+            .collect::<Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn golden_intel() {
+        let formatter = Formatter::intel();
+        let text: Vec<_> = decode_all()
+            .iter()
+            .map(|(ip, insn)| formatter.format(Some(*ip), insn).unwrap())
+            .collect();
+        assert_eq!(text, GOLDEN_INTEL);
+    }
+
+    #[test]
+    fn output_is_path_independent() {
+        for style in [FormatterStyle::INTEL, FormatterStyle::ATT] {
+            let formatter = Formatter::new(style);
+            for (ip, insn) in decode_all() {
+                let expected = formatter.format(Some(ip), &insn).unwrap();
+
+                let mut raw = [0u8; 256];
+                let mut buffer = OutputBuffer::new(&mut raw[..]);
+                formatter
+                    .format_ex(Some(ip), &insn, &mut buffer, None)
+                    .unwrap();
+                assert_eq!(buffer.as_str().unwrap(), expected);
+
+                let mut growable = GrowableBuffer::with_capacity(0);
+                formatter
+                    .format_growable(Some(ip), &insn, &mut growable, None)
+                    .unwrap();
+                assert_eq!(growable.as_str().unwrap(), expected);
+
+                let mut raw = [0u8; 256];
+                let tokens: String = formatter
+                    .tokenize(Some(ip), &insn, &mut raw[..], None)
+                    .unwrap()
+                    .into_iter()
+                    .map(|(_, value)| value)
+                    .collect();
+                assert_eq!(tokens, expected);
+
+                let fresh = Formatter::new(style);
+                assert_eq!(fresh.format(Some(ip), &insn).unwrap(), expected);
+                assert_eq!(formatter.format(Some(ip), &insn).unwrap(), expected);
+            }
+        }
+    }
+}
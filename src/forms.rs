@@ -0,0 +1,269 @@
+//! Enumeration of the encodable forms of a mnemonic.
+
+use crate::*;
+use alloc::vec::Vec;
+use core::mem::{self, Discriminant};
+
+/// Maximum number of operands tried when enumerating forms.
+const MAX_ENUMERATED_OPERANDS: usize = 4;
+
+/// Physical encodings tried for every encodable operand combination.
+const ENCODINGS: [EncodableEncoding; 6] = [
+    EncodableEncoding::LEGACY,
+    EncodableEncoding::_3DNOW,
+    EncodableEncoding::XOP,
+    EncodableEncoding::VEX,
+    EncodableEncoding::EVEX,
+    EncodableEncoding::MVEX,
+];
+
+const BRANCH_WIDTHS: [BranchWidth; 4] = [
+    BranchWidth::_8,
+    BranchWidth::_16,
+    BranchWidth::_32,
+    BranchWidth::_64,
+];
+
+const OPERAND_SIZE_HINTS: [OperandSizeHint; 4] = [
+    OperandSizeHint::_8,
+    OperandSizeHint::_16,
+    OperandSizeHint::_32,
+    OperandSizeHint::_64,
+];
+
+/// What distinguishes two forms of an instruction.
+#[derive(PartialEq, Eq)]
+struct FormKey {
+    encoding: InstructionEncoding,
+    opcode_map: OpcodeMap,
+    opcode: u8,
+    operand_width: u8,
+    address_width: u8,
+    operands: Vec<(Discriminant<ffi::DecodedOperandKind>, u16, OperandEncoding)>,
+}
+
+/// Returns one operand of every kind and width, as far as applicable to
+/// `machine_mode`.
+fn representative_operands(machine_mode: MachineMode) -> Vec<EncoderOperand> {
+    let base = match machine_mode {
+        MachineMode::LONG_64 => Register::RAX,
+        MachineMode::LONG_COMPAT_16 | MachineMode::LEGACY_16 | MachineMode::REAL_16 => Register::BX,
+        _ => Register::EAX,
+    };
+
+    let mut operands: Vec<EncoderOperand> = [
+        Register::AL,
+        Register::CL,
+        Register::AX,
+        Register::DX,
+        Register::EAX,
+        Register::RAX,
+        Register::ES,
+        Register::FS,
+        Register::ST0,
+        Register::ST1,
+        Register::MM1,
+        Register::XMM1,
+        Register::YMM1,
+        Register::ZMM1,
+        Register::K1,
+        Register::CR0,
+        Register::DR0,
+        Register::BND0,
+        Register::TMM0,
+    ]
+    .into_iter()
+    .map(EncoderOperand::reg)
+    .collect();
+
+    operands.extend(
+        [1, 2, 4, 6, 8, 10, 16, 32, 64]
+            .into_iter()
+            .map(|size| EncoderOperand::mem_base_disp(size, base, 0)),
+    );
+    if machine_mode != MachineMode::LONG_64 {
+        operands.push(EncoderOperand::ptr(0x10, 0x1000));
+    }
+    operands.extend(
+        [1, 0x7F, 0x7FFF, 0x7FFF_FFFF, 0x7FFF_FFFF_FFFF]
+            .into_iter()
+            .map(EncoderOperand::imm),
+    );
+
+    operands
+}
+
+/// Returns a representative encoder request for every form of `mnemonic`
+/// the encoder supports in `machine_mode`.
+///
+/// A form is a distinct combination of physical encoding (legacy, VEX,
+/// EVEX, ...), opcode, operand and address width and operand kinds and
+/// sizes. Forms are discovered by trying all combinations of up to four
+/// representative operands (one register of every class and width, memory
+/// operands of every size and immediates of every width), each with every
+/// physical encoding, branch width and operand size hint. Forms that only
+/// accept specific registers not among the representatives, e.g. `xmm0`,
+/// aren't found. With about 34 representatives, this needs over a million
+/// encoding attempts regardless of the mnemonic, so it's meant for test
+/// generation rather than hot paths. Use
+/// [`enumerate_encodings_with_max_operands`] to limit the search.
+///
+/// Requests are ordered by operand count, then by discovery order.
+pub fn enumerate_encodings(mnemonic: Mnemonic, machine_mode: MachineMode) -> Vec<EncoderRequest> {
+    enumerate_encodings_with_max_operands(mnemonic, machine_mode, MAX_ENUMERATED_OPERANDS)
+}
+
+/// Like [`enumerate_encodings`], but only tries up to `max_operands`
+/// operands.
+///
+/// The number of encoding attempts grows exponentially with `max_operands`,
+/// so limiting it to the operand count of the mnemonic is much faster.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// let decoder = Decoder::new64();
+/// let forms = enumerate_encodings_with_max_operands(Mnemonic::INC, MachineMode::LONG_64, 1);
+///
+/// let operands: Vec<_> = forms
+///     .iter()
+///     .map(|request| {
+///         let bytes = request.encode().unwrap();
+///         let insn = decoder.decode_first::<VisibleOperands>(&bytes).unwrap().unwrap();
+///         assert_eq!(insn.mnemonic, Mnemonic::INC);
+///         (insn.operands()[0].size, request.operands()[0].ty)
+///     })
+///     .collect();
+///
+/// assert!(operands.contains(&(8, OperandType::REGISTER)));
+/// assert!(operands.contains(&(64, OperandType::REGISTER)));
+/// assert!(operands.contains(&(32, OperandType::MEMORY)));
+/// ```
+pub fn enumerate_encodings_with_max_operands(
+    mnemonic: Mnemonic,
+    machine_mode: MachineMode,
+    max_operands: usize,
+) -> Vec<EncoderRequest> {
+    let Ok(decoder) = Decoder::new(machine_mode, default_stack_width(machine_mode)) else {
+        return Vec::new();
+    };
+
+    let candidates = representative_operands(machine_mode);
+    let mut forms = Enumeration {
+        decoder,
+        keys: Vec::new(),
+        requests: Vec::new(),
+    };
+
+    let request = EncoderRequest::new(machine_mode, mnemonic);
+    for count in 0..=max_operands.min(ENCODER_MAX_OPERANDS) {
+        forms.combine(&request, &candidates, count);
+    }
+
+    forms.requests
+}
+
+/// Returns the usual stack width for `machine_mode`.
+pub(crate) fn default_stack_width(machine_mode: MachineMode) -> StackWidth {
+    match machine_mode {
+        MachineMode::LONG_64 => StackWidth::_64,
+        MachineMode::LONG_COMPAT_16 | MachineMode::LEGACY_16 | MachineMode::REAL_16 => {
+            StackWidth::_16
+        }
+        _ => StackWidth::_32,
+    }
+}
+
+struct Enumeration {
+    decoder: Decoder,
+    keys: Vec<FormKey>,
+    requests: Vec<EncoderRequest>,
+}
+
+impl Enumeration {
+    /// Tries all requests with `remaining` more operands appended.
+    fn combine(
+        &mut self,
+        request: &EncoderRequest,
+        candidates: &[EncoderOperand],
+        remaining: usize,
+    ) {
+        if remaining == 0 {
+            self.try_request(request);
+            return;
+        }
+
+        let last = request.operands().last().map(|op| op.ty);
+        let has_mem = request
+            .operands()
+            .iter()
+            .any(|op| op.ty == OperandType::MEMORY);
+
+        for op in candidates {
+            // Immediates are always last and there's at most one memory operand.
+            let skip = match op.ty {
+                OperandType::IMMEDIATE => false,
+                OperandType::MEMORY => has_mem || last == Some(OperandType::IMMEDIATE),
+                _ => last == Some(OperandType::IMMEDIATE),
+            };
+            if !skip {
+                let request = request.clone().add_operand(op.clone());
+                self.combine(&request, candidates, remaining - 1);
+            }
+        }
+    }
+
+    fn try_request(&mut self, request: &EncoderRequest) {
+        if !self.record(request) {
+            return;
+        }
+
+        let has_imm = request
+            .operands()
+            .iter()
+            .any(|op| op.ty == OperandType::IMMEDIATE);
+        for encoding in ENCODINGS {
+            let mut variant = request.clone();
+            variant.allowed_encodings = encoding;
+            self.record(&variant);
+        }
+        if has_imm {
+            for width in BRANCH_WIDTHS {
+                self.record(&request.clone().set_branch_width(width));
+            }
+        }
+        for hint in OPERAND_SIZE_HINTS {
+            self.record(&request.clone().set_operand_size_hint(hint));
+        }
+    }
+
+    /// Records `request` if it's encodable and of a new form, returning
+    /// whether it's encodable.
+    fn record(&mut self, request: &EncoderRequest) -> bool {
+        let mut buf = [0; MAX_INSTRUCTION_LENGTH];
+        let Ok(length) = request.encode_into(&mut buf) else {
+            return false;
+        };
+        let Ok(Some(insn)) = self.decoder.decode_first::<VisibleOperands>(&buf[..length]) else {
+            return true;
+        };
+
+        let key = FormKey {
+            encoding: insn.encoding,
+            opcode_map: insn.opcode_map,
+            opcode: insn.opcode,
+            operand_width: insn.operand_width,
+            address_width: insn.address_width,
+            operands: insn
+                .operands()
+                .iter()
+                .map(|op| (mem::discriminant(&op.kind), op.size, op.encoding))
+                .collect(),
+        };
+        if !self.keys.contains(&key) {
+            self.keys.push(key);
+            self.requests.push(request.clone());
+        }
+        true
+    }
+}
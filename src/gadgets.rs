@@ -0,0 +1,115 @@
+//! Discovery of ROP/JOP gadgets.
+
+use crate::*;
+use std::{string::String, vec::Vec};
+
+/// A short instruction sequence ending in an indirect control transfer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Gadget {
+    /// Address of the first instruction.
+    pub address: u64,
+    /// Number of instructions, including the final one.
+    pub instruction_count: usize,
+    /// The instructions, formatted with absolute addresses and separated by
+    /// `"; "`.
+    pub text: String,
+}
+
+/// Finds all gadgets of up to `max_instructions` instructions in `bytes`.
+///
+/// A gadget ends in a `ret`, `jmp reg` or `call reg` and contains no other
+/// control flow instructions. Every byte offset is tried as a start, so
+/// gadgets hidden in the middle of other instructions are found as well.
+/// `base` is the address of the first byte. Gadgets are returned sorted by
+/// address.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// // mov eax, 0xC35F0000 (which contains pop rdi; ret)
+/// static CODE: &[u8] = &[0xB8, 0x00, 0x00, 0x5F, 0xC3];
+///
+/// let gadgets = gadgets::find(
+///     &Decoder::new64(),
+///     &Formatter::intel(),
+///     CODE,
+///     0x1000,
+///     2,
+/// )
+/// .unwrap();
+///
+/// let texts: Vec<_> = gadgets.iter().map(|g| (g.address, g.text.as_str())).collect();
+/// assert_eq!(texts, [(0x1003, "pop rdi; ret"), (0x1004, "ret")]);
+/// ```
+pub fn find<UserData>(
+    decoder: &Decoder,
+    formatter: &Formatter<UserData>,
+    bytes: &[u8],
+    base: u64,
+    max_instructions: usize,
+) -> Result<Vec<Gadget>> {
+    let mut gadgets = Vec::new();
+    if max_instructions == 0 {
+        return Ok(gadgets);
+    }
+
+    let max_back = (max_instructions - 1) * MAX_INSTRUCTION_LENGTH;
+    let mut insns = Vec::with_capacity(max_instructions);
+
+    for end in 0..bytes.len() {
+        let last = match decoder.decode_first::<VisibleOperands>(&bytes[end..]) {
+            Ok(Some(insn)) if is_gadget_end(&insn) => insn,
+            _ => continue,
+        };
+
+        for start in end.saturating_sub(max_back)..=end {
+            insns.clear();
+
+            let mut offset = start;
+            while offset < end && insns.len() < max_instructions - 1 {
+                match decoder.decode_first::<VisibleOperands>(&bytes[offset..]) {
+                    Ok(Some(insn)) if insn.meta.branch_type == BranchType::NONE => {
+                        let ip = base + offset as u64;
+                        offset += usize::from(insn.length);
+                        insns.push((ip, insn));
+                    }
+                    _ => break,
+                }
+            }
+
+            if offset != end {
+                continue;
+            }
+
+            insns.push((base + end as u64, last.clone()));
+
+            let mut text = String::new();
+            for (idx, (ip, insn)) in insns.iter().enumerate() {
+                if idx != 0 {
+                    text.push_str("; ");
+                }
+                text.push_str(&formatter.format(Some(*ip), insn)?);
+            }
+
+            gadgets.push(Gadget {
+                address: base + start as u64,
+                instruction_count: insns.len(),
+                text,
+            });
+        }
+    }
+
+    gadgets.sort_by_key(|gadget| gadget.address);
+    Ok(gadgets)
+}
+
+fn is_gadget_end(insn: &Instruction<VisibleOperands>) -> bool {
+    match insn.mnemonic {
+        Mnemonic::RET => true,
+        Mnemonic::JMP | Mnemonic::CALL => matches!(
+            insn.visible_operands().first().map(|op| &op.kind),
+            Some(ffi::DecodedOperandKind::Reg(_))
+        ),
+        _ => false,
+    }
+}
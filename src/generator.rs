@@ -0,0 +1,164 @@
+//! Generation of random, valid instructions for stress testing.
+
+use crate::*;
+use alloc::{boxed::Box, vec::Vec};
+
+/// Opcode escapes and encoding prefixes mixed into the random bytes, so
+/// that multi-byte opcode maps and vector encodings aren't vanishingly rare.
+const LEADS: &[&[u8]] = &[
+    &[],
+    &[0x0F],
+    &[0x0F, 0x38],
+    &[0x0F, 0x3A],
+    &[0x66, 0x0F],
+    &[0xF3, 0x0F],
+    &[0xF2, 0x0F],
+    &[0xC5],
+    &[0xC4],
+    &[0x62],
+];
+
+/// Predicate selecting the instructions yielded by [`InstructionGenerator`].
+type InstructionFilter = Box<dyn Fn(&Instruction<VisibleOperands>) -> bool>;
+
+/// A random, encodable instruction produced by [`InstructionGenerator`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GeneratedInstruction {
+    /// The encoder request describing the instruction.
+    pub request: EncoderRequest,
+    /// The encoded instruction.
+    pub bytes: Vec<u8>,
+}
+
+/// Iterator producing random encodable instructions, e.g. for stress testing
+/// decoders, emulators or CPUs.
+///
+/// Instructions are found by decoding random bytes, so every instruction
+/// known to the decoder can come up. Only instructions that the encoder can
+/// encode again are yielded, together with the encoded bytes. The same seed
+/// and configuration always yield the same instructions.
+///
+/// The iterator ends if no matching instruction was found within
+/// [`InstructionGenerator::max_attempts`] attempts, e.g. because the filters
+/// can't be satisfied in the machine mode of the decoder.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// let generator = InstructionGenerator::new(Decoder::new64(), 0x5EED)
+///     .categories([InstructionCategory::AVX2])
+///     .filter(|insn| insn.operand_count_visible == 3);
+///
+/// let decoder = Decoder::new64();
+/// for generated in generator.take(16) {
+///     let insn: Instruction<VisibleOperands> =
+///         decoder.decode_first(&generated.bytes).unwrap().unwrap();
+///     assert_eq!(insn.mnemonic, generated.request.mnemonic);
+///     assert_eq!(insn.operand_count_visible, 3);
+/// }
+/// ```
+pub struct InstructionGenerator {
+    decoder: Decoder,
+    state: u64,
+    isa_sets: Vec<ISASet>,
+    categories: Vec<InstructionCategory>,
+    filter: Option<InstructionFilter>,
+    max_attempts: usize,
+}
+
+impl InstructionGenerator {
+    /// Creates a generator for the machine mode of `decoder`, seeded with
+    /// `seed`.
+    pub fn new(decoder: Decoder, seed: u64) -> Self {
+        Self {
+            decoder,
+            state: seed,
+            isa_sets: Vec::new(),
+            categories: Vec::new(),
+            filter: None,
+            max_attempts: 1_000_000,
+        }
+    }
+
+    /// Only generates instructions from the given ISA sets.
+    pub fn isa_sets(mut self, isa_sets: impl IntoIterator<Item = ISASet>) -> Self {
+        self.isa_sets.extend(isa_sets);
+        self
+    }
+
+    /// Only generates instructions from the given categories.
+    pub fn categories(mut self, categories: impl IntoIterator<Item = InstructionCategory>) -> Self {
+        self.categories.extend(categories);
+        self
+    }
+
+    /// Only generates instructions matching `filter`, e.g. to constrain the
+    /// operands.
+    pub fn filter(
+        mut self,
+        filter: impl Fn(&Instruction<VisibleOperands>) -> bool + 'static,
+    ) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Sets the number of random byte sequences tried per instruction
+    /// before giving up. Defaults to one million.
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// SplitMix64.
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn random_bytes(&mut self) -> [u8; MAX_INSTRUCTION_LENGTH] {
+        let lead = LEADS[self.next_u64() as usize % LEADS.len()];
+        let mut bytes = [0; MAX_INSTRUCTION_LENGTH];
+        for chunk in bytes.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes()[..chunk.len()]);
+        }
+        bytes[..lead.len()].copy_from_slice(lead);
+        bytes
+    }
+
+    fn matches(&self, insn: &Instruction<VisibleOperands>) -> bool {
+        (self.isa_sets.is_empty() || self.isa_sets.contains(&insn.meta.isa_set))
+            && (self.categories.is_empty() || self.categories.contains(&insn.meta.category))
+            && match &self.filter {
+                Some(filter) => filter(insn),
+                None => true,
+            }
+    }
+}
+
+impl Iterator for InstructionGenerator {
+    type Item = GeneratedInstruction;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for _ in 0..self.max_attempts {
+            let bytes = self.random_bytes();
+            let Ok(Some(insn)) = self.decoder.decode_first::<VisibleOperands>(&bytes) else {
+                continue;
+            };
+            if !self.matches(&insn) {
+                continue;
+            }
+
+            let Ok(request) = EncoderRequest::from_instruction(&insn) else {
+                continue;
+            };
+            if let Ok(bytes) = request.encode() {
+                return Some(GeneratedInstruction { request, bytes });
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,464 @@
+//! Golden disassembly fixtures for regression tests.
+//!
+//! A [`GoldenDump`] records the formatted text and key fields of every
+//! instruction in a byte corpus. Dumps are written in a canonical,
+//! versioned text format suitable for checking into a repository, and can
+//! be parsed back and [compared](compare) against a fresh dump.
+//!
+//! The text format starts with `# key value` metadata lines, followed by
+//! one tab-separated line per instruction: the address, the raw bytes, the
+//! formatted instruction and space-separated `key=value` fields. With the
+//! tabs shown as spaces:
+//!
+//! ```text
+//! # zydis-golden 1
+//! # zydis-version 4.1.0.0
+//! # machine-mode LONG_64
+//! # stack-width _64
+//! # formatter-style INTEL
+//! 0000000000001000  51  push rcx  mnemonic=push encoding=LEGACY category=PUSH isa-set=I86 operand-width=64 address-width=64
+//! ```
+
+use crate::{formatter::with_scratch_buffer, *};
+use core::fmt::{self, Write as _};
+use std::{io, string::String, vec::Vec};
+
+/// Version of the dump format, written as the `zydis-golden` metadata.
+pub const GOLDEN_FORMAT_VERSION: u32 = 1;
+
+/// Text used for bytes that don't decode to a valid instruction.
+const BAD: &str = "(bad)";
+
+/// A single instruction, or undecodable byte, of a [`GoldenDump`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GoldenEntry {
+    /// Address of the instruction.
+    pub ip: u64,
+    /// Raw instruction bytes.
+    pub bytes: Vec<u8>,
+    /// Instruction text, formatted with absolute addresses.
+    ///
+    /// Bytes that don't decode are represented as `(bad)`.
+    pub text: String,
+    /// Key fields of the instruction as key-value pairs, in a fixed order.
+    ///
+    /// Undecodable bytes only have a `status` field with the hex code of
+    /// the decoding error.
+    pub fields: Vec<(String, String)>,
+}
+
+/// Canonical disassembly of a byte corpus.
+///
+/// # Examples
+/// ```
+/// # use zydis::{*, golden::*};
+/// // push rcx; lea eax, [rbp-0x01]
+/// static CODE: &[u8] = &[0x51, 0x8D, 0x45, 0xFF];
+/// let decoder = Decoder::new64();
+/// let formatter = Formatter::intel();
+///
+/// let golden = GoldenDump::generate(&decoder, &formatter, CODE, 0x1000).unwrap();
+/// let mut text = Vec::new();
+/// golden.write_text(&mut text).unwrap();
+///
+/// // Later, e.g. after upgrading:
+/// let expected = GoldenDump::parse(&String::from_utf8(text).unwrap()).unwrap();
+/// let actual = GoldenDump::generate(&decoder, &formatter, CODE, 0x1000).unwrap();
+/// assert!(compare(&expected, &actual).is_empty());
+///
+/// // lea eax, [rbp-0x02]
+/// let patched = GoldenDump::generate(&decoder, &formatter, &[0x51, 0x8D, 0x45, 0xFE], 0x1000);
+/// let mismatches = compare(&expected, &patched.unwrap());
+/// assert_eq!(mismatches.len(), 1);
+/// assert_eq!(
+///     mismatches[0].to_string(),
+///     concat!(
+///         "changed 0000000000001001:\n",
+///         "  - 8D 45 FF\tlea eax, [rbp-0x01]\n",
+///         "  + 8D 45 FE\tlea eax, [rbp-0x02]",
+///     )
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GoldenDump {
+    /// Metadata as key-value pairs, starting with the format version.
+    pub metadata: Vec<(String, String)>,
+    /// The decoded instructions, ordered by address.
+    pub entries: Vec<GoldenEntry>,
+}
+
+impl GoldenDump {
+    /// Decodes `bytes`, starting at address `base`.
+    ///
+    /// Bytes that don't decode to a valid instruction are recorded one at a
+    /// time as `(bad)`. The metadata records the Zydis version, the
+    /// machine mode, the stack width and the formatter style, so changes to
+    /// them show up as metadata mismatches rather than as changed
+    /// instructions.
+    pub fn generate<UserData>(
+        decoder: &Decoder,
+        formatter: &Formatter<UserData>,
+        bytes: &[u8],
+        base: u64,
+    ) -> Result<Self> {
+        let (major, minor, patch, build) = version();
+        let metadata = [
+            ("zydis-golden", GOLDEN_FORMAT_VERSION.to_string()),
+            ("zydis-version", format!("{major}.{minor}.{patch}.{build}")),
+            ("machine-mode", format!("{:?}", decoder.machine_mode())),
+            ("stack-width", format!("{:?}", decoder.stack_width())),
+            ("formatter-style", format!("{:?}", formatter.raw().style())),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value))
+        .collect();
+
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let ip = base.wrapping_add(offset as u64);
+            let entry = match decoder.decode_first::<VisibleOperands>(&bytes[offset..]) {
+                Ok(Some(insn)) => {
+                    let text = with_scratch_buffer(|buffer| {
+                        formatter.format_ex(Some(ip), &insn, buffer, None)?;
+                        Ok::<_, Status>(buffer.as_str()?.to_owned())
+                    })?;
+                    GoldenEntry {
+                        ip,
+                        bytes: bytes[offset..offset + usize::from(insn.length)].to_vec(),
+                        text,
+                        fields: instruction_fields(&insn),
+                    }
+                }
+                // The remaining buffer is never empty.
+                Ok(None) => break,
+                Err(status) => bad_entry(ip, bytes[offset], status),
+            };
+            offset += entry.bytes.len();
+            entries.push(entry);
+        }
+
+        Ok(Self { metadata, entries })
+    }
+
+    /// Parses a dump written by [`GoldenDump::write_text`].
+    ///
+    /// Fails with [`Status::InvalidArgument`] on malformed input or if the
+    /// dump was written in a different format version.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut metadata = Vec::new();
+        let mut entries = Vec::new();
+
+        for line in text.lines().filter(|line| !line.is_empty()) {
+            if let Some(meta) = line.strip_prefix("# ") {
+                let (key, value) = meta.split_once(' ').ok_or(Status::InvalidArgument)?;
+                metadata.push((key.to_owned(), value.to_owned()));
+                continue;
+            }
+
+            let mut columns = line.split('\t');
+            let (Some(ip), Some(bytes), Some(text), fields, None) = (
+                columns.next(),
+                columns.next(),
+                columns.next(),
+                columns.next().unwrap_or(""),
+                columns.next(),
+            ) else {
+                return Err(Status::InvalidArgument);
+            };
+
+            entries.push(GoldenEntry {
+                ip: u64::from_str_radix(ip, 16).map_err(|_| Status::InvalidArgument)?,
+                bytes: bytes
+                    .split_whitespace()
+                    .map(|byte| u8::from_str_radix(byte, 16))
+                    .collect::<core::result::Result<_, _>>()
+                    .map_err(|_| Status::InvalidArgument)?,
+                text: text.to_owned(),
+                fields: fields
+                    .split_whitespace()
+                    .map(|field| {
+                        let (key, value) = field.split_once('=')?;
+                        Some((key.to_owned(), value.to_owned()))
+                    })
+                    .collect::<Option<_>>()
+                    .ok_or(Status::InvalidArgument)?,
+            });
+        }
+
+        let version = GOLDEN_FORMAT_VERSION.to_string();
+        match metadata.first() {
+            Some((key, value)) if key == "zydis-golden" && *value == version => {
+                Ok(Self { metadata, entries })
+            }
+            _ => Err(Status::InvalidArgument),
+        }
+    }
+
+    /// Writes the dump in the canonical text format.
+    pub fn write_text<W: io::Write>(&self, mut out: W) -> io::Result<()> {
+        let mut line = String::new();
+        for (key, value) in &self.metadata {
+            writeln!(out, "# {key} {value}")?;
+        }
+        for entry in &self.entries {
+            line.clear();
+            write_entry(&mut line, entry).map_err(fmt_to_io)?;
+            if !entry.fields.is_empty() {
+                line.push('\t');
+                for (idx, (key, value)) in entry.fields.iter().enumerate() {
+                    if idx > 0 {
+                        line.push(' ');
+                    }
+                    line.push_str(key);
+                    line.push('=');
+                    line.push_str(value);
+                }
+            }
+            line.push('\n');
+            out.write_all(line.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Writes the dump as a JSON document, e.g. for consumption by other
+    /// tools.
+    ///
+    /// Addresses and bytes are hex strings, as in the text format. The JSON
+    /// form can't be parsed back by this crate.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::{*, golden::*};
+    /// let golden = GoldenDump::generate(&Decoder::new64(), &Formatter::intel(), &[0xCC], 0).unwrap();
+    /// let mut json = Vec::new();
+    /// golden.write_json(&mut json).unwrap();
+    ///
+    /// assert!(String::from_utf8(json).unwrap().contains(r#""text": "int3""#));
+    /// ```
+    pub fn write_json<W: io::Write>(&self, mut out: W) -> io::Result<()> {
+        let mut json = String::new();
+        self.write_json_to(&mut json).map_err(fmt_to_io)?;
+        out.write_all(json.as_bytes())
+    }
+
+    fn write_json_to(&self, json: &mut String) -> fmt::Result {
+        json.push_str("{\n  \"metadata\": {");
+        write_json_object(json, &self.metadata)?;
+        json.push_str("},\n  \"instructions\": [");
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if idx > 0 {
+                json.push(',');
+            }
+            json.push_str("\n    {\"ip\": \"");
+            write_hex_u64_padded(json, entry.ip, 16)?;
+            json.push_str("\", \"bytes\": \"");
+            write_hex_bytes(json, &entry.bytes)?;
+            json.push_str("\", \"text\": ");
+            write_json_string(json, &entry.text)?;
+            json.push_str(", \"fields\": {");
+            write_json_object(json, &entry.fields)?;
+            json.push_str("}}");
+        }
+        json.push_str("\n  ]\n}\n");
+        Ok(())
+    }
+}
+
+/// A difference between two [`GoldenDump`]s, see [`compare`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GoldenMismatch {
+    /// A metadata entry differs or is only present in one dump.
+    Metadata {
+        /// The metadata key.
+        key: String,
+        /// The expected value, if any.
+        expected: Option<String>,
+        /// The actual value, if any.
+        actual: Option<String>,
+    },
+    /// The instruction is only present in the expected dump.
+    Missing(GoldenEntry),
+    /// The instruction is only present in the actual dump.
+    Unexpected(GoldenEntry),
+    /// The instruction at the same address differs.
+    Changed {
+        /// The expected instruction.
+        expected: GoldenEntry,
+        /// The actual instruction.
+        actual: GoldenEntry,
+    },
+}
+
+impl fmt::Display for GoldenMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "<none>".into());
+        match self {
+            GoldenMismatch::Metadata {
+                key,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "metadata {key}: expected {}, got {}",
+                value(expected),
+                value(actual)
+            ),
+            GoldenMismatch::Missing(entry) => {
+                f.write_str("missing ")?;
+                write_entry(f, entry)
+            }
+            GoldenMismatch::Unexpected(entry) => {
+                f.write_str("unexpected ")?;
+                write_entry(f, entry)
+            }
+            GoldenMismatch::Changed { expected, actual } => {
+                f.write_str("changed ")?;
+                write_hex_u64_padded(f, expected.ip, 16)?;
+                f.write_str(":")?;
+                let show_fields = expected.fields != actual.fields;
+                for (marker, entry) in [('-', expected), ('+', actual)] {
+                    write!(f, "\n  {marker} ")?;
+                    write_hex_bytes(f, &entry.bytes)?;
+                    write!(f, "\t{}", entry.text)?;
+                    if show_fields {
+                        for (key, value) in &entry.fields {
+                            write!(f, " {key}={value}")?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Compares a freshly generated dump against an expected one.
+///
+/// Instructions are matched by address. Returns all differences in the
+/// metadata, followed by all differing instructions ordered by address, or
+/// nothing if the dumps are equal.
+pub fn compare(expected: &GoldenDump, actual: &GoldenDump) -> Vec<GoldenMismatch> {
+    let mut mismatches = Vec::new();
+
+    let lookup = |metadata: &[(String, String)], key: &str| {
+        metadata
+            .iter()
+            .find(|(other, _)| other == key)
+            .map(|(_, value)| value.clone())
+    };
+    let mut keys: Vec<&String> = expected.metadata.iter().map(|(key, _)| key).collect();
+    for (key, _) in &actual.metadata {
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    for key in keys {
+        let (expected, actual) = (
+            lookup(&expected.metadata, key),
+            lookup(&actual.metadata, key),
+        );
+        if expected != actual {
+            mismatches.push(GoldenMismatch::Metadata {
+                key: key.clone(),
+                expected,
+                actual,
+            });
+        }
+    }
+
+    let mut expected = expected.entries.iter().peekable();
+    let mut actual = actual.entries.iter().peekable();
+    loop {
+        let mismatch = match (expected.peek(), actual.peek()) {
+            (None, None) => break,
+            (Some(&exp), Some(&act)) if exp.ip == act.ip => {
+                expected.next();
+                actual.next();
+                if exp == act {
+                    continue;
+                }
+                GoldenMismatch::Changed {
+                    expected: exp.clone(),
+                    actual: act.clone(),
+                }
+            }
+            (Some(&exp), Some(act)) if exp.ip < act.ip => {
+                expected.next();
+                GoldenMismatch::Missing(exp.clone())
+            }
+            (Some(&exp), None) => {
+                expected.next();
+                GoldenMismatch::Missing(exp.clone())
+            }
+            (_, Some(&act)) => {
+                actual.next();
+                GoldenMismatch::Unexpected(act.clone())
+            }
+        };
+        mismatches.push(mismatch);
+    }
+
+    mismatches
+}
+
+fn instruction_fields(insn: &Instruction<VisibleOperands>) -> Vec<(String, String)> {
+    let mnemonic = insn.mnemonic.static_string().unwrap_or("invalid");
+    [
+        ("mnemonic", mnemonic.to_owned()),
+        ("encoding", format!("{:?}", insn.encoding)),
+        ("category", format!("{:?}", insn.meta.category)),
+        ("isa-set", format!("{:?}", insn.meta.isa_set)),
+        ("operand-width", insn.operand_width.to_string()),
+        ("address-width", insn.address_width.to_string()),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_owned(), value))
+    .collect()
+}
+
+fn bad_entry(ip: u64, byte: u8, status: Status) -> GoldenEntry {
+    GoldenEntry {
+        ip,
+        bytes: vec![byte],
+        text: BAD.to_owned(),
+        fields: vec![("status".to_owned(), format!("{:08X}", status as u32))],
+    }
+}
+
+/// Writes the address, bytes and text columns of `entry`.
+fn write_entry<W: fmt::Write + ?Sized>(out: &mut W, entry: &GoldenEntry) -> fmt::Result {
+    write_hex_u64_padded(out, entry.ip, 16)?;
+    out.write_char('\t')?;
+    write_hex_bytes(out, &entry.bytes)?;
+    out.write_char('\t')?;
+    out.write_str(&entry.text)
+}
+
+fn write_json_object(json: &mut String, pairs: &[(String, String)]) -> fmt::Result {
+    for (idx, (key, value)) in pairs.iter().enumerate() {
+        if idx > 0 {
+            json.push_str(", ");
+        }
+        write_json_string(json, key)?;
+        json.push_str(": ");
+        write_json_string(json, value)?;
+    }
+    Ok(())
+}
+
+fn write_json_string(json: &mut String, value: &str) -> fmt::Result {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32)?,
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    Ok(())
+}
@@ -0,0 +1,110 @@
+//! Table-driven hexadecimal output helpers.
+//!
+//! These are used by the textual output layers of this crate in place of
+//! `core::fmt` integer formatting, which is comparatively slow for the
+//! fixed-width addresses and byte dumps making up most of a listing.
+
+use core::{fmt, str};
+
+/// Maps every byte value to its two uppercase hex digits.
+static HEX_PAIRS: [[u8; 2]; 256] = hex_pairs();
+
+const fn hex_pairs() -> [[u8; 2]; 256] {
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    let mut table = [[0; 2]; 256];
+    let mut i = 0;
+    while i < table.len() {
+        table[i] = [DIGITS[i >> 4], DIGITS[i & 0xF]];
+        i += 1;
+    }
+    table
+}
+
+/// Writes `value` as uppercase hexadecimal, zero-padded to `width` digits.
+///
+/// Produces the same output as `write!(out, "{:0width$X}", value)`. Values
+/// requiring more than `width` digits are written in full.
+///
+/// # Examples
+///
+/// ```
+/// let mut s = String::new();
+/// zydis::write_hex_u64_padded(&mut s, 0x1234, 8).unwrap();
+/// assert_eq!(s, "00001234");
+/// ```
+pub fn write_hex_u64_padded<W: fmt::Write + ?Sized>(
+    out: &mut W,
+    value: u64,
+    width: usize,
+) -> fmt::Result {
+    let mut digits = [0u8; 16];
+    for (pair, byte) in digits.chunks_exact_mut(2).zip(value.to_be_bytes()) {
+        pair.copy_from_slice(&HEX_PAIRS[usize::from(byte)]);
+    }
+
+    for _ in digits.len()..width {
+        out.write_char('0')?;
+    }
+
+    let significant = (16 - value.leading_zeros() as usize / 4).max(1);
+    let start = digits.len() - significant.max(width.min(digits.len()));
+
+    // SAFETY: the table only contains ASCII digits.
+    out.write_str(unsafe { str::from_utf8_unchecked(&digits[start..]) })
+}
+
+/// Writes `bytes` as space-separated pairs of uppercase hex digits.
+///
+/// # Examples
+///
+/// ```
+/// let mut s = String::new();
+/// zydis::write_hex_bytes(&mut s, &[0x48, 0x8B, 0x05]).unwrap();
+/// assert_eq!(s, "48 8B 05");
+/// ```
+pub fn write_hex_bytes<W: fmt::Write + ?Sized>(out: &mut W, bytes: &[u8]) -> fmt::Result {
+    for (i, byte) in bytes.iter().enumerate() {
+        if i != 0 {
+            out.write_char(' ')?;
+        }
+
+        // SAFETY: the table only contains ASCII digits.
+        out.write_str(unsafe { str::from_utf8_unchecked(&HEX_PAIRS[usize::from(*byte)]) })?;
+    }
+
+    Ok(())
+}
+
+/// Converts a formatting error into an I/O error, for writers that format
+/// into a `String` before writing it out.
+#[cfg(all(feature = "std", feature = "formatter"))]
+pub(crate) fn fmt_to_io(err: fmt::Error) -> std::io::Error {
+    std::io::Error::other(err)
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use alloc::{format, string::String};
+
+    #[test]
+    fn hex_u64_matches_core_fmt() {
+        for value in [0, 1, 0xF, 0x10, 0x1234, 0xFFFF_FFFF, u64::MAX] {
+            for width in [0, 1, 2, 8, 16, 20] {
+                let mut s = String::new();
+                write_hex_u64_padded(&mut s, value, width).unwrap();
+                assert_eq!(s, format!("{:0width$X}", value, width = width));
+            }
+        }
+    }
+
+    #[test]
+    fn hex_bytes() {
+        let mut s = String::new();
+        write_hex_bytes(&mut s, &[]).unwrap();
+        assert_eq!(s, "");
+
+        write_hex_bytes(&mut s, &[0x00, 0xAB, 0xFF]).unwrap();
+        assert_eq!(s, "00 AB FF");
+    }
+}
@@ -0,0 +1,273 @@
+//! Static HTML disassembly reports.
+
+use crate::*;
+use core::fmt::Write as _;
+use std::{collections::BTreeSet, io};
+
+const STYLE: &str = "\
+body { font-family: monospace; }
+table.listing { border-collapse: collapse; }
+table.listing td { padding: 0 1em 0 0; white-space: pre; }
+td.ip, td.bytes { color: #888; }
+.prefix, .mnemonic { color: #0550ae; font-weight: bold; }
+.register { color: #8250df; }
+.imm, .disp { color: #0a7f34; }
+.addr { color: #bc4c00; }
+.typecast, .decorator { color: #6e7781; }
+.bad { color: #cf222e; }
+";
+
+/// Additional style of annotated reports.
+const ANNOTATION_STYLE: &str = "\
+tr.label td { font-weight: bold; }
+td.comment { color: #6e7781; }
+";
+
+/// Writes a standalone HTML report disassembling `bytes`.
+///
+/// Every instruction becomes a table row with the anchor `insn-<ip>` (the
+/// address in uppercase hex). Each formatter token is wrapped in a `<span>`
+/// whose class names the token kind: `prefix`, `mnemonic`, `register`,
+/// `addr`, `disp`, `imm`, `typecast`, `decorator`, `symbol`, `delim`, `paren`
+/// or `user`. Relative branches targeting an instruction within the report
+/// link to that instruction.
+///
+/// Bytes that don't decode to a valid instruction are shown one at a time as
+/// `(bad)`.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// // push rcx; jmp 0x1000
+/// static CODE: &[u8] = &[0x51, 0xEB, 0xFD];
+///
+/// let mut out = Vec::new();
+/// html::write_report(
+///     &Decoder::new64(),
+///     &Formatter::intel(),
+///     CODE,
+///     0x1000,
+///     "example",
+///     &mut out,
+/// )
+/// .unwrap();
+///
+/// let html = String::from_utf8(out).unwrap();
+/// assert!(html.contains(r#"<tr id="insn-1000">"#));
+/// assert!(html.contains(r#"<span class="mnemonic">jmp</span>"#));
+/// assert!(html.contains(r##"<a class="addr" href="#insn-1000">"##));
+/// ```
+pub fn write_report<UserData, W: io::Write>(
+    decoder: &Decoder,
+    formatter: &Formatter<UserData>,
+    bytes: &[u8],
+    base: u64,
+    title: &str,
+    out: W,
+) -> io::Result<()> {
+    write_report_impl::<_, (), _>(decoder, formatter, bytes, base, title, None, out)
+}
+
+/// Writes a standalone HTML report like [`write_report`], interleaving the
+/// labels and comments from `annotations`.
+///
+/// A label becomes a row of class `label` before its instruction, with the
+/// anchor `label-<name>`. Comments are added to the instruction row in a cell
+/// of class `comment`.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// // push rcx; jmp 0x1000
+/// static CODE: &[u8] = &[0x51, 0xEB, 0xFD];
+///
+/// let mut annotations = AnnotationMap::<()>::new();
+/// annotations.set_label(0x1000, "loop");
+/// annotations.add_comment(0x1001, "<forever>");
+///
+/// let mut out = Vec::new();
+/// html::write_annotated_report(
+///     &Decoder::new64(),
+///     &Formatter::intel(),
+///     CODE,
+///     0x1000,
+///     "example",
+///     &annotations,
+///     &mut out,
+/// )
+/// .unwrap();
+///
+/// let html = String::from_utf8(out).unwrap();
+/// assert!(html.contains(r#"<tr class="label" id="label-loop"><td colspan="4">loop:</td></tr>"#));
+/// assert!(html.contains(r#"<td class="comment">; &lt;forever&gt;</td>"#));
+/// ```
+pub fn write_annotated_report<UserData, T, W: io::Write>(
+    decoder: &Decoder,
+    formatter: &Formatter<UserData>,
+    bytes: &[u8],
+    base: u64,
+    title: &str,
+    annotations: &AnnotationMap<T>,
+    out: W,
+) -> io::Result<()> {
+    write_report_impl(
+        decoder,
+        formatter,
+        bytes,
+        base,
+        title,
+        Some(annotations),
+        out,
+    )
+}
+
+/// Writes a report, with a comment column if `annotations` is given.
+fn write_report_impl<UserData, T, W: io::Write>(
+    decoder: &Decoder,
+    formatter: &Formatter<UserData>,
+    bytes: &[u8],
+    base: u64,
+    title: &str,
+    annotations: Option<&AnnotationMap<T>>,
+    mut out: W,
+) -> io::Result<()> {
+    let mut rows = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let insn = decoder
+            .decode_first::<VisibleOperands>(&bytes[offset..])
+            .ok()
+            .flatten();
+        let length = insn.as_ref().map_or(1, |insn| usize::from(insn.length));
+        rows.push((
+            base.wrapping_add(offset as u64),
+            offset..offset + length,
+            insn,
+        ));
+        offset += length;
+    }
+
+    let starts: BTreeSet<u64> = rows.iter().map(|(ip, ..)| *ip).collect();
+
+    out.write_all(b"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>")?;
+    write_escaped(&mut out, title)?;
+    let annotation_style = if annotations.is_some() {
+        ANNOTATION_STYLE
+    } else {
+        ""
+    };
+    write!(
+        out,
+        "</title>\n<style>\n{STYLE}{annotation_style}</style>\n</head>\n<body>\n<table \
+         class=\"listing\">\n"
+    )?;
+
+    let mut token_buffer = [0u8; 256];
+    let mut hex = String::new();
+    for (ip, range, insn) in &rows {
+        if let Some(label) = annotations.and_then(|annotations| annotations.label(*ip)) {
+            out.write_all(b"<tr class=\"label\" id=\"label-")?;
+            write_escaped(&mut out, label)?;
+            out.write_all(b"\"><td colspan=\"4\">")?;
+            write_escaped(&mut out, label)?;
+            out.write_all(b":</td></tr>\n")?;
+        }
+
+        write!(out, "<tr id=\"insn-{ip:X}\"><td class=\"ip\">")?;
+        hex.clear();
+        write_hex_u64_padded(&mut hex, *ip, 16)
+            .and_then(|_| hex.write_str("</td><td class=\"bytes\">"))
+            .and_then(|_| write_hex_bytes(&mut hex, &bytes[range.clone()]))
+            .map_err(fmt_to_io)?;
+        out.write_all(hex.as_bytes())?;
+        out.write_all(b"</td><td class=\"asm\">")?;
+
+        match insn {
+            Some(insn) => {
+                let target = branch_target(insn, *ip).filter(|x| starts.contains(x));
+                let tokens = formatter
+                    .tokenize(Some(*ip), insn, &mut token_buffer, None)
+                    .map_err(io::Error::other)?;
+
+                for (ty, value) in tokens {
+                    let Some(class) = token_class(ty) else {
+                        write_escaped(&mut out, value)?;
+                        continue;
+                    };
+
+                    match target {
+                        Some(target) if ty == TOKEN_ADDRESS_ABS => {
+                            write!(out, "<a class=\"{class}\" href=\"#insn-{target:X}\">")?;
+                            write_escaped(&mut out, value)?;
+                            out.write_all(b"</a>")?;
+                        }
+                        _ => {
+                            write!(out, "<span class=\"{class}\">")?;
+                            write_escaped(&mut out, value)?;
+                            out.write_all(b"</span>")?;
+                        }
+                    }
+                }
+            }
+            None => out.write_all(b"<span class=\"bad\">(bad)</span>")?,
+        }
+
+        if let Some(annotations) = annotations {
+            out.write_all(b"</td><td class=\"comment\">")?;
+            let comments = annotations.comments(*ip);
+            if !comments.is_empty() {
+                out.write_all(b"; ")?;
+                write_escaped(&mut out, &comments.join("; "))?;
+            }
+        }
+
+        out.write_all(b"</td></tr>\n")?;
+    }
+
+    out.write_all(b"</table>\n</body>\n</html>\n")
+}
+
+/// Absolute target of a relative branch.
+fn branch_target(insn: &Instruction<VisibleOperands>, ip: u64) -> Option<u64> {
+    if insn.meta.branch_type == BranchType::NONE {
+        return None;
+    }
+
+    insn.visible_operands()
+        .iter()
+        .find(|op| matches!(&op.kind, ffi::DecodedOperandKind::Imm(imm) if imm.is_relative))
+        .and_then(|op| insn.calc_absolute_address(ip, op).ok())
+}
+
+fn token_class(ty: Token) -> Option<&'static str> {
+    Some(match ty {
+        TOKEN_WHITESPACE | TOKEN_INVALID => return None,
+        TOKEN_DELIMITER => "delim",
+        TOKEN_PARENTHESIS_OPEN | TOKEN_PARENTHESIS_CLOSE => "paren",
+        TOKEN_PREFIX => "prefix",
+        TOKEN_MNEMONIC => "mnemonic",
+        TOKEN_REGISTER => "register",
+        TOKEN_ADDRESS_ABS | TOKEN_ADDRESS_REL => "addr",
+        TOKEN_DISPLACEMENT => "disp",
+        TOKEN_IMMEDIATE => "imm",
+        TOKEN_TYPECAST => "typecast",
+        TOKEN_DECORATOR => "decorator",
+        TOKEN_SYMBOL => "symbol",
+        _ => "user",
+    })
+}
+
+fn write_escaped(out: &mut impl io::Write, text: &str) -> io::Result<()> {
+    let mut rest = text;
+    while let Some(pos) = rest.find(['<', '>', '&', '"']) {
+        out.write_all(&rest.as_bytes()[..pos])?;
+        out.write_all(match rest.as_bytes()[pos] {
+            b'<' => b"&lt;",
+            b'>' => b"&gt;",
+            b'&' => b"&amp;",
+            _ => b"&quot;",
+        })?;
+        rest = &rest[pos + 1..];
+    }
+    out.write_all(rest.as_bytes())
+}
@@ -1,5 +1,5 @@
 // The doc-test in README.md needs formatter
-#![cfg_attr(feature = "formatter", doc = include_str!("../README.md"))]
+#![cfg_attr(all(feature = "formatter", feature = "alloc"), doc = include_str!("../README.md"))]
 #![cfg_attr(not(feature = "std"), no_std)]
 
 //! ## Navigation
@@ -14,20 +14,138 @@ extern crate alloc;
 
 #[macro_use]
 mod status;
+mod abi;
+mod addr_expr;
+#[cfg(feature = "alloc")]
+mod address_space;
+#[cfg(feature = "alloc")]
+mod annotations;
+mod arch;
+#[cfg(feature = "bumpalo")]
+mod arena;
+#[cfg(feature = "encoder")]
+pub mod asm;
+#[cfg(feature = "alloc")]
+mod boundaries;
+#[cfg(all(feature = "heapless", any(feature = "encoder", feature = "formatter")))]
+mod bounded;
+#[cfg(all(feature = "std", feature = "full-decoder"))]
+mod cache;
+#[cfg(feature = "encoder")]
+mod canonical;
+#[cfg(feature = "std")]
+mod classify;
+#[cfg(all(feature = "alloc", feature = "full-decoder"))]
+mod code_view;
+mod compat;
+#[cfg(all(feature = "alloc", feature = "full-decoder"))]
+mod constants;
+#[cfg(feature = "alloc")]
+mod coverage;
 mod decoder;
+#[cfg(feature = "decoder-stats")]
+mod decoder_stats;
+#[cfg(feature = "full-decoder")]
+mod decorators;
+#[cfg(all(feature = "std", feature = "formatter"))]
+pub mod diff;
+#[cfg(all(feature = "formatter", feature = "alloc"))]
+mod disasm;
 #[cfg(feature = "encoder")]
 mod encoder;
 mod enums;
+mod faults;
 pub mod ffi;
+mod flow;
 #[cfg(feature = "formatter")]
 mod formatter;
+#[cfg(feature = "encoder")]
+mod forms;
+#[cfg(all(feature = "std", feature = "formatter"))]
+pub mod gadgets;
+#[cfg(feature = "encoder")]
+mod generator;
+#[cfg(all(feature = "std", feature = "formatter"))]
+pub mod golden;
+mod hex;
+#[cfg(all(feature = "std", feature = "formatter"))]
+pub mod html;
+mod limits;
+#[cfg(all(feature = "std", feature = "formatter"))]
+pub mod listing;
+#[cfg(feature = "alloc")]
+mod lookahead;
+mod memory;
+#[cfg(all(feature = "alloc", feature = "full-decoder"))]
+mod pool;
+mod register_state;
+#[cfg(feature = "encoder")]
+mod rename;
+#[cfg(feature = "alloc")]
+mod shingled;
+mod signature;
+#[cfg(all(feature = "alloc", feature = "full-decoder"))]
+mod stack_strings;
+#[cfg(feature = "std")]
+mod stats;
 
+pub use abi::*;
+pub use addr_expr::*;
+#[cfg(feature = "alloc")]
+pub use address_space::*;
+#[cfg(feature = "alloc")]
+pub use annotations::*;
+pub use arch::*;
+#[cfg(feature = "bumpalo")]
+pub use arena::*;
+#[cfg(feature = "alloc")]
+pub use boundaries::*;
+#[cfg(all(feature = "std", feature = "full-decoder"))]
+pub use cache::*;
+#[cfg(feature = "std")]
+pub use classify::*;
+#[cfg(all(feature = "alloc", feature = "full-decoder"))]
+pub use code_view::*;
+pub use compat::*;
+#[cfg(all(feature = "alloc", feature = "full-decoder"))]
+pub use constants::*;
+#[cfg(feature = "alloc")]
+pub use coverage::*;
 pub use decoder::*;
+#[cfg(feature = "decoder-stats")]
+pub use decoder_stats::*;
+#[cfg(feature = "full-decoder")]
+pub use decorators::*;
+#[cfg(all(feature = "formatter", feature = "alloc"))]
+pub use disasm::*;
 #[cfg(feature = "encoder")]
 pub use encoder::*;
 pub use enums::*;
+pub use faults::*;
+pub use flow::*;
 #[cfg(feature = "formatter")]
 pub use formatter::*;
+#[cfg(feature = "encoder")]
+pub use forms::*;
+#[cfg(feature = "encoder")]
+pub use generator::*;
+pub use hex::*;
+pub use limits::*;
+#[cfg(feature = "alloc")]
+pub use lookahead::*;
+pub use memory::*;
+#[cfg(all(feature = "alloc", feature = "full-decoder"))]
+pub use pool::*;
+pub use register_state::*;
+#[cfg(feature = "encoder")]
+pub use rename::*;
+#[cfg(feature = "alloc")]
+pub use shingled::*;
+pub use signature::*;
+#[cfg(all(feature = "alloc", feature = "full-decoder"))]
+pub use stack_strings::*;
+#[cfg(feature = "std")]
+pub use stats::*;
 pub use status::*;
 
 /// Returns the version of the zydis C library as a quadruple
@@ -54,3 +172,28 @@ pub fn version() -> (u16, u16, u16, u16) {
 pub fn get_version() -> (u16, u16, u16, u16) {
     version()
 }
+
+// All wrapper types without user-provided callbacks can be shared between
+// threads.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<ffi::Decoder>();
+    assert_send_sync::<ffi::DecoderContext>();
+    assert_send_sync::<Decoder>();
+    assert_send_sync::<MinimalDecoder>();
+    assert_send_sync::<MinimalInstruction>();
+    assert_send_sync::<Instruction<NoOperands>>();
+    assert_send_sync::<InstructionIter<'static, 'static, NoOperands>>();
+    assert_send_sync::<MemoryInstructionIter<'static, &[u8], NoOperands>>();
+    #[cfg(feature = "full-decoder")]
+    assert_send_sync::<Instruction<AllOperands>>();
+    #[cfg(feature = "formatter")]
+    {
+        assert_send_sync::<ffi::Formatter>();
+        #[cfg(not(feature = "alloc"))]
+        assert_send_sync::<Formatter<*mut ()>>();
+    }
+    #[cfg(feature = "encoder")]
+    assert_send_sync::<EncoderRequest>();
+};
@@ -0,0 +1,188 @@
+//! Decoding with limits and explicit termination reasons.
+
+use crate::*;
+
+/// Why a [`BoundedInstructionIter`] stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// All bytes were decoded, or the last instruction is cut off by the end
+    /// of the buffer.
+    EndOfBuffer,
+    /// The maximum number of instructions or bytes was reached.
+    Limit,
+    /// An instruction matched the stop predicate.
+    StopPredicate,
+    /// Decoding failed with the given status.
+    Error(Status),
+}
+
+impl<'decoder, 'buffer, O: Operands> InstructionIter<'decoder, 'buffer, O> {
+    /// Limits decoding, recording why it stopped.
+    ///
+    /// Without further configuration, the returned iterator decodes like
+    /// this one, except that it ends after the first error.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // push rbp; mov rbp, rsp; pop rbp; ret; int3
+    /// static CODE: &[u8] = &[0x55, 0x48, 0x89, 0xE5, 0x5D, 0xC3, 0xCC];
+    /// let decoder = Decoder::new64();
+    ///
+    /// let mut iter = decoder
+    ///     .decode_all::<NoOperands>(CODE, 0x1000)
+    ///     .bounded()
+    ///     .max_instructions(16)
+    ///     .stop_at(|insn| insn.mnemonic == Mnemonic::RET);
+    /// assert_eq!(iter.by_ref().count(), 4);
+    /// assert_eq!(iter.stop_reason(), Some(StopReason::StopPredicate));
+    ///
+    /// let mut iter = decoder.decode_all::<NoOperands>(CODE, 0x1000).bounded().max_bytes(5);
+    /// assert_eq!(iter.by_ref().count(), 3);
+    /// assert_eq!(iter.stop_reason(), Some(StopReason::Limit));
+    ///
+    /// // The instruction exceeding the limit is left for the inner iterator.
+    /// let mut rest = iter.into_inner();
+    /// assert_eq!(rest.current_ip(), 0x1005);
+    /// assert_eq!(rest.next().unwrap().unwrap().2.mnemonic, Mnemonic::RET);
+    ///
+    /// let mut iter = decoder.decode_all::<NoOperands>(&CODE[..3], 0x1000).bounded();
+    /// assert_eq!(iter.by_ref().count(), 1);
+    /// assert_eq!(iter.stop_reason(), Some(StopReason::EndOfBuffer));
+    /// ```
+    pub fn bounded(self) -> BoundedInstructionIter<'decoder, 'buffer, O> {
+        BoundedInstructionIter {
+            iter: self,
+            max_instructions: None,
+            max_bytes: None,
+            stop_at: None,
+            instructions: 0,
+            bytes: 0,
+            stop_reason: None,
+        }
+    }
+}
+
+/// Iterator created by [`InstructionIter::bounded`].
+#[derive(Clone)]
+pub struct BoundedInstructionIter<'decoder, 'buffer, O: Operands, P = fn(&Instruction<O>) -> bool> {
+    iter: InstructionIter<'decoder, 'buffer, O>,
+    max_instructions: Option<usize>,
+    max_bytes: Option<usize>,
+    stop_at: Option<P>,
+    instructions: usize,
+    bytes: usize,
+    stop_reason: Option<StopReason>,
+}
+
+impl<'decoder, 'buffer, O: Operands, P> BoundedInstructionIter<'decoder, 'buffer, O, P>
+where
+    P: FnMut(&Instruction<O>) -> bool,
+{
+    /// Stops after `max_instructions` instructions.
+    pub fn max_instructions(mut self, max_instructions: usize) -> Self {
+        self.max_instructions = Some(max_instructions);
+        self
+    }
+
+    /// Stops before the first instruction that doesn't end within the first
+    /// `max_bytes` bytes.
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Stops after the first instruction matching `predicate`, e.g. a
+    /// return. The matching instruction is still yielded.
+    pub fn stop_at<Q>(self, predicate: Q) -> BoundedInstructionIter<'decoder, 'buffer, O, Q>
+    where
+        Q: FnMut(&Instruction<O>) -> bool,
+    {
+        BoundedInstructionIter {
+            iter: self.iter,
+            max_instructions: self.max_instructions,
+            max_bytes: self.max_bytes,
+            stop_at: Some(predicate),
+            instructions: self.instructions,
+            bytes: self.bytes,
+            stop_reason: self.stop_reason,
+        }
+    }
+
+    /// Returns why the iterator stopped, or `None` if it didn't stop yet.
+    #[inline]
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        self.stop_reason
+    }
+
+    /// Returns the number of bytes decoded so far.
+    #[inline]
+    pub fn bytes_decoded(&self) -> usize {
+        self.bytes
+    }
+
+    /// Returns the inner iterator, positioned after the last yielded
+    /// instruction, e.g. to resume decoding after a limit was reached.
+    #[inline]
+    pub fn into_inner(self) -> InstructionIter<'decoder, 'buffer, O> {
+        self.iter
+    }
+
+    fn stop(&mut self, reason: StopReason) {
+        self.stop_reason = Some(reason);
+    }
+}
+
+impl<'buffer, O: Operands, P> Iterator for BoundedInstructionIter<'_, 'buffer, O, P>
+where
+    P: FnMut(&Instruction<O>) -> bool,
+{
+    type Item = Result<(u64, &'buffer [u8], Instruction<O>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stop_reason.is_some() {
+            return None;
+        }
+        if self
+            .max_instructions
+            .is_some_and(|max| self.instructions >= max)
+        {
+            self.stop(StopReason::Limit);
+            return None;
+        }
+
+        // Decode ahead, so the inner iterator stays at an instruction that
+        // exceeds the byte limit.
+        let mut iter = self.iter.clone();
+        let (ip, bytes, insn) = match iter.next() {
+            Some(Ok(item)) => item,
+            Some(Err(e)) => {
+                self.stop(StopReason::Error(e));
+                return Some(Err(e));
+            }
+            None => {
+                self.stop(StopReason::EndOfBuffer);
+                return None;
+            }
+        };
+
+        if self
+            .max_bytes
+            .is_some_and(|max| self.bytes + bytes.len() > max)
+        {
+            self.stop(StopReason::Limit);
+            return None;
+        }
+
+        self.iter = iter;
+        self.instructions += 1;
+        self.bytes += bytes.len();
+        if let Some(predicate) = &mut self.stop_at {
+            if predicate(&insn) {
+                self.stop(StopReason::StopPredicate);
+            }
+        }
+
+        Some(Ok((ip, bytes, insn)))
+    }
+}
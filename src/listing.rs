@@ -0,0 +1,267 @@
+//! Human-readable disassembly listings.
+
+use crate::{formatter::with_scratch_buffer, *};
+use core::fmt::Write as _;
+use std::{io, string::String};
+
+/// Layout options for [`hexdump_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexdumpOptions {
+    /// Number of instruction bytes shown per row.
+    ///
+    /// Bytes of longer instructions continue on additional rows.
+    pub bytes_per_line: usize,
+    /// Minimum number of hex digits used for addresses.
+    pub address_digits: usize,
+}
+
+impl Default for HexdumpOptions {
+    fn default() -> Self {
+        Self {
+            bytes_per_line: 8,
+            address_digits: 16,
+        }
+    }
+}
+
+/// Writes an `objdump`-style dump of `bytes` with default options.
+///
+/// See [`hexdump_with`].
+pub fn hexdump<UserData, W: io::Write>(
+    decoder: &Decoder,
+    formatter: &Formatter<UserData>,
+    bytes: &[u8],
+    base: u64,
+    out: W,
+) -> io::Result<()> {
+    hexdump_with(
+        decoder,
+        formatter,
+        bytes,
+        base,
+        &HexdumpOptions::default(),
+        out,
+    )
+}
+
+/// Writes an `objdump`-style dump of `bytes`, showing the address, the raw
+/// bytes and the formatted instruction side by side.
+///
+/// `base` is the address of the first byte. Bytes that don't decode to a
+/// valid instruction are shown one at a time as `(bad)`.
+///
+/// # Examples
+/// ```
+/// # use zydis::{*, listing::*};
+/// // push rcx; mov eax, 0x12345678; int3
+/// static CODE: &[u8] = &[0x51, 0xB8, 0x78, 0x56, 0x34, 0x12, 0xCC];
+/// let options = HexdumpOptions {
+///     bytes_per_line: 4,
+///     address_digits: 8,
+/// };
+///
+/// let mut out = Vec::new();
+/// hexdump_with(
+///     &Decoder::new64(),
+///     &Formatter::intel(),
+///     CODE,
+///     0x1000,
+///     &options,
+///     &mut out,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     concat!(
+///         "00001000  51           push rcx\n",
+///         "00001001  B8 78 56 34  mov eax, 0x12345678\n",
+///         "00001005  12\n",
+///         "00001006  CC           int3\n",
+///     )
+/// );
+/// ```
+pub fn hexdump_with<UserData, W: io::Write>(
+    decoder: &Decoder,
+    formatter: &Formatter<UserData>,
+    bytes: &[u8],
+    base: u64,
+    options: &HexdumpOptions,
+    out: W,
+) -> io::Result<()> {
+    hexdump_annotated(
+        decoder,
+        formatter,
+        bytes,
+        base,
+        options,
+        &AnnotationMap::<()>::new(),
+        out,
+    )
+}
+
+/// Writes an `objdump`-style dump of `bytes` like [`hexdump_with`],
+/// interleaving the labels and comments from `annotations`.
+///
+/// Labels are written on their own line before the instruction, comments
+/// are appended to the instruction after a `;`.
+///
+/// # Examples
+/// ```
+/// # use zydis::{*, listing::*};
+/// // push rcx; int3
+/// static CODE: &[u8] = &[0x51, 0xCC];
+/// let options = HexdumpOptions {
+///     bytes_per_line: 2,
+///     address_digits: 4,
+/// };
+///
+/// let mut annotations = AnnotationMap::<()>::new();
+/// annotations.set_label(0x1000, "main");
+/// annotations.add_comment(0x1001, "unreachable");
+///
+/// let mut out = Vec::new();
+/// hexdump_annotated(
+///     &Decoder::new64(),
+///     &Formatter::intel(),
+///     CODE,
+///     0x1000,
+///     &options,
+///     &annotations,
+///     &mut out,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     concat!(
+///         "main:\n",
+///         "1000  51     push rcx\n",
+///         "1001  CC     int3  ; unreachable\n",
+///     )
+/// );
+/// ```
+pub fn hexdump_annotated<UserData, T, W: io::Write>(
+    decoder: &Decoder,
+    formatter: &Formatter<UserData>,
+    mut bytes: &[u8],
+    base: u64,
+    options: &HexdumpOptions,
+    annotations: &AnnotationMap<T>,
+    mut out: W,
+) -> io::Result<()> {
+    let per_line = options.bytes_per_line.max(1);
+    let column_width = per_line.saturating_mul(3) - 1;
+    let mut ip = base;
+    let mut line = String::new();
+
+    while !bytes.is_empty() {
+        let insn = decoder
+            .decode_first::<VisibleOperands>(bytes)
+            .ok()
+            .flatten();
+        let length = insn.as_ref().map_or(1, |insn| usize::from(insn.length));
+        let (insn_bytes, rest) = bytes.split_at(length);
+
+        if let Some(label) = annotations.label(ip) {
+            writeln!(out, "{label}:")?;
+        }
+
+        for (row, chunk) in insn_bytes.chunks(per_line).enumerate() {
+            line.clear();
+            write_hex_u64_padded(
+                &mut line,
+                ip.wrapping_add((row * per_line) as u64),
+                options.address_digits,
+            )
+            .and_then(|_| line.write_str("  "))
+            .and_then(|_| write_hex_bytes(&mut line, chunk))
+            .map_err(fmt_to_io)?;
+
+            if row == 0 {
+                let padding = column_width - (chunk.len() * 3 - 1);
+                line.extend(core::iter::repeat_n(' ', padding + 2));
+
+                match &insn {
+                    Some(insn) => with_scratch_buffer(|buffer| {
+                        formatter.format_ex(Some(ip), insn, buffer, None)?;
+                        line.push_str(buffer.as_str()?);
+                        Ok(())
+                    })
+                    .map_err(|e: Status| io::Error::other(e))?,
+                    None => line.push_str("(bad)"),
+                }
+
+                let comments = annotations.comments(ip);
+                if !comments.is_empty() {
+                    line.push_str("  ; ");
+                    line.push_str(&comments.join("; "));
+                }
+            }
+
+            line.push('\n');
+            out.write_all(line.as_bytes())?;
+        }
+
+        bytes = rest;
+        ip = ip.wrapping_add(length as u64);
+    }
+
+    Ok(())
+}
+
+/// Writes an `objdump`-style dump of `bytes` like [`hexdump_with`], with the
+/// hit count of every instruction from `coverage`.
+///
+/// # Examples
+/// ```
+/// # use zydis::{*, listing::*};
+/// // push rcx; int3
+/// static CODE: &[u8] = &[0x51, 0xCC];
+/// let options = HexdumpOptions {
+///     bytes_per_line: 2,
+///     address_digits: 4,
+/// };
+///
+/// let mut coverage = Coverage::new([(0x1000, 1), (0x1001, 1)]);
+/// coverage.record_all([0x1000, 0x1000]);
+///
+/// let mut out = Vec::new();
+/// hexdump_coverage(
+///     &Decoder::new64(),
+///     &Formatter::intel(),
+///     CODE,
+///     0x1000,
+///     &options,
+///     &coverage,
+///     &mut out,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     concat!(
+///         "1000  51     push rcx  ; hits: 2\n",
+///         "1001  CC     int3  ; hits: 0\n",
+///     )
+/// );
+/// ```
+pub fn hexdump_coverage<UserData, W: io::Write>(
+    decoder: &Decoder,
+    formatter: &Formatter<UserData>,
+    bytes: &[u8],
+    base: u64,
+    options: &HexdumpOptions,
+    coverage: &Coverage,
+    out: W,
+) -> io::Result<()> {
+    hexdump_annotated(
+        decoder,
+        formatter,
+        bytes,
+        base,
+        options,
+        &coverage.annotations(),
+        out,
+    )
+}
@@ -0,0 +1,109 @@
+//! Decoding with lookahead, e.g. for matching instruction sequences.
+
+use crate::*;
+use alloc::{collections::VecDeque, vec::Vec};
+
+impl<'decoder, 'buffer, O: Operands + Clone> InstructionIter<'decoder, 'buffer, O> {
+    /// Yields every instruction together with up to `successors` decoded
+    /// instructions following it.
+    ///
+    /// Windows near the end of the buffer (or before a decoding error) have
+    /// fewer successors. A decoding error is reported once, after the window
+    /// of the last valid instruction, and ends the iteration.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // cmp eax, 1; jz +2; nop; nop
+    /// static CODE: &[u8] = &[0x83, 0xF8, 0x01, 0x74, 0x02, 0x90, 0x90];
+    ///
+    /// let decoder = Decoder::new64();
+    /// let compare_and_branch: Vec<_> = decoder
+    ///     .decode_all::<NoOperands>(CODE, 0x1000)
+    ///     .window(1)
+    ///     .map(|window| window.unwrap())
+    ///     .filter(|window| {
+    ///         window.current().insn.mnemonic == Mnemonic::CMP
+    ///             && window.successors().first().map_or(false, |next| {
+    ///                 next.insn.meta.category == InstructionCategory::COND_BR
+    ///             })
+    ///     })
+    ///     .map(|window| window.current().ip)
+    ///     .collect();
+    ///
+    /// assert_eq!(compare_and_branch, [0x1000]);
+    /// ```
+    pub fn window(self, successors: usize) -> InstructionWindows<'decoder, 'buffer, O> {
+        InstructionWindows {
+            iter: self,
+            pending: VecDeque::with_capacity(successors + 1),
+            error: None,
+            exhausted: false,
+            size: successors + 1,
+        }
+    }
+}
+
+/// Iterator created by [`InstructionIter::window`].
+pub struct InstructionWindows<'decoder, 'buffer, O: Operands> {
+    iter: InstructionIter<'decoder, 'buffer, O>,
+    pending: VecDeque<DecodedItem<'buffer, O>>,
+    error: Option<Status>,
+    exhausted: bool,
+    size: usize,
+}
+
+impl<'buffer, O: Operands + Clone> Iterator for InstructionWindows<'_, 'buffer, O> {
+    type Item = Result<InstructionWindow<'buffer, O>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.exhausted && self.pending.len() < self.size {
+            match self.iter.next() {
+                Some(Ok(item)) => self.pending.push_back(item.into()),
+                Some(Err(e)) => {
+                    self.error = Some(e);
+                    self.exhausted = true;
+                }
+                None => self.exhausted = true,
+            }
+        }
+
+        let Some(current) = self.pending.pop_front() else {
+            return self.error.take().map(Err);
+        };
+
+        Some(Ok(InstructionWindow {
+            current,
+            successors: self.pending.iter().cloned().collect(),
+        }))
+    }
+}
+
+/// An instruction together with the instructions following it, see
+/// [`InstructionIter::window`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InstructionWindow<'buffer, O: Operands> {
+    current: DecodedItem<'buffer, O>,
+    successors: Vec<DecodedItem<'buffer, O>>,
+}
+
+impl<'buffer, O: Operands> InstructionWindow<'buffer, O> {
+    /// The current instruction.
+    #[inline]
+    pub fn current(&self) -> &DecodedItem<'buffer, O> {
+        &self.current
+    }
+
+    /// The decoded instructions directly following the current one, in
+    /// order.
+    #[inline]
+    pub fn successors(&self) -> &[DecodedItem<'buffer, O>] {
+        &self.successors
+    }
+
+    /// Iterates the current instruction and its successors.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &DecodedItem<'buffer, O>> {
+        core::iter::once(&self.current).chain(&self.successors)
+    }
+}
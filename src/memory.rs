@@ -0,0 +1,157 @@
+//! Decoding from address spaces that aren't a single contiguous buffer.
+
+use crate::*;
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::marker::PhantomData;
+
+/// A source of code bytes, e.g. the guest memory of an emulator or the
+/// address space of a debugged process.
+pub trait Memory {
+    /// Reads bytes starting at `addr` into `buf`, returning the number of
+    /// bytes read.
+    ///
+    /// Short reads are allowed, e.g. at the end of a page. Reading is retried
+    /// at the following address until `buf` is full or `0` is returned, which
+    /// marks `addr` as unreadable.
+    fn read(&self, addr: u64, buf: &mut [u8]) -> Result<usize>;
+}
+
+impl<M: Memory + ?Sized> Memory for &M {
+    #[inline]
+    fn read(&self, addr: u64, buf: &mut [u8]) -> Result<usize> {
+        (**self).read(addr, buf)
+    }
+}
+
+/// A flat buffer, mapped at address `0`.
+impl Memory for [u8] {
+    fn read(&self, addr: u64, buf: &mut [u8]) -> Result<usize> {
+        let Some(src) = usize::try_from(addr).ok().and_then(|addr| self.get(addr..)) else {
+            return Ok(0);
+        };
+        let len = src.len().min(buf.len());
+        buf[..len].copy_from_slice(&src[..len]);
+        Ok(len)
+    }
+}
+
+/// Disjoint regions, keyed by their start address.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// use std::collections::BTreeMap;
+///
+/// // mov eax, 0x12345678, split across two pages.
+/// let mut memory = BTreeMap::new();
+/// memory.insert(0x0FFE, vec![0xB8, 0x78]);
+/// memory.insert(0x1000, vec![0x56, 0x34, 0x12, 0xC3]);
+///
+/// let insn = Decoder::new64()
+///     .decode_memory::<NoOperands>(&memory, 0x0FFE)
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(insn.mnemonic, Mnemonic::MOV);
+/// assert_eq!(insn.length, 5);
+/// ```
+#[cfg(feature = "alloc")]
+impl Memory for BTreeMap<u64, Vec<u8>> {
+    fn read(&self, addr: u64, buf: &mut [u8]) -> Result<usize> {
+        let Some((&start, region)) = self.range(..=addr).next_back() else {
+            return Ok(0);
+        };
+        let offset = addr - start;
+        region.read(offset, buf)
+    }
+}
+
+/// Fills `buf` from `memory`, returning the number of contiguous bytes read.
+fn read_exact_or_short<M: Memory + ?Sized>(memory: &M, addr: u64, buf: &mut [u8]) -> Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match memory.read(addr.wrapping_add(len as u64), &mut buf[len..])? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    Ok(len)
+}
+
+impl Decoder {
+    /// Decodes the instruction at `ip` in `memory`.
+    ///
+    /// Instructions spanning multiple regions (e.g. pages) are decoded as
+    /// long as all their bytes are readable. Returns `Ok(None)` if `ip`
+    /// itself isn't readable or the instruction is cut short by an unreadable
+    /// address.
+    pub fn decode_memory<O: Operands>(
+        &self,
+        memory: &(impl Memory + ?Sized),
+        ip: u64,
+    ) -> Result<Option<Instruction<O>>> {
+        let mut buf = [0; MAX_INSTRUCTION_LENGTH];
+        let len = read_exact_or_short(memory, ip, &mut buf)?;
+        self.decode_first(&buf[..len])
+    }
+
+    /// Returns an iterator decoding the instructions in `memory`, starting
+    /// at `ip`.
+    ///
+    /// The iterator stops at the first unreadable address, and after
+    /// yielding the first error.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // push rcx; lea eax, [rbp-0x01]; int3
+    /// static CODE: &[u8] = &[0x51, 0x8D, 0x45, 0xFF, 0xCC];
+    /// let decoder = Decoder::new64();
+    ///
+    /// let insns = decoder
+    ///     .decode_all_memory::<NoOperands, _>(CODE, 1)
+    ///     .map(|r| r.map(|(ip, insn)| (ip, insn.mnemonic)))
+    ///     .collect::<Result<Vec<_>>>()
+    ///     .unwrap();
+    /// assert_eq!(insns, [(1, Mnemonic::LEA), (4, Mnemonic::INT3)]);
+    /// ```
+    pub fn decode_all_memory<O: Operands, M: Memory>(
+        &self,
+        memory: M,
+        ip: u64,
+    ) -> MemoryInstructionIter<'_, M, O> {
+        MemoryInstructionIter {
+            decoder: self,
+            memory,
+            ip: Some(ip),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Iterator decoding instructions from a [`Memory`].
+///
+/// Created via [`Decoder::decode_all_memory`].
+#[derive(Clone)]
+pub struct MemoryInstructionIter<'decoder, M, O: Operands> {
+    decoder: &'decoder Decoder,
+    memory: M,
+    ip: Option<u64>,
+    _marker: PhantomData<fn() -> O>,
+}
+
+impl<M: Memory, O: Operands> Iterator for MemoryInstructionIter<'_, M, O> {
+    type Item = Result<(u64, Instruction<O>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ip = self.ip.take()?;
+        match self.decoder.decode_memory::<O>(&self.memory, ip) {
+            Ok(Some(insn)) => {
+                self.ip = Some(insn.next_ip(ip));
+                Some(Ok((ip, insn)))
+            }
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Bulk decoding into contiguous, shared storage.
+
+use crate::*;
+use alloc::vec::Vec;
+use core::ops;
+
+/// Lightweight handle to an instruction stored in a [`DecodedPool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InsnRef(u32);
+
+impl InsnRef {
+    /// Index of the instruction within its pool, in decoding order.
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PoolEntry {
+    ip: u64,
+    operands_start: usize,
+}
+
+/// Arena owning bulk-decoded instructions and their operands.
+///
+/// Instructions and operands are each stored contiguously. Instead of handing
+/// out copies of the rather large [`ffi::DecodedInstruction`] and
+/// [`ffi::DecodedOperand`] structures, the pool gives out [`InsnRef`]s that
+/// can be cheaply stored in user data structures and resolved to an
+/// [`InsnView`] via [`DecodedPool::get`].
+///
+/// All operands (visible and hidden) are decoded.
+///
+/// # Examples
+///
+/// ```
+/// # use zydis::*;
+/// // push rcx; lea eax, [rbp-0x01]; int3
+/// static CODE: &[u8] = &[0x51, 0x8D, 0x45, 0xFF, 0xCC];
+///
+/// let decoder = Decoder::new64();
+/// let mut pool = DecodedPool::new();
+/// pool.decode_all(&decoder, CODE, 0x1000).unwrap();
+///
+/// let lea = pool.iter().nth(1).unwrap().id();
+/// let insn = pool.get(lea);
+/// assert_eq!(insn.ip(), 0x1001);
+/// assert_eq!(insn.mnemonic, Mnemonic::LEA);
+/// assert_eq!(insn.visible_operands().len(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DecodedPool {
+    entries: Vec<PoolEntry>,
+    infos: Vec<ffi::DecodedInstruction>,
+    operands: Vec<ffi::DecodedOperand>,
+}
+
+impl DecodedPool {
+    /// Creates a new, empty pool.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes the first instruction in `buffer` and appends it to the pool.
+    ///
+    /// `ip` is the address recorded for the instruction.
+    pub fn push(&mut self, decoder: &Decoder, buffer: &[u8], ip: u64) -> Result<Option<InsnRef>> {
+        let Some((ctx, info)) = decoder.decode_raw(buffer)? else {
+            return Ok(None);
+        };
+
+        let id = InsnRef(u32::try_from(self.infos.len()).map_err(|_| Status::OutOfRange)?);
+        let operands_start = self.operands.len();
+        let operand_count = usize::from(info.operand_count);
+        self.operands.reserve(operand_count);
+
+        unsafe {
+            ffi::ZydisDecoderDecodeOperands(
+                decoder.raw(),
+                &ctx,
+                &info,
+                self.operands.as_mut_ptr().add(operands_start),
+                info.operand_count,
+            )
+            .as_result()?;
+            for index in operands_start..operands_start + operand_count {
+                ffi::DecodedOperand::check_variants(self.operands.as_ptr().add(index))?;
+            }
+            self.operands.set_len(operands_start + operand_count);
+        }
+
+        self.entries.push(PoolEntry { ip, operands_start });
+        self.infos.push(info);
+        Ok(Some(id))
+    }
+
+    /// Decodes all instructions in `buffer` and appends them to the pool.
+    ///
+    /// Returns the number of instructions added. On error, instructions
+    /// decoded up to that point remain in the pool.
+    pub fn decode_all(
+        &mut self,
+        decoder: &Decoder,
+        mut buffer: &[u8],
+        mut ip: u64,
+    ) -> Result<usize> {
+        let mut count = 0;
+        while let Some(id) = self.push(decoder, buffer, ip)? {
+            let length = self.infos[id.index()].length;
+            buffer = &buffer[usize::from(length)..];
+            ip += u64::from(length);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Resolves an instruction handle.
+    ///
+    /// # Panics
+    ///
+    /// If `id` doesn't belong to this pool. See [`DecodedPool::try_get`] for a
+    /// non-panicking version.
+    #[inline]
+    #[allow(clippy::expect_used)]
+    pub fn get(&self, id: InsnRef) -> InsnView<'_> {
+        self.try_get(id).expect("instruction handle out of bounds")
+    }
+
+    /// Resolves an instruction handle, returning `None` if `id` doesn't
+    /// belong to this pool.
+    #[inline]
+    pub fn try_get(&self, id: InsnRef) -> Option<InsnView<'_>> {
+        (id.index() < self.infos.len()).then_some(InsnView { pool: self, id })
+    }
+
+    /// Returns an iterator over all instructions, in decoding order.
+    #[inline]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = InsnView<'_>> + '_ {
+        (0..self.infos.len() as u32).map(move |idx| InsnView {
+            pool: self,
+            id: InsnRef(idx),
+        })
+    }
+
+    /// Number of instructions in the pool.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.infos.len()
+    }
+
+    /// Whether the pool is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.infos.is_empty()
+    }
+
+    /// Removes all instructions, keeping the allocated storage.
+    ///
+    /// Previously handed out [`InsnRef`]s must no longer be used.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.infos.clear();
+        self.operands.clear();
+    }
+}
+
+/// Borrowed view of an instruction stored in a [`DecodedPool`].
+///
+/// Instruction information can be accessed via [`core::ops::Deref`].
+#[derive(Debug, Clone, Copy)]
+pub struct InsnView<'pool> {
+    pool: &'pool DecodedPool,
+    id: InsnRef,
+}
+
+impl<'pool> InsnView<'pool> {
+    /// The handle of this instruction.
+    #[inline]
+    pub fn id(&self) -> InsnRef {
+        self.id
+    }
+
+    /// The address recorded for this instruction.
+    #[inline]
+    pub fn ip(&self) -> u64 {
+        self.pool.entries[self.id.index()].ip
+    }
+
+    /// The basic instruction info.
+    #[inline]
+    pub fn info(&self) -> &'pool ffi::DecodedInstruction {
+        &self.pool.infos[self.id.index()]
+    }
+
+    /// All (visible and hidden) operands.
+    #[inline]
+    pub fn operands(&self) -> &'pool [ffi::DecodedOperand] {
+        let start = self.pool.entries[self.id.index()].operands_start;
+        &self.pool.operands[start..start + usize::from(self.info().operand_count)]
+    }
+
+    /// The visible operands.
+    #[inline]
+    pub fn visible_operands(&self) -> &'pool [ffi::DecodedOperand] {
+        &self.operands()[..usize::from(self.info().operand_count_visible)]
+    }
+}
+
+impl ops::Deref for InsnView<'_> {
+    type Target = ffi::DecodedInstruction;
+
+    fn deref(&self) -> &Self::Target {
+        self.info()
+    }
+}
@@ -0,0 +1,109 @@
+//! Pluggable register state for resolving runtime addresses.
+
+use crate::*;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+/// A source of runtime register values, e.g. an emulator or debugger.
+///
+/// Used by [`ffi::DecodedInstruction::calc_absolute_address_with`] to resolve
+/// the registers an operand depends on. Implemented for closures, so most
+/// backends can be plugged in without a wrapper type.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// let state = |reg| match reg {
+///     Register::RAX => Some(0x1000),
+///     _ => None,
+/// };
+/// assert_eq!(state.read(Register::RAX), Some(0x1000));
+/// assert_eq!(state.read(Register::RBX), None);
+/// ```
+pub trait RegisterState {
+    /// Returns the current value of `reg`, or `None` if it's unknown.
+    fn read(&self, reg: Register) -> Option<u64>;
+}
+
+impl<F: Fn(Register) -> Option<u64>> RegisterState for F {
+    #[inline]
+    fn read(&self, reg: Register) -> Option<u64> {
+        self(reg)
+    }
+}
+
+impl RegisterState for ffi::RegisterContext {
+    #[inline]
+    fn read(&self, reg: Register) -> Option<u64> {
+        Some(self.get(reg))
+    }
+}
+
+#[cfg(feature = "std")]
+impl RegisterState for HashMap<Register, u64> {
+    #[inline]
+    fn read(&self, reg: Register) -> Option<u64> {
+        self.get(&reg).copied()
+    }
+}
+
+impl ffi::DecodedInstruction {
+    /// Behaves like `calc_absolute_address_ex`, but reads the registers the
+    /// operand depends on from `state`.
+    ///
+    /// Returns [`Status::NotFound`] if `state` doesn't know the value of a
+    /// required register.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // jmp qword ptr [rax+rbx*4]
+    /// static CODE: &[u8] = &[0xFF, 0x24, 0x98];
+    /// let insn = Decoder::new64()
+    ///     .decode_first::<VisibleOperands>(CODE)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let operand = &insn.operands()[0];
+    ///
+    /// let state = |reg| match reg {
+    ///     Register::RAX => Some(0x1000),
+    ///     Register::RBX => Some(3),
+    ///     _ => None,
+    /// };
+    /// assert_eq!(insn.calc_absolute_address_with(0, operand, &state), Ok(0x100C));
+    ///
+    /// let partial = |reg| (reg == Register::RAX).then_some(0x1000);
+    /// assert_eq!(
+    ///     insn.calc_absolute_address_with(0, operand, &partial),
+    ///     Err(Status::NotFound)
+    /// );
+    /// ```
+    pub fn calc_absolute_address_with<S: RegisterState + ?Sized>(
+        &self,
+        address: u64,
+        operand: &ffi::DecodedOperand,
+        state: &S,
+    ) -> Result<u64> {
+        let mut context = ffi::RegisterContext::new();
+        let mut load = |reg: Register| -> Result<()> {
+            if !matches!(
+                reg,
+                Register::NONE | Register::RIP | Register::EIP | Register::IP
+            ) {
+                context.values[reg as usize] = state.read(reg).ok_or(Status::NotFound)?;
+            }
+            Ok(())
+        };
+
+        match &operand.kind {
+            ffi::DecodedOperandKind::Reg(reg) => load(*reg)?,
+            ffi::DecodedOperandKind::Mem(mem) => {
+                load(mem.base)?;
+                load(mem.index)?;
+            }
+            _ => (),
+        }
+
+        self.calc_absolute_address_ex(address, operand, &context)
+    }
+}
@@ -0,0 +1,214 @@
+//! Renaming of registers in existing code.
+
+use crate::{asm::nop_fill, *};
+use alloc::vec::Vec;
+
+/// Why an instruction couldn't be rewritten by
+/// [`Decoder::replace_register`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplacementError {
+    /// The instruction accesses the register implicitly, e.g. `rax` in
+    /// `mul rcx`.
+    Implicit,
+    /// The replacement has no counterpart of the same class as the given
+    /// register, e.g. there's no high byte register for `r8`.
+    NoCounterpart(Register),
+    /// The rewritten instruction can't be encoded, or is longer than the
+    /// original one ([`Status::InstructionTooLong`]).
+    Encoding(Status),
+}
+
+/// An instruction [`Decoder::replace_register`] left unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplacementFailure {
+    /// Address of the instruction.
+    pub ip: u64,
+    /// Why the register couldn't be replaced.
+    pub reason: ReplacementError,
+}
+
+/// Result of [`Decoder::replace_register`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterReplacement {
+    /// The rewritten code, with the same length and instruction addresses
+    /// as the original.
+    pub code: Vec<u8>,
+    /// Addresses of the rewritten instructions.
+    pub rewritten: Vec<u64>,
+    /// Instructions using the register that were left unchanged.
+    pub failures: Vec<ReplacementFailure>,
+}
+
+impl RegisterReplacement {
+    /// Checks whether every use of the register was replaced.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Maps registers overlapping `from` to the matching register overlapping
+/// `to`.
+struct Renaming {
+    machine_mode: MachineMode,
+    from: Register,
+    to: Register,
+}
+
+impl Renaming {
+    fn affects(&self, reg: Register) -> bool {
+        reg != Register::NONE && reg.largest_enclosing(self.machine_mode) == self.from
+    }
+
+    /// Returns the register of the same class as `reg` overlapping `to`.
+    fn rename(&self, reg: Register) -> core::result::Result<Register, ReplacementError> {
+        if !self.affects(reg) {
+            return Ok(reg);
+        }
+
+        let is_high_byte = |reg: Register| {
+            matches!(
+                reg,
+                Register::AH | Register::CH | Register::DH | Register::BH
+            )
+        };
+        reg.class()
+            .registers()
+            .find(|&other| {
+                other.largest_enclosing(self.machine_mode) == self.to
+                    && is_high_byte(other) == is_high_byte(reg)
+            })
+            .ok_or(ReplacementError::NoCounterpart(reg))
+    }
+
+    fn rewrite(&self, insn: &FullInstruction) -> core::result::Result<Vec<u8>, ReplacementError> {
+        let hidden = &insn.operands()[insn.visible_operands().len()..];
+        if hidden.iter().any(|op| self.references(op)) {
+            return Err(ReplacementError::Implicit);
+        }
+
+        let mut request =
+            EncoderRequest::from_instruction(insn).map_err(ReplacementError::Encoding)?;
+        for op in request.operands_mut() {
+            match op.ty {
+                OperandType::REGISTER => op.reg.value = self.rename(op.reg.value)?,
+                OperandType::MEMORY => {
+                    op.mem.base = self.rename(op.mem.base)?;
+                    op.mem.index = self.rename(op.mem.index)?;
+                }
+                _ => {}
+            }
+        }
+
+        request
+            .encode_with_max_len(usize::from(insn.length))
+            .map_err(ReplacementError::Encoding)
+    }
+
+    fn references(&self, op: &ffi::DecodedOperand) -> bool {
+        match &op.kind {
+            ffi::DecodedOperandKind::Reg(reg) => self.affects(*reg),
+            ffi::DecodedOperandKind::Mem(mem) => self.affects(mem.base) || self.affects(mem.index),
+            _ => false,
+        }
+    }
+}
+
+impl Decoder {
+    /// Replaces every use of `from` in `code` by `to`, including sub-register
+    /// forms, e.g. `eax` by `edx` when replacing `rax` by `rdx`.
+    ///
+    /// Instructions are decoded linearly, starting at `base`, substituted in
+    /// all register and memory address operands and re-encoded. To keep
+    /// all instruction addresses and relative offsets valid, rewritten
+    /// instructions must not be longer than the original ones and shorter
+    /// ones are preceded by `nop`s. Instructions that can't be rewritten
+    /// are left unchanged and reported. Undecodable bytes are copied as is
+    /// and segment registers in memory operands aren't replaced.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // mov eax, [rax+0x08]; add al, 0x01; mul rcx
+    /// static CODE: &[u8] = &[0x8B, 0x40, 0x08, 0x04, 0x01, 0x48, 0xF7, 0xE1];
+    ///
+    /// let result = Decoder::new64().replace_register(CODE, 0x1000, Register::RAX, Register::RDX);
+    ///
+    /// // mov edx, [rdx+0x08]; add al, 0x01; mul rcx
+    /// assert_eq!(result.code, [0x8B, 0x52, 0x08, 0x04, 0x01, 0x48, 0xF7, 0xE1]);
+    /// assert_eq!(result.rewritten, [0x1000]);
+    /// assert_eq!(
+    ///     result.failures,
+    ///     [
+    ///         // add dl, 0x01 has no two byte encoding
+    ///         ReplacementFailure {
+    ///             ip: 0x1003,
+    ///             reason: ReplacementError::Encoding(Status::InstructionTooLong),
+    ///         },
+    ///         // mul implicitly uses rax
+    ///         ReplacementFailure {
+    ///             ip: 0x1005,
+    ///             reason: ReplacementError::Implicit,
+    ///         },
+    ///     ]
+    /// );
+    /// ```
+    pub fn replace_register(
+        &self,
+        code: &[u8],
+        base: u64,
+        from: Register,
+        to: Register,
+    ) -> RegisterReplacement {
+        let machine_mode = self.machine_mode();
+        let renaming = Renaming {
+            machine_mode,
+            from: from.largest_enclosing(machine_mode),
+            to: to.largest_enclosing(machine_mode),
+        };
+
+        let mut result = RegisterReplacement {
+            code: code.to_vec(),
+            rewritten: Vec::new(),
+            failures: Vec::new(),
+        };
+        if renaming.from == renaming.to {
+            return result;
+        }
+
+        let mut offset = 0;
+        while offset < code.len() {
+            let ip = base.wrapping_add(offset as u64);
+            let Ok(Some(insn)) = self.decode_first::<AllOperands>(&code[offset..]) else {
+                offset += 1;
+                continue;
+            };
+            let length = usize::from(insn.length);
+            if !insn.operands().iter().any(|op| renaming.references(op)) {
+                offset += length;
+                continue;
+            }
+
+            match renaming.rewrite(&insn) {
+                Ok(bytes) => {
+                    let slot = &mut result.code[offset..offset + length];
+                    let (padding, rest) = slot.split_at_mut(length - bytes.len());
+                    match nop_fill(padding) {
+                        Ok(()) => {
+                            rest.copy_from_slice(&bytes);
+                            result.rewritten.push(ip);
+                        }
+                        Err(status) => result.failures.push(ReplacementFailure {
+                            ip,
+                            reason: ReplacementError::Encoding(status),
+                        }),
+                    }
+                }
+                Err(reason) => result.failures.push(ReplacementFailure { ip, reason }),
+            }
+            offset += length;
+        }
+
+        result
+    }
+}
@@ -0,0 +1,110 @@
+//! Decoding at every byte offset of a buffer.
+
+use crate::*;
+use alloc::vec::Vec;
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+impl Decoder {
+    /// Decodes an instruction at every byte offset of `buffer`.
+    ///
+    /// This is the starting point for analyses that can't rely on a linear
+    /// sweep, like finding gadgets or instructions hidden inside other
+    /// instructions. Offsets where decoding fails are recorded as invalid.
+    /// `base` is the address of the first byte.
+    ///
+    /// Operands are never decoded, so the cost per offset is that of
+    /// [`Decoder::decode_first`] with [`NoOperands`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// // mov eax, 0xC35F0000 (which contains pop rdi; ret)
+    /// static CODE: &[u8] = &[0xB8, 0x00, 0x00, 0x5F, 0xC3];
+    ///
+    /// let shingles = Decoder::new64().decode_shingled(CODE, 0x1000);
+    /// assert_eq!(shingles.len(), CODE.len());
+    /// assert_eq!(shingles.get(0x1000).unwrap().mnemonic, Mnemonic::MOV);
+    /// assert_eq!(shingles.get(0x1004).unwrap().mnemonic, Mnemonic::RET);
+    ///
+    /// let hidden: Vec<_> = shingles
+    ///     .follow(0x1003)
+    ///     .map(|(ip, insn)| (ip, insn.mnemonic))
+    ///     .collect();
+    /// assert_eq!(hidden, [(0x1003, Mnemonic::POP), (0x1004, Mnemonic::RET)]);
+    /// ```
+    pub fn decode_shingled(&self, buffer: &[u8], base: u64) -> ShingledDisassembly {
+        let mut table = Vec::with_capacity(buffer.len());
+        for offset in 0..buffer.len() {
+            let entry = match self.decode_raw(&buffer[offset..]) {
+                Ok(Some((_, info))) => Some(CompactInstruction::from(&info)),
+                _ => None,
+            };
+            table.push(entry);
+        }
+
+        ShingledDisassembly { base, table }
+    }
+}
+
+/// The result of decoding at every byte offset of a buffer.
+///
+/// Created via [`Decoder::decode_shingled`].
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShingledDisassembly {
+    base: u64,
+    table: Vec<Option<CompactInstruction>>,
+}
+
+impl ShingledDisassembly {
+    /// The address of the first byte.
+    #[inline]
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    /// The number of offsets, i.e. the length of the decoded buffer.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Checks whether the decoded buffer was empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// Returns the instruction starting at `ip`, or `None` if `ip` is out of
+    /// range or doesn't decode.
+    #[inline]
+    pub fn get(&self, ip: u64) -> Option<&CompactInstruction> {
+        let offset = usize::try_from(ip.checked_sub(self.base)?).ok()?;
+        self.table.get(offset)?.as_ref()
+    }
+
+    /// Returns an iterator over all offsets that decode to an instruction,
+    /// together with their address.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &CompactInstruction)> + '_ {
+        self.table
+            .iter()
+            .enumerate()
+            .filter_map(move |(offset, insn)| Some((self.base + offset as u64, insn.as_ref()?)))
+    }
+
+    /// Returns an iterator over the instructions reached by linearly
+    /// decoding from `ip`.
+    ///
+    /// The iterator stops at the end of the buffer or at the first offset
+    /// that doesn't decode.
+    pub fn follow(&self, ip: u64) -> impl Iterator<Item = (u64, &CompactInstruction)> + '_ {
+        let mut next = Some(ip);
+        core::iter::from_fn(move || {
+            let ip = next?;
+            let insn = self.get(ip);
+            next = insn.map(|insn| ip + u64::from(insn.length));
+            Some((ip, insn?))
+        })
+    }
+}
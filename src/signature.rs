@@ -0,0 +1,212 @@
+//! Byte signatures with wildcards.
+
+/// A byte pattern where each position either matches a specific byte or any
+/// byte.
+///
+/// Signatures are usually created from IDA/x64dbg-style pattern strings via
+/// the [`pattern!`](crate::pattern) macro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Signature<'a> {
+    pattern: &'a [Option<u8>],
+}
+
+impl<'a> Signature<'a> {
+    /// Creates a signature from its positions, `None` being a wildcard.
+    ///
+    /// An empty signature never matches.
+    #[inline]
+    pub const fn new(pattern: &'a [Option<u8>]) -> Self {
+        Self { pattern }
+    }
+
+    /// The positions of this signature, `None` being a wildcard.
+    #[inline]
+    pub const fn pattern(&self) -> &'a [Option<u8>] {
+        self.pattern
+    }
+
+    /// Length of the signature in bytes.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.pattern.len()
+    }
+
+    /// Whether the signature is empty.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.pattern.is_empty()
+    }
+
+    /// Whether `bytes` starts with this signature.
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        !self.pattern.is_empty()
+            && bytes.len() >= self.pattern.len()
+            && self
+                .pattern
+                .iter()
+                .zip(bytes)
+                .all(|(expected, actual)| expected.is_none_or(|x| x == *actual))
+    }
+
+    /// Returns the offset of the first match in `haystack`.
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        self.find_all(haystack).next()
+    }
+
+    /// Returns an iterator over the offsets of all (possibly overlapping)
+    /// matches in `haystack`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// let sig = pattern!("E8 ?? ?? ?? ?? C3");
+    /// let code = [0x90, 0xE8, 0x01, 0x02, 0x03, 0x04, 0xC3];
+    ///
+    /// assert_eq!(sig.len(), 6);
+    /// assert_eq!(sig.find_all(&code).collect::<Vec<_>>(), [1]);
+    /// ```
+    pub fn find_all<'h>(&self, haystack: &'h [u8]) -> impl Iterator<Item = usize> + 'h
+    where
+        'a: 'h,
+    {
+        let this = *self;
+        let end = match self.pattern.len() {
+            0 => 0,
+            len => (haystack.len() + 1).saturating_sub(len),
+        };
+        (0..end).filter(move |&offset| this.matches(&haystack[offset..]))
+    }
+}
+
+/// Creates a [`Signature`] from an IDA/x64dbg-style pattern string.
+///
+/// Positions are separated by whitespace and are either two hex digits or
+/// `?`/`??` for a wildcard. The pattern is parsed at compile time, so
+/// malformed or empty patterns are reported as build errors.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// let sig = pattern!("E8 ?? ?? ?? ?? 48 8B ?");
+/// assert_eq!(sig.pattern()[0], Some(0xE8));
+/// assert_eq!(sig.pattern()[7], None);
+/// ```
+///
+/// ```compile_fail
+/// # use zydis::*;
+/// let sig = pattern!("E8 XX");
+/// ```
+///
+/// ```compile_fail
+/// # use zydis::*;
+/// let sig = pattern!("  ");
+/// ```
+#[macro_export]
+macro_rules! pattern {
+    ($pattern:literal) => {{
+        const LEN: usize = $crate::__pattern_len($pattern);
+        const PATTERN: [::core::option::Option<u8>; LEN] = $crate::__parse_pattern::<LEN>($pattern);
+        const SIGNATURE: $crate::Signature<'static> = $crate::Signature::new(&PATTERN);
+        SIGNATURE
+    }};
+}
+
+/// Counts the positions of a pattern string, validating its syntax.
+///
+/// # Panics
+///
+/// If the pattern is malformed or empty. Only meant to be evaluated at
+/// compile time by [`pattern!`], turning panics into build errors.
+#[doc(hidden)]
+pub const fn __pattern_len(pattern: &str) -> usize {
+    let bytes = pattern.as_bytes();
+    let mut len = 0;
+    let mut pos = 0;
+    while let Some((_, next)) = next_position(bytes, pos) {
+        len += 1;
+        pos = next;
+    }
+    assert!(len > 0, "pattern must not be empty");
+    len
+}
+
+/// Parses a pattern string into its `N` positions.
+///
+/// # Panics
+///
+/// See [`__pattern_len`], or if `N` doesn't match the pattern length.
+#[doc(hidden)]
+pub const fn __parse_pattern<const N: usize>(pattern: &str) -> [Option<u8>; N] {
+    let bytes = pattern.as_bytes();
+    let mut out = [None; N];
+    let mut idx = 0;
+    let mut pos = 0;
+    while let Some((value, next)) = next_position(bytes, pos) {
+        out[idx] = value;
+        idx += 1;
+        pos = next;
+    }
+    assert!(idx == N, "pattern length mismatch");
+    out
+}
+
+/// Parses the position starting at or after `pos`, returning it along with
+/// the offset following it.
+#[allow(clippy::panic)]
+const fn next_position(bytes: &[u8], mut pos: usize) -> Option<(Option<u8>, usize)> {
+    while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    if pos == bytes.len() {
+        return None;
+    }
+
+    let start = pos;
+    while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+
+    let value = match pos - start {
+        1 if bytes[start] == b'?' => None,
+        2 if bytes[start] == b'?' && bytes[start + 1] == b'?' => None,
+        2 => Some(hex_digit(bytes[start]) << 4 | hex_digit(bytes[start + 1])),
+        _ => panic!("pattern positions must be two hex digits or a `?`/`??` wildcard"),
+    };
+
+    Some((value, pos))
+}
+
+#[allow(clippy::panic)]
+const fn hex_digit(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        b'A'..=b'F' => c - b'A' + 10,
+        _ => panic!("invalid hex digit in pattern"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_and_match() {
+        let sig = pattern!("  E8 ?? ? 0a\tFF ");
+        assert_eq!(
+            sig.pattern(),
+            [Some(0xE8), None, None, Some(0x0A), Some(0xFF)]
+        );
+
+        assert!(sig.matches(&[0xE8, 1, 2, 0x0A, 0xFF, 0x00]));
+        assert!(!sig.matches(&[0xE8, 1, 2, 0x0B, 0xFF]));
+        assert!(!sig.matches(&[0xE8, 1, 2, 0x0A]));
+        assert_eq!(sig.find(&[0, 0xE8, 1, 2, 0x0A, 0xFF]), Some(1));
+        assert_eq!(sig.find(&[0xE8]), None);
+    }
+
+    #[test]
+    fn empty_never_matches() {
+        let sig = crate::Signature::new(&[]);
+        assert!(!sig.matches(&[0xE8]));
+        assert_eq!(sig.find_all(&[0xE8, 0xC3]).count(), 0);
+    }
+}
@@ -0,0 +1,230 @@
+//! Reconstruction of strings built on the stack.
+
+use crate::*;
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+/// Registers addressing the stack frame.
+const FRAME_REGISTERS: [Register; 6] = [
+    Register::SP,
+    Register::ESP,
+    Register::RSP,
+    Register::BP,
+    Register::EBP,
+    Register::RBP,
+];
+
+/// A string written to the stack piece by piece, found by
+/// [`Decoder::stack_strings`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StackString {
+    /// Address of the first instruction of the sequence writing the string.
+    pub ip: u64,
+    /// The register the string is addressed relative to, e.g. `rsp`.
+    pub base: Register,
+    /// Displacement of the first byte relative to `base`.
+    pub offset: i64,
+    /// The reconstructed bytes, including terminating `NUL`s.
+    pub bytes: Vec<u8>,
+}
+
+impl StackString {
+    /// Decodes the bytes as printable ASCII or UTF-16LE text, without
+    /// terminating `NUL`s.
+    ///
+    /// Returns `None` if the bytes are neither.
+    pub fn text(&self) -> Option<String> {
+        let len = self
+            .bytes
+            .iter()
+            .rposition(|&b| b != 0)
+            .map_or(0, |idx| idx + 1);
+        let bytes = &self.bytes[..len];
+        let printable = |b: u8| b.is_ascii_graphic() || matches!(b, b' ' | b'\t' | b'\r' | b'\n');
+
+        if bytes.iter().all(|&b| printable(b)) {
+            return Some(bytes.iter().map(|&b| char::from(b)).collect());
+        }
+
+        // The high byte of the last UTF-16 unit was trimmed as `NUL`.
+        let units = bytes.chunks(2);
+        if units
+            .clone()
+            .all(|unit| printable(unit[0]) && unit.get(1).is_none_or(|&b| b == 0))
+        {
+            return Some(units.map(|unit| char::from(unit[0])).collect());
+        }
+
+        None
+    }
+}
+
+/// State of the write sequence currently being tracked.
+#[derive(Default)]
+struct Sequence {
+    /// Address of the first write.
+    ip: Option<u64>,
+    /// Bytes written relative to each frame register, by displacement.
+    bytes: Vec<(Register, BTreeMap<i64, u8>)>,
+    /// Largest enclosing registers holding known constants, with the value
+    /// and the width of the known part.
+    constants: Vec<(Register, u64, u16)>,
+}
+
+impl Decoder {
+    /// Finds strings that `code` builds on the stack with a sequence of
+    /// `mov` instructions, as commonly done by malware to hide strings from
+    /// static string extraction.
+    ///
+    /// The code is decoded linearly, starting at `base`. A sequence consists
+    /// of `mov`s of immediates, or registers previously loaded with an
+    /// immediate, to memory addressed by `rsp` or `rbp` (or narrower forms)
+    /// and a displacement. Any other instruction ends the sequence. The
+    /// bytes written by a sequence are merged, later writes overwriting
+    /// earlier ones, and every contiguous run of at least `min_length` bytes
+    /// (excluding trailing `NUL`s) that forms ASCII or UTF-16LE text is
+    /// reported.
+    ///
+    /// # Examples
+    /// ```
+    /// # use zydis::*;
+    /// static CODE: &[u8] = &[
+    ///     0xC7, 0x04, 0x24, 0x63, 0x6D, 0x64, 0x2E, // mov dword ptr [rsp], 0x2E646D63
+    ///     0x48, 0xB8, 0x65, 0x78, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, // mov rax, 0x657865
+    ///     0x48, 0x89, 0x44, 0x24, 0x04, // mov [rsp+0x04], rax
+    ///     0xC3, // ret
+    /// ];
+    ///
+    /// let strings = Decoder::new64().stack_strings(CODE, 0x1000, 4);
+    /// assert_eq!(strings.len(), 1);
+    /// assert_eq!(strings[0].ip, 0x1000);
+    /// assert_eq!((strings[0].base, strings[0].offset), (Register::RSP, 0));
+    /// assert_eq!(strings[0].text().unwrap(), "cmd.exe");
+    /// ```
+    pub fn stack_strings(&self, code: &[u8], base: u64, min_length: usize) -> Vec<StackString> {
+        let mut strings = Vec::new();
+        let mut sequence = Sequence::default();
+
+        let mut offset = 0;
+        while offset < code.len() {
+            let ip = base.wrapping_add(offset as u64);
+            let insn = self.decode_first::<VisibleOperands>(&code[offset..]);
+            let continues = match &insn {
+                Ok(Some(insn)) => sequence.apply(ip, insn),
+                _ => false,
+            };
+            if !continues {
+                sequence.finish(min_length, &mut strings);
+                // The instruction might start the next sequence.
+                if let Ok(Some(insn)) = &insn {
+                    sequence.apply(ip, insn);
+                }
+            }
+
+            offset += match insn {
+                Ok(Some(insn)) => usize::from(insn.length),
+                _ => 1,
+            };
+        }
+        sequence.finish(min_length, &mut strings);
+
+        strings
+    }
+}
+
+impl Sequence {
+    /// Applies `insn` if it's part of a sequence, returning whether it was.
+    fn apply(&mut self, ip: u64, insn: &Instruction<VisibleOperands>) -> bool {
+        if insn.mnemonic != Mnemonic::MOV {
+            return false;
+        }
+        let [dst, src] = insn.operands() else {
+            return false;
+        };
+
+        match (&dst.kind, &src.kind) {
+            (ffi::DecodedOperandKind::Reg(reg), ffi::DecodedOperandKind::Imm(imm)) => {
+                let enclosing = reg.largest_enclosing(insn.machine_mode);
+                self.constants.retain(|&(other, ..)| other != enclosing);
+                if !is_high_byte(*reg) {
+                    self.constants.push((enclosing, imm.value, dst.size));
+                }
+                true
+            }
+            (ffi::DecodedOperandKind::Mem(mem), value)
+                if FRAME_REGISTERS.contains(&mem.base) && mem.index == Register::NONE =>
+            {
+                let value = match value {
+                    ffi::DecodedOperandKind::Imm(imm) => imm.value,
+                    ffi::DecodedOperandKind::Reg(reg) if !is_high_byte(*reg) => {
+                        let enclosing = reg.largest_enclosing(insn.machine_mode);
+                        match self
+                            .constants
+                            .iter()
+                            .find(|&&(other, ..)| other == enclosing)
+                        {
+                            Some(&(_, value, width)) if width >= src.size => value,
+                            _ => return false,
+                        }
+                    }
+                    _ => return false,
+                };
+
+                self.ip.get_or_insert(ip);
+                let idx = match self.bytes.iter().position(|(base, _)| *base == mem.base) {
+                    Some(idx) => idx,
+                    None => {
+                        self.bytes.push((mem.base, BTreeMap::new()));
+                        self.bytes.len() - 1
+                    }
+                };
+                let disp = mem.disp.displacement;
+                let width = usize::from(dst.size / 8).min(8);
+                for (pos, &byte) in value.to_le_bytes()[..width].iter().enumerate() {
+                    self.bytes[idx].1.insert(disp + pos as i64, byte);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Reports the strings of the sequence and resets it.
+    fn finish(&mut self, min_length: usize, strings: &mut Vec<StackString>) {
+        let sequence = core::mem::take(self);
+        let Some(ip) = sequence.ip else {
+            return;
+        };
+
+        for (base, bytes) in sequence.bytes {
+            let mut current: Option<StackString> = None;
+            for (disp, byte) in bytes {
+                match &mut current {
+                    Some(string) if string.offset + string.bytes.len() as i64 == disp => {
+                        string.bytes.push(byte);
+                    }
+                    _ => {
+                        strings.extend(current.take().filter(|s| is_text(s, min_length)));
+                        current = Some(StackString {
+                            ip,
+                            base,
+                            offset: disp,
+                            bytes: alloc::vec![byte],
+                        });
+                    }
+                }
+            }
+            strings.extend(current.filter(|s| is_text(s, min_length)));
+        }
+    }
+}
+
+fn is_text(string: &StackString, min_length: usize) -> bool {
+    string.text().is_some_and(|text| text.len() >= min_length)
+}
+
+fn is_high_byte(reg: Register) -> bool {
+    matches!(
+        reg,
+        Register::AH | Register::CH | Register::DH | Register::BH
+    )
+}
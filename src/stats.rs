@@ -0,0 +1,143 @@
+//! Aggregate statistics over decoded instruction streams.
+
+use crate::*;
+use core::ops;
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Attributes counted as prefix usage by [`Stats`].
+const PREFIX_ATTRIBUTES: [InstructionAttributes; 19] = [
+    InstructionAttributes::HAS_REX,
+    InstructionAttributes::HAS_LOCK,
+    InstructionAttributes::HAS_REP,
+    InstructionAttributes::HAS_REPE,
+    InstructionAttributes::HAS_REPNE,
+    InstructionAttributes::HAS_BND,
+    InstructionAttributes::HAS_XACQUIRE,
+    InstructionAttributes::HAS_XRELEASE,
+    InstructionAttributes::HAS_BRANCH_NOT_TAKEN,
+    InstructionAttributes::HAS_BRANCH_TAKEN,
+    InstructionAttributes::HAS_NOTRACK,
+    InstructionAttributes::HAS_SEGMENT_CS,
+    InstructionAttributes::HAS_SEGMENT_SS,
+    InstructionAttributes::HAS_SEGMENT_DS,
+    InstructionAttributes::HAS_SEGMENT_ES,
+    InstructionAttributes::HAS_SEGMENT_FS,
+    InstructionAttributes::HAS_SEGMENT_GS,
+    InstructionAttributes::HAS_OPERANDSIZE,
+    InstructionAttributes::HAS_ADDRESSIZE,
+];
+
+/// Instruction statistics over a decoded stream.
+///
+/// # Examples
+/// ```
+/// # use zydis::*;
+/// // lock inc dword ptr [rax]; push rcx; push rdx; vzeroupper
+/// static CODE: &[u8] = &[0xF0, 0xFF, 0x00, 0x51, 0x52, 0xC5, 0xF8, 0x77];
+///
+/// let stats = Stats::collect(
+///     Decoder::new64()
+///         .decode_all::<NoOperands>(CODE, 0)
+///         .map(|r| r.unwrap().2),
+/// );
+///
+/// assert_eq!(stats.count, 4);
+/// assert_eq!(stats.mnemonics[&Mnemonic::PUSH], 2);
+/// assert_eq!(stats.prefixes[&InstructionAttributes::HAS_LOCK], 1);
+/// assert_eq!(stats.average_length(), 2.0);
+/// assert_eq!(stats.avx_ratio(), 0.25);
+/// ```
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of instructions.
+    pub count: u64,
+    /// Sum of all instruction lengths.
+    pub total_length: u64,
+    /// Number of instructions per mnemonic.
+    pub mnemonics: HashMap<Mnemonic, u64>,
+    /// Number of instructions per category.
+    pub categories: HashMap<InstructionCategory, u64>,
+    /// Number of instructions per encoding.
+    pub encodings: HashMap<InstructionEncoding, u64>,
+    /// Number of instructions using each prefix, keyed by the corresponding
+    /// `HAS_*` attribute.
+    pub prefixes: HashMap<InstructionAttributes, u64>,
+}
+
+impl Stats {
+    /// Creates empty statistics.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Computes statistics over the given instructions.
+    pub fn collect<I>(insns: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: ops::Deref<Target = ffi::DecodedInstruction>,
+    {
+        let mut stats = Self::new();
+        stats.extend(insns);
+        stats
+    }
+
+    /// Adds a single instruction.
+    pub fn add(&mut self, insn: &ffi::DecodedInstruction) {
+        self.count += 1;
+        self.total_length += u64::from(insn.length);
+        *self.mnemonics.entry(insn.mnemonic).or_default() += 1;
+        *self.categories.entry(insn.meta.category).or_default() += 1;
+        *self.encodings.entry(insn.encoding).or_default() += 1;
+
+        for prefix in PREFIX_ATTRIBUTES {
+            if insn.attributes.contains(prefix) {
+                *self.prefixes.entry(prefix).or_default() += 1;
+            }
+        }
+    }
+
+    /// Average instruction length in bytes.
+    ///
+    /// Returns `0.0` if no instructions were added.
+    pub fn average_length(&self) -> f64 {
+        self.ratio(self.total_length)
+    }
+
+    /// Share of instructions using the VEX or EVEX encoding.
+    pub fn avx_ratio(&self) -> f64 {
+        self.ratio(self.encoding_count(InstructionEncoding::VEX) + self.evex_count())
+    }
+
+    /// Share of instructions using the EVEX encoding.
+    pub fn evex_ratio(&self) -> f64 {
+        self.ratio(self.evex_count())
+    }
+
+    fn evex_count(&self) -> u64 {
+        self.encoding_count(InstructionEncoding::EVEX)
+    }
+
+    fn encoding_count(&self, encoding: InstructionEncoding) -> u64 {
+        self.encodings.get(&encoding).copied().unwrap_or(0)
+    }
+
+    fn ratio(&self, value: u64) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            value as f64 / self.count as f64
+        }
+    }
+}
+
+impl<T: ops::Deref<Target = ffi::DecodedInstruction>> Extend<T> for Stats {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for insn in iter {
+            self.add(&insn);
+        }
+    }
+}
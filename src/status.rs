@@ -175,7 +175,7 @@ macro_rules! check_string {
 
         match $expression {
             x if x.is_null() => None,
-            x => Some(CStr::from_ptr(x).to_str().unwrap()),
+            x => CStr::from_ptr(x).to_str().ok(),
         }
     }};
 }